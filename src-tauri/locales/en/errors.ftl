@@ -0,0 +1,170 @@
+## Messages of the errors returned to the frontend.
+##
+## `error-*` is the generic message of each ErrorCode. Other languages fall back to it for errors
+## that have no message of their own here.
+
+error-user-input-validation = Invalid input
+error-search-object-not-found = Not found
+error-unsupported-image = Unsupported image
+error-image-too-large = The image is too large
+error-corrupted-file = The file is damaged or not a valid archive
+error-unsupported-version = The file was made by a newer version of the app
+error-stale-data = The data was changed since
+error-expected = Expected error
+error-database = Database error
+error-incompatible-schema = Database was created by a newer version of the app
+error-migration = Failed to update the database schema
+error-resource = Resource not found
+error-io = I/O error occurred
+error-file-rw = Could not read or write the file
+error-network = Connection error occurred
+error-insufficient-privileges = Permission denied
+error-too-many-attempts = Too many failed attempts
+error-wrong-passphrase = Wrong passphrase
+error-misconfigured-privileges = Permissions are misconfigured
+error-unhandled = Unhandled error
+error-placeholder = Placeholder error
+
+## Database
+
+db-unique-violation = Value already exists
+db-not-null-violation = Value must not be empty
+db-check-violation = Check constraint violation
+db-connection-lost = Database connection lost
+db-migration-failed = Failed to update the database schema: { $details }
+db-unreachable = Database is not reachable
+db-open-failed = Could not open the database: { $details }
+db-newer-schema = This database was last opened by a newer version of NoMoreBeans (schema { $schema }). Update the app to keep using it.
+
+## Files
+
+io-not-found = File or resource not found
+io-permission-denied = Permission denied
+io-connection = Connection error occurred
+io-timed-out = I/O operation timed out
+io-unexpected-eof = Unexpected end of file
+
+## Archives and encryption
+##
+## $format is the name in the archive manifest.
+
+archive-not-this-format =
+    This is not a { $format ->
+        [nomorebeans-backup] NoMoreBeans backup
+       *[other] NoMoreBeans profile archive
+    }
+archive-newer-version =
+    This { $format ->
+        [nomorebeans-backup] backup
+       *[other] profile archive
+    } was made by a newer version of NoMoreBeans ({ $version })
+archive-damaged = The archive is damaged or was modified
+archive-too-many-files = The archive has too many files
+archive-file-too-large = { $name } is too large
+archive-manifest-unreadable = The archive manifest is unreadable
+archive-no-profile = The archive does not contain a readable profile
+archive-incomplete-lock = The archived profile has an incomplete lock
+archive-avatar-missing = The archived avatar is missing
+archive-no-free-username = Could not find a free username for { $username }
+encryption-passphrase-length = Passphrase must be between { $min } and { $max } characters
+encryption-passphrase-needed = This file is encrypted; enter its passphrase
+encryption-newer-version = This file was encrypted by a newer version of NoMoreBeans
+encryption-damaged = The encrypted file is damaged or was modified
+
+## Images
+
+image-unrecognized-format = Unrecognized image format
+image-animated = Animated images are not supported
+image-undecodable = Image could not be decoded
+image-crop-outside = Crop area must lie within the image
+image-too-many-pixels = Image must be at most { $max }x{ $max } pixels
+
+## Validation
+##
+## `validation-<field>-<rule>` is looked up first, then `validation-<rule>`. Messages take the
+## arguments of the rule, such as $min and $max.
+
+validation-failed = Validation error occurred
+validation-invalid = Invalid value
+validation-length = Invalid length
+validation-range = Value out of range
+validation-regex = Invalid format
+validation-username-length = Username must be between { $min } and { $max } characters
+validation-username-regex = Username can only contain lowercase letters and numbers
+validation-display_name-length = Display name must be between { $min } and { $max } characters
+validation-width-range =
+    Crop width must be at least { $min ->
+        [one] { $min } pixel
+       *[other] { $min } pixels
+    }
+validation-height-range =
+    Crop height must be at least { $min ->
+        [one] { $min } pixel
+       *[other] { $min } pixels
+    }
+validation-secret-length = PIN or passphrase must be between { $min } and { $max } characters
+validation-profile_picture_size = Profile picture must be less than 2MB
+validation-warn_at_percent-range = Warning must come between { $min }% and { $max }% of the allocation
+validation-limit-range = Limit must be between { $min } and { $max }
+validation-locale-regex = Locale must be a language tag such as en-US
+validation-default_locale-regex = Locale must be a language tag such as en-US
+validation-base_currency-regex = Currency must be a three-letter ISO 4217 code
+validation-default_currency-regex = Currency must be a three-letter ISO 4217 code
+validation-budget_month_start_day-range = Budget month must start between day { $min } and { $max }
+validation-database_url-length = Database URL must be between { $min } and { $max } characters
+validation-database_pool_size-range = Pool size must be between { $min } and { $max }
+validation-session_idle_minutes-range = Idle timeout must be at most { $max } minutes
+validation-trash_retention_days-range = Trash retention must be at most { $max } days
+validation-backup_keep_daily-range = At most { $max } daily backups can be kept
+validation-backup_keep_weekly-range = At most { $max } weekly backups can be kept
+validation-backup_keep_monthly-range = At most { $max } monthly backups can be kept
+validation-data_dir-absolute = Data directory must be an absolute path
+
+## Session and undo
+
+session-no-profile = No profile selected
+session-locked = Session is locked
+undo-nothing = Nothing to undo
+redo-nothing = Nothing to redo
+
+## Profiles
+
+profile-not-found = Profile not found
+profile-locked = Profile is locked
+profile-stale = The profile was changed since, so this can no longer be undone or redone
+profile-restore-needs-username = This profile needs a new username to be restored
+profile-restore-username-taken = Username { $username } is taken; pick another one to restore this profile
+profile-wrong-secret = Wrong PIN or passphrase
+profile-too-many-attempts =
+    Too many failed attempts. Try again in { $seconds ->
+        [one] { $seconds } second
+       *[other] { $seconds } seconds
+    }
+profile-pin-format = PIN must be between 4 and 8 digits
+profile-passphrase-too-short = Passphrase must be at least { $min } characters
+secret-corrupted = Stored secret is corrupted
+
+## Settings
+
+settings-postgres-needs-url = The Postgres backend needs a database URL
+settings-postgres-url-scheme = Postgres URLs must start with postgres:// or postgresql://
+settings-sqlite-url-scheme = SQLite URLs must start with sqlite:
+settings-file-unreadable = { $path } could not be read: { $reason }
+settings-file-invalid = { $path } has an invalid { $field }
+
+## Backups
+
+backup-not-found = Backup not found
+backup-newer-version = This backup was made by a newer version of NoMoreBeans. Update the app to restore it.
+backup-passphrase-needed = Backups are encrypted; enter the backup passphrase first
+backup-no-snapshot = The backup does not contain a readable snapshot
+backup-unexpected-files = The backup contains unexpected files
+
+## Notifications
+
+notification-not-found = Notification not found
+budget-alert-rule-not-found = This category has no budget alert
+
+## Subscriptions
+
+subscription-not-found = No subscription to this payee was found on this account
@@ -0,0 +1,162 @@
+## Mensagens dos erros devolvidos ao frontend. Veja en/errors.ftl.
+
+error-user-input-validation = Entrada inválida
+error-search-object-not-found = Não encontrado
+error-unsupported-image = Imagem não suportada
+error-image-too-large = A imagem é grande demais
+error-corrupted-file = O arquivo está danificado ou não é um arquivo válido
+error-unsupported-version = O arquivo foi criado por uma versão mais nova do aplicativo
+error-stale-data = Os dados foram alterados desde então
+error-expected = Erro esperado
+error-database = Erro no banco de dados
+error-incompatible-schema = O banco de dados foi criado por uma versão mais nova do aplicativo
+error-migration = Falha ao atualizar o esquema do banco de dados
+error-resource = Recurso não encontrado
+error-io = Ocorreu um erro de E/S
+error-file-rw = Não foi possível ler ou gravar o arquivo
+error-network = Ocorreu um erro de conexão
+error-insufficient-privileges = Permissão negada
+error-too-many-attempts = Tentativas malsucedidas demais
+error-wrong-passphrase = Frase secreta incorreta
+error-misconfigured-privileges = As permissões estão mal configuradas
+error-unhandled = Erro não tratado
+error-placeholder = Erro provisório
+
+## Banco de dados
+
+db-unique-violation = O valor já existe
+db-not-null-violation = O valor não pode ficar vazio
+db-check-violation = Violação de restrição de verificação
+db-connection-lost = A conexão com o banco de dados foi perdida
+db-migration-failed = Falha ao atualizar o esquema do banco de dados: { $details }
+db-unreachable = O banco de dados não está acessível
+db-open-failed = Não foi possível abrir o banco de dados: { $details }
+db-newer-schema = Este banco de dados foi aberto por último por uma versão mais nova do NoMoreBeans (esquema { $schema }). Atualize o aplicativo para continuar usando-o.
+
+## Arquivos
+
+io-not-found = Arquivo ou recurso não encontrado
+io-permission-denied = Permissão negada
+io-connection = Ocorreu um erro de conexão
+io-timed-out = A operação de E/S expirou
+io-unexpected-eof = Fim de arquivo inesperado
+
+## Arquivos compactados e criptografia
+
+archive-not-this-format =
+    Este não é { $format ->
+        [nomorebeans-backup] um backup do NoMoreBeans
+       *[other] um arquivo de perfil do NoMoreBeans
+    }
+archive-newer-version =
+    Este { $format ->
+        [nomorebeans-backup] backup
+       *[other] arquivo de perfil
+    } foi criado por uma versão mais nova do NoMoreBeans ({ $version })
+archive-damaged = O arquivo está danificado ou foi modificado
+archive-too-many-files = O arquivo contém arquivos demais
+archive-file-too-large = { $name } é grande demais
+archive-manifest-unreadable = O manifesto do arquivo não pode ser lido
+archive-no-profile = O arquivo não contém um perfil legível
+archive-incomplete-lock = O perfil arquivado tem um bloqueio incompleto
+archive-avatar-missing = A foto do perfil arquivado está faltando
+archive-no-free-username = Não foi possível encontrar um nome de usuário livre para { $username }
+encryption-passphrase-length = A frase secreta deve ter entre { $min } e { $max } caracteres
+encryption-passphrase-needed = Este arquivo está criptografado; informe a frase secreta dele
+encryption-newer-version = Este arquivo foi criptografado por uma versão mais nova do NoMoreBeans
+encryption-damaged = O arquivo criptografado está danificado ou foi modificado
+
+## Imagens
+
+image-unrecognized-format = Formato de imagem não reconhecido
+image-animated = Imagens animadas não são suportadas
+image-undecodable = Não foi possível decodificar a imagem
+image-crop-outside = A área de recorte deve ficar dentro da imagem
+image-too-many-pixels = A imagem deve ter no máximo { $max }x{ $max } pixels
+
+## Validação
+
+validation-failed = Ocorreu um erro de validação
+validation-invalid = Valor inválido
+validation-length = Comprimento inválido
+validation-range = Valor fora do intervalo
+validation-regex = Formato inválido
+validation-username-length = O nome de usuário deve ter entre { $min } e { $max } caracteres
+validation-username-regex = O nome de usuário só pode conter letras minúsculas e números
+validation-display_name-length = O nome de exibição deve ter entre { $min } e { $max } caracteres
+validation-width-range =
+    A largura do recorte deve ser de pelo menos { $min ->
+        [one] { $min } pixel
+       *[other] { $min } pixels
+    }
+validation-height-range =
+    A altura do recorte deve ser de pelo menos { $min ->
+        [one] { $min } pixel
+       *[other] { $min } pixels
+    }
+validation-secret-length = O PIN ou a frase secreta deve ter entre { $min } e { $max } caracteres
+validation-profile_picture_size = A foto de perfil deve ter menos de 2 MB
+validation-warn_at_percent-range = O aviso deve vir entre { $min }% e { $max }% do orçamento
+validation-limit-range = O limite deve estar entre { $min } e { $max }
+validation-locale-regex = O idioma deve ser uma etiqueta de idioma como pt-BR
+validation-default_locale-regex = O idioma deve ser uma etiqueta de idioma como pt-BR
+validation-base_currency-regex = A moeda deve ser um código ISO 4217 de três letras
+validation-default_currency-regex = A moeda deve ser um código ISO 4217 de três letras
+validation-budget_month_start_day-range = O mês do orçamento deve começar entre o dia { $min } e o dia { $max }
+validation-database_url-length = A URL do banco de dados deve ter entre { $min } e { $max } caracteres
+validation-database_pool_size-range = O tamanho do pool deve estar entre { $min } e { $max }
+validation-session_idle_minutes-range = O tempo de inatividade deve ser de no máximo { $max } minutos
+validation-trash_retention_days-range = A retenção da lixeira deve ser de no máximo { $max } dias
+validation-backup_keep_daily-range = No máximo { $max } backups diários podem ser mantidos
+validation-backup_keep_weekly-range = No máximo { $max } backups semanais podem ser mantidos
+validation-backup_keep_monthly-range = No máximo { $max } backups mensais podem ser mantidos
+validation-data_dir-absolute = O diretório de dados deve ser um caminho absoluto
+
+## Sessão e desfazer
+
+session-no-profile = Nenhum perfil selecionado
+session-locked = A sessão está bloqueada
+undo-nothing = Nada para desfazer
+redo-nothing = Nada para refazer
+
+## Perfis
+
+profile-not-found = Perfil não encontrado
+profile-locked = O perfil está bloqueado
+profile-stale = O perfil foi alterado desde então, então isso não pode mais ser desfeito ou refeito
+profile-restore-needs-username = Este perfil precisa de um novo nome de usuário para ser restaurado
+profile-restore-username-taken = O nome de usuário { $username } já está em uso; escolha outro para restaurar este perfil
+profile-wrong-secret = PIN ou frase secreta incorretos
+profile-too-many-attempts =
+    Tentativas malsucedidas demais. Tente novamente em { $seconds ->
+        [one] { $seconds } segundo
+       *[other] { $seconds } segundos
+    }
+profile-pin-format = O PIN deve ter entre 4 e 8 dígitos
+profile-passphrase-too-short = A frase secreta deve ter pelo menos { $min } caracteres
+secret-corrupted = O segredo armazenado está corrompido
+
+## Configurações
+
+settings-postgres-needs-url = O backend Postgres precisa de uma URL de banco de dados
+settings-postgres-url-scheme = URLs do Postgres devem começar com postgres:// ou postgresql://
+settings-sqlite-url-scheme = URLs do SQLite devem começar com sqlite:
+settings-file-unreadable = Não foi possível ler { $path }: { $reason }
+settings-file-invalid = { $path } tem um valor inválido em { $field }
+
+## Backups
+
+backup-not-found = Backup não encontrado
+backup-newer-version = Este backup foi criado por uma versão mais nova do NoMoreBeans. Atualize o aplicativo para restaurá-lo.
+backup-passphrase-needed = Os backups são criptografados; informe primeiro a frase secreta dos backups
+backup-no-snapshot = O backup não contém um instantâneo legível
+backup-unexpected-files = O backup contém arquivos inesperados
+
+## Notificações
+
+notification-not-found = Notificação não encontrada
+budget-alert-rule-not-found = Esta categoria não tem alerta de orçamento
+
+## Assinaturas
+
+subscription-not-found = Nenhuma assinatura deste beneficiário foi encontrada nesta conta
@@ -0,0 +1,21 @@
+CREATE TABLE IF NOT EXISTS budget_alert_rules (
+    profile_id INTEGER NOT NULL REFERENCES profiles(id) ON DELETE CASCADE,
+    category_id INTEGER NOT NULL, -- No foreign key yet: categories have no table of their own.
+    warn_at_percent INTEGER NOT NULL DEFAULT 80,
+    notify_desktop BOOLEAN NOT NULL DEFAULT FALSE,
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (profile_id, category_id)
+);
+
+CREATE TABLE IF NOT EXISTS notifications (
+    id SERIAL PRIMARY KEY,
+    profile_id INTEGER NOT NULL REFERENCES profiles(id) ON DELETE CASCADE,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    kind TEXT NOT NULL,
+    payload TEXT NOT NULL,
+    dedup_key TEXT NULL, -- Notifications with the same key are raised once per profile.
+    read_at TIMESTAMP NULL
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS notifications_dedup_idx ON notifications (profile_id, dedup_key);
+CREATE INDEX IF NOT EXISTS notifications_unread_idx ON notifications (profile_id, read_at);
\ No newline at end of file
@@ -0,0 +1,5 @@
+ALTER TABLE profiles
+    ADD COLUMN secret_hash TEXT NULL,
+    ADD COLUMN secret_kind TEXT NULL,
+    ADD COLUMN failed_unlock_attempts INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN unlock_blocked_until TIMESTAMP NULL;
\ No newline at end of file
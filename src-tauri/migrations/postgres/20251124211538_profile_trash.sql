@@ -0,0 +1,2 @@
+ALTER TABLE profiles
+    ADD COLUMN original_username TEXT NULL;
\ No newline at end of file
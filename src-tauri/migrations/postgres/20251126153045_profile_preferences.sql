@@ -0,0 +1,6 @@
+CREATE TABLE IF NOT EXISTS profile_preferences (
+    profile_id INTEGER PRIMARY KEY REFERENCES profiles(id) ON DELETE CASCADE,
+    version INTEGER NOT NULL,
+    preferences TEXT NOT NULL,
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
\ No newline at end of file
@@ -0,0 +1,4 @@
+ALTER TABLE profiles ADD COLUMN secret_hash TEXT NULL;
+ALTER TABLE profiles ADD COLUMN secret_kind TEXT NULL;
+ALTER TABLE profiles ADD COLUMN failed_unlock_attempts INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE profiles ADD COLUMN unlock_blocked_until TIMESTAMP NULL;
\ No newline at end of file
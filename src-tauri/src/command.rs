@@ -1,13 +1,77 @@
 use crate::{
-    services::dto::profile_dto::{CreateProfileDTO, GetProfileDTO, UpdateProfileDTO},
+    services::{
+        anomaly_service::{self, AnomalyReport},
+        dto::{
+            audit_dto::{AuditEventDTO, AuditLogFilterDTO},
+            backup_dto::BackupDTO,
+            notification_dto::{
+                BudgetAlertRuleDTO, NotificationDTO, RaisedNotificationDTO, SetBudgetAlertRuleDTO,
+            },
+            preferences_dto::{PreferencesDTO, SetPreferencesDTO},
+            profile_dto::{
+                CreateProfileDTO, DeletedProfileDTO, GetProfileDTO, RestoreProfileDTO,
+                SetProfileSecretDTO, UnlockProfileDTO, UpdateProfileDTO,
+            },
+            session_dto::SessionDTO,
+            settings_dto::{GetSettingsDTO, SettingsDTO},
+            undo_dto::UndoStatusDTO,
+        },
+        forecast_service::ScheduledTransaction,
+        notification_service::CategoryBudget,
+        profile_archive_service::ProfileArchiveService,
+        profile_service::ProfileService,
+        report_service::HistoricalTransaction,
+        subscription_service::{self, ConfirmSubscriptionDTO},
+    },
     state::AppState,
-    utils::error::mapping::ErrorResponse,
+    utils::{db::connection::ConnectionStatus, error::mapping::ErrorResponse},
 };
+use std::{future::Future, path::PathBuf};
 use tauri::State;
 
+/// Runs a command and hands its error back in the locale of the selected profile, or in the
+/// app's default one before a profile is selected.
+async fn localized<T>(
+    state: &AppState,
+    command: impl Future<Output = Result<T, ErrorResponse>>,
+) -> Result<T, ErrorResponse> {
+    match command.await {
+        Ok(value) => Ok(value),
+        Err(err) => {
+            let profile_id = state.session_service.status().profile_id;
+            Err(err.localize(&state.preferences_service.locale(profile_id).await))
+        }
+    }
+}
+
+/// Profile changes are audited as made by whoever holds the session, or by nobody before one
+/// is selected.
+fn profile_service(state: &AppState) -> ProfileService {
+    let actor = state.session_service.active_profile_id().ok();
+    state.profile_service.acting_as(actor)
+}
+
+fn profile_archive_service(state: &AppState) -> ProfileArchiveService {
+    let actor = state.session_service.active_profile_id().ok();
+    state.profile_archive_service.acting_as(actor)
+}
+
+async fn start_session(
+    state: &AppState,
+    id: i32,
+    secret: Option<String>,
+) -> Result<GetProfileDTO, ErrorResponse> {
+    let profile = state.session_service.select_profile(id, secret).await?;
+    // Each session starts with an empty history; it outlives leaving so a deleted profile can
+    // still be brought back from the picker.
+    state.undo_service.clear();
+
+    Ok(profile)
+}
+
 #[tauri::command]
 pub async fn get_profiles(state: State<'_, AppState>) -> Result<Vec<GetProfileDTO>, ErrorResponse> {
-    state.profile_service.get_all().await
+    localized(&state, state.profile_service.get_all()).await
 }
 
 #[tauri::command]
@@ -15,35 +79,703 @@ pub async fn create_profile(
     state: State<'_, AppState>,
     profile: CreateProfileDTO,
 ) -> Result<GetProfileDTO, ErrorResponse> {
-    state.profile_service.create_profile(profile).await
+    localized(&state, async {
+        let profile = profile_service(&state).create_profile(profile).await?;
+
+        // The profile is usable without them; it falls back to the same defaults.
+        if let Err(err) = state.preferences_service.create_defaults(profile.id).await {
+            log::warn!("Could not store default preferences: {}", err.message);
+        }
+
+        Ok(profile)
+    })
+    .await
 }
 
 #[tauri::command]
-pub async fn get_profile_by_id(
+pub async fn get_profile_by_username(
+    state: State<'_, AppState>,
+    username: &str,
+) -> Result<GetProfileDTO, ErrorResponse> {
+    localized(&state, state.profile_service.get_one_by_username(username)).await
+}
+
+/// With a secret this is [`unlock_profile`]; a profile without one is selected as it is.
+#[tauri::command]
+pub async fn select_profile(
     state: State<'_, AppState>,
     id: i32,
+    secret: Option<String>,
 ) -> Result<GetProfileDTO, ErrorResponse> {
-    state.profile_service.get_one_by_id(id).await
+    localized(&state, start_session(&state, id, secret)).await
 }
 
+/// Checks the PIN or passphrase of a profile, rate-limiting failures, and starts an unlocked
+/// session on it.
 #[tauri::command]
-pub async fn get_profile_by_username(
+pub async fn unlock_profile(
     state: State<'_, AppState>,
-    username: &str,
+    id: i32,
+    unlock: UnlockProfileDTO,
 ) -> Result<GetProfileDTO, ErrorResponse> {
-    state.profile_service.get_one_by_username(username).await
+    localized(&state, start_session(&state, id, Some(unlock.secret))).await
 }
 
 #[tauri::command]
-pub async fn delete_profile(state: State<'_, AppState>, id: i32) -> Result<(), ErrorResponse> {
-    state.profile_service.delete_profile(id).await
+pub fn leave_profile(state: State<'_, AppState>) {
+    state.session_service.leave_profile()
 }
 
 #[tauri::command]
-pub async fn update_profile(
+pub fn get_session(state: State<'_, AppState>) -> SessionDTO {
+    state.session_service.status()
+}
+
+/// Lets the frontend report user activity that does not reach the backend otherwise.
+#[tauri::command]
+pub async fn touch_session(state: State<'_, AppState>) -> Result<(), ErrorResponse> {
+    localized(&state, async {
+        state.session_service.active_profile_id().map(|_| ())
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn get_active_profile(
+    state: State<'_, AppState>,
+) -> Result<GetProfileDTO, ErrorResponse> {
+    localized(&state, async {
+        let id = state.session_service.active_profile_id()?;
+        state.profile_service.get_one_by_id(id).await
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn delete_profile(state: State<'_, AppState>) -> Result<(), ErrorResponse> {
+    localized(&state, async {
+        let id = state.session_service.active_profile_id()?;
+        profile_service(&state).delete_profile(id).await?;
+        state.session_service.leave_profile();
+
+        Ok(())
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn get_deleted_profiles(
+    state: State<'_, AppState>,
+) -> Result<Vec<DeletedProfileDTO>, ErrorResponse> {
+    let retention_days = state.settings_service.current().trash_retention_days;
+    localized(
+        &state,
+        state.profile_service.get_deleted_profiles(retention_days),
+    )
+    .await
+}
+
+/// Trashed profiles are not the active one, so they are named by id; acting on them still takes
+/// an unlocked session, like the other changes that reach beyond the active profile.
+#[tauri::command]
+pub async fn restore_profile(
     state: State<'_, AppState>,
     id: i32,
+    restore: RestoreProfileDTO,
+) -> Result<GetProfileDTO, ErrorResponse> {
+    localized(&state, async {
+        state.session_service.active_profile_id()?;
+        profile_service(&state).restore_profile(id, restore).await
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn purge_profile(state: State<'_, AppState>, id: i32) -> Result<(), ErrorResponse> {
+    localized(&state, async {
+        state.session_service.active_profile_id()?;
+        profile_service(&state).purge_profile(id).await
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn update_profile(
+    state: State<'_, AppState>,
     profile: UpdateProfileDTO,
 ) -> Result<GetProfileDTO, ErrorResponse> {
-    state.profile_service.update_profile(id, profile).await
+    localized(&state, async {
+        let id = state.session_service.active_profile_id()?;
+        profile_service(&state).update_profile(id, profile).await
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn set_profile_secret(
+    state: State<'_, AppState>,
+    secret: Option<SetProfileSecretDTO>,
+) -> Result<GetProfileDTO, ErrorResponse> {
+    localized(&state, async {
+        let id = state.session_service.active_profile_id()?;
+        profile_service(&state).set_profile_secret(id, secret).await
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn export_profile(
+    state: State<'_, AppState>,
+    path: PathBuf,
+    passphrase: Option<String>,
+) -> Result<(), ErrorResponse> {
+    localized(&state, async {
+        let id = state.session_service.active_profile_id()?;
+        state
+            .profile_archive_service
+            .export_profile(id, path, passphrase)
+            .await
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn import_profile(
+    state: State<'_, AppState>,
+    path: PathBuf,
+    passphrase: Option<String>,
+) -> Result<GetProfileDTO, ErrorResponse> {
+    localized(
+        &state,
+        profile_archive_service(&state).import_profile(path, passphrase),
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn get_audit_log(
+    state: State<'_, AppState>,
+    filter: Option<AuditLogFilterDTO>,
+) -> Result<Vec<AuditEventDTO>, ErrorResponse> {
+    localized(&state, async {
+        state.session_service.active_profile_id()?;
+        state
+            .audit_service
+            .get_audit_log(filter.unwrap_or_default())
+            .await
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn get_undo_status(state: State<'_, AppState>) -> Result<UndoStatusDTO, ErrorResponse> {
+    localized(&state, async {
+        state.session_service.active_profile_id()?;
+        Ok(state.undo_service.status())
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn undo(state: State<'_, AppState>) -> Result<UndoStatusDTO, ErrorResponse> {
+    localized(&state, async {
+        let id = state.session_service.active_profile_id()?;
+        state.undo_service.acting_as(Some(id)).undo().await
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn redo(state: State<'_, AppState>) -> Result<UndoStatusDTO, ErrorResponse> {
+    localized(&state, async {
+        let id = state.session_service.active_profile_id()?;
+        state.undo_service.acting_as(Some(id)).redo().await
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn get_preferences(state: State<'_, AppState>) -> Result<PreferencesDTO, ErrorResponse> {
+    localized(&state, async {
+        let id = state.session_service.active_profile_id()?;
+        state.preferences_service.get_preferences(id).await
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn set_preferences(
+    state: State<'_, AppState>,
+    preferences: SetPreferencesDTO,
+) -> Result<PreferencesDTO, ErrorResponse> {
+    localized(&state, async {
+        let id = state.session_service.active_profile_id()?;
+        state
+            .preferences_service
+            .set_preferences(id, preferences)
+            .await
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn list_backups(state: State<'_, AppState>) -> Result<Vec<BackupDTO>, ErrorResponse> {
+    localized(&state, state.backup_service.list_backups()).await
+}
+
+#[tauri::command]
+pub async fn set_backup_passphrase(
+    state: State<'_, AppState>,
+    passphrase: Option<String>,
+) -> Result<(), ErrorResponse> {
+    localized(&state, async {
+        state.session_service.active_profile_id()?;
+        state.backup_service.set_passphrase(passphrase)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn restore_backup(
+    state: State<'_, AppState>,
+    name: String,
+    passphrase: Option<String>,
+) -> Result<(), ErrorResponse> {
+    localized(&state, async {
+        let actor = state.session_service.active_profile_id()?;
+        state
+            .backup_service
+            .acting_as(Some(actor))
+            .restore_backup(name, passphrase)
+            .await?;
+
+        // The active profile may not exist in the restored data, or be someone else now.
+        state.session_service.leave_profile();
+        state.undo_service.clear();
+        Ok(())
+    })
+    .await
+}
+
+#[tauri::command]
+pub fn get_database_status(state: State<'_, AppState>) -> ConnectionStatus {
+    state.db.status()
+}
+
+#[tauri::command]
+pub fn get_settings(state: State<'_, AppState>) -> GetSettingsDTO {
+    state.settings_service.get_settings()
+}
+
+#[tauri::command]
+pub async fn update_settings(
+    state: State<'_, AppState>,
+    settings: SettingsDTO,
+) -> Result<GetSettingsDTO, ErrorResponse> {
+    localized(&state, async {
+        state.session_service.active_profile_id()?;
+        state.settings_service.update_settings(settings)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn get_notifications(
+    state: State<'_, AppState>,
+    unread_only: Option<bool>,
+) -> Result<Vec<NotificationDTO>, ErrorResponse> {
+    localized(&state, async {
+        let id = state.session_service.active_profile_id()?;
+        state
+            .notification_service
+            .get_notifications(id, unread_only.unwrap_or(false))
+            .await
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn set_notification_read(
+    state: State<'_, AppState>,
+    id: i32,
+    read: bool,
+) -> Result<NotificationDTO, ErrorResponse> {
+    localized(&state, async {
+        let profile_id = state.session_service.active_profile_id()?;
+        state
+            .notification_service
+            .set_read(profile_id, id, read)
+            .await
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn mark_all_notifications_read(state: State<'_, AppState>) -> Result<u64, ErrorResponse> {
+    localized(&state, async {
+        let id = state.session_service.active_profile_id()?;
+        state.notification_service.mark_all_read(id).await
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn get_budget_alert_rules(
+    state: State<'_, AppState>,
+) -> Result<Vec<BudgetAlertRuleDTO>, ErrorResponse> {
+    localized(&state, async {
+        let id = state.session_service.active_profile_id()?;
+        state.notification_service.get_alert_rules(id).await
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn set_budget_alert_rule(
+    state: State<'_, AppState>,
+    rule: SetBudgetAlertRuleDTO,
+) -> Result<BudgetAlertRuleDTO, ErrorResponse> {
+    localized(&state, async {
+        let id = state.session_service.active_profile_id()?;
+        state.notification_service.set_alert_rule(id, rule).await
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn delete_budget_alert_rule(
+    state: State<'_, AppState>,
+    category_id: i32,
+) -> Result<(), ErrorResponse> {
+    localized(&state, async {
+        let id = state.session_service.active_profile_id()?;
+        state
+            .notification_service
+            .delete_alert_rule(id, category_id)
+            .await
+    })
+    .await
+}
+
+/// There is no ledger to raise these on its own yet, so whoever records or imports transactions
+/// reports the totals of the categories they touched.
+#[tauri::command]
+pub async fn check_budgets(
+    state: State<'_, AppState>,
+    budgets: Vec<CategoryBudget>,
+) -> Result<Vec<RaisedNotificationDTO>, ErrorResponse> {
+    localized(&state, async {
+        let id = state.session_service.active_profile_id()?;
+        state.notification_service.check_budgets(id, &budgets).await
+    })
+    .await
+}
+
+/// There is no ledger to read the history from yet, so the caller passes the transactions the
+/// subscription was detected in. The schedule is returned rather than stored.
+#[tauri::command]
+pub async fn confirm_subscription(
+    state: State<'_, AppState>,
+    history: Vec<HistoricalTransaction>,
+    subscription: ConfirmSubscriptionDTO,
+) -> Result<ScheduledTransaction, ErrorResponse> {
+    localized(&state, async {
+        state.session_service.active_profile_id()?;
+        let today = chrono::Local::now().date_naive();
+        subscription_service::confirm_subscription(&history, today, &subscription)
+    })
+    .await
+}
+
+/// Like [`confirm_subscription`], the caller passes the history until there is a ledger to read
+/// it from.
+#[tauri::command]
+pub async fn get_anomalies(
+    state: State<'_, AppState>,
+    history: Vec<HistoricalTransaction>,
+) -> Result<AnomalyReport, ErrorResponse> {
+    localized(&state, async {
+        state.session_service.active_profile_id()?;
+        Ok(anomaly_service::detect_anomalies(&history))
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        services::{
+            dto::profile_dto::ProfileSecretKind, forecast_service::Frequency,
+            settings_service::SettingsService,
+        },
+        utils::{
+            db::{
+                connection::ConnectionManager,
+                pool::{DbBackend, DbConfig},
+            },
+            error::mapping::ErrorCode,
+        },
+    };
+    use std::{
+        fmt::Debug,
+        time::{Duration, Instant},
+    };
+    use tauri::{
+        test::{mock_app, MockRuntime},
+        App, Manager,
+    };
+
+    async fn app() -> App<MockRuntime> {
+        let dir =
+            std::env::temp_dir().join(format!("nomorebeans-commands-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let db = ConnectionManager::new(DbConfig {
+            backend: DbBackend::Sqlite,
+            url: format!("sqlite://{}", dir.join("nomorebeans.db").display()),
+            max_connections: 1,
+        });
+        db.connect().await.unwrap();
+        let settings = SettingsService::load_from(dir.join("settings.toml")).unwrap();
+
+        let app = mock_app();
+        app.manage(AppState::new(db, settings));
+        app
+    }
+
+    fn assert_refused<T: Debug>(result: Result<T, ErrorResponse>) {
+        let err = result.unwrap_err();
+        assert!(
+            matches!(err.code_name, ErrorCode::InsufficientPrivilegesError),
+            "{err:?}"
+        );
+    }
+
+    async fn refuses_app_wide_changes(app: &App<MockRuntime>) {
+        let settings = app.state::<AppState>().settings_service.current();
+
+        assert_refused(purge_profile(app.state(), 1).await);
+        assert_refused(restore_profile(app.state(), 1, RestoreProfileDTO::default()).await);
+        assert_refused(restore_backup(app.state(), "backup.zip".into(), None).await);
+        assert_refused(set_backup_passphrase(app.state(), Some("correct horse".into())).await);
+        assert_refused(get_undo_status(app.state()).await);
+        assert_refused(undo(app.state()).await);
+        assert_refused(redo(app.state()).await);
+        assert_refused(
+            update_settings(
+                app.state(),
+                SettingsDTO {
+                    trash_retention_days: 1,
+                    ..SettingsDTO::from(settings.clone())
+                },
+            )
+            .await,
+        );
+
+        assert_eq!(app.state::<AppState>().settings_service.current(), settings);
+    }
+
+    #[tokio::test]
+    async fn unlock_profile_starts_an_unlocked_session() {
+        let app = app().await;
+        let profile = create_profile(
+            app.state(),
+            CreateProfileDTO {
+                username: "alice".into(),
+                display_name: None,
+                profile_picture_bytes: None,
+                profile_picture_crop: None,
+            },
+        )
+        .await
+        .unwrap();
+        select_profile(app.state(), profile.id, None).await.unwrap();
+        set_profile_secret(
+            app.state(),
+            Some(SetProfileSecretDTO {
+                kind: ProfileSecretKind::Pin,
+                secret: "1234".into(),
+            }),
+        )
+        .await
+        .unwrap();
+        leave_profile(app.state());
+
+        let unlock = |secret: &str| UnlockProfileDTO {
+            secret: secret.into(),
+        };
+        assert_refused(unlock_profile(app.state(), profile.id, unlock("4321")).await);
+        assert_eq!(get_session(app.state()).profile_id, None);
+
+        unlock_profile(app.state(), profile.id, unlock("1234"))
+            .await
+            .unwrap();
+        let session = get_session(app.state());
+        assert_eq!(session.profile_id, Some(profile.id));
+        assert!(!session.locked);
+    }
+
+    #[tokio::test]
+    async fn check_budgets_notifies_the_active_profile() {
+        let app = app().await;
+        let budget = |spent| CategoryBudget {
+            category_id: 1,
+            month: "2025-03-01".parse().unwrap(),
+            allocated: 50_000,
+            spent,
+        };
+        assert_refused(check_budgets(app.state(), vec![budget(41_000)]).await);
+
+        let profile = create_profile(
+            app.state(),
+            CreateProfileDTO {
+                username: "alice".into(),
+                display_name: None,
+                profile_picture_bytes: None,
+                profile_picture_crop: None,
+            },
+        )
+        .await
+        .unwrap();
+        select_profile(app.state(), profile.id, None).await.unwrap();
+        set_budget_alert_rule(
+            app.state(),
+            SetBudgetAlertRuleDTO {
+                category_id: 1,
+                warn_at_percent: None,
+                notify_desktop: true,
+            },
+        )
+        .await
+        .unwrap();
+
+        let raised = check_budgets(app.state(), vec![budget(41_000)])
+            .await
+            .unwrap();
+        assert_eq!(raised.len(), 1);
+        assert!(raised[0].notify_desktop);
+
+        let notifications = get_notifications(app.state(), None).await.unwrap();
+        assert_eq!(notifications.len(), 1);
+        assert_eq!(notifications[0].payload["threshold"], "warning");
+    }
+
+    #[tokio::test]
+    async fn confirm_subscription_schedules_a_detected_one() {
+        let app = app().await;
+        let today = chrono::Local::now().date_naive();
+        let history: Vec<_> = (1..=3)
+            .map(|months_ago| HistoricalTransaction {
+                id: months_ago,
+                account_id: 1,
+                category_id: None,
+                payee: "Streaming Co".into(),
+                amount: -1_500,
+                date: today - chrono::Months::new(months_ago as u32),
+                tag_ids: Vec::new(),
+            })
+            .collect();
+        let confirmed = |payee: &str| ConfirmSubscriptionDTO {
+            account_id: 1,
+            payee: payee.into(),
+        };
+        assert_refused(
+            confirm_subscription(app.state(), history.clone(), confirmed("Streaming Co")).await,
+        );
+
+        let profile = create_profile(
+            app.state(),
+            CreateProfileDTO {
+                username: "alice".into(),
+                display_name: None,
+                profile_picture_bytes: None,
+                profile_picture_crop: None,
+            },
+        )
+        .await
+        .unwrap();
+        select_profile(app.state(), profile.id, None).await.unwrap();
+
+        let scheduled =
+            confirm_subscription(app.state(), history.clone(), confirmed("streaming co"))
+                .await
+                .unwrap();
+        assert_eq!(scheduled.amount, -1_500);
+        assert_eq!(scheduled.frequency, Some(Frequency::Monthly));
+
+        let err = confirm_subscription(app.state(), history, confirmed("Gym"))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.code_name,
+            ErrorCode::SearchObjectNotFoundError
+        ));
+    }
+
+    #[tokio::test]
+    async fn get_anomalies_flags_outliers_for_the_active_profile() {
+        let app = app().await;
+        let history: Vec<_> = [-1_000, -1_100, -900, -1_050, -950, -9_000]
+            .into_iter()
+            .zip(1..)
+            .map(|(amount, id)| HistoricalTransaction {
+                id,
+                account_id: 1,
+                category_id: Some(7),
+                payee: "Grocer".into(),
+                amount,
+                date: "2025-03-10".parse().unwrap(),
+                tag_ids: Vec::new(),
+            })
+            .collect();
+        assert_refused(get_anomalies(app.state(), history.clone()).await);
+
+        let profile = create_profile(
+            app.state(),
+            CreateProfileDTO {
+                username: "alice".into(),
+                display_name: None,
+                profile_picture_bytes: None,
+                profile_picture_crop: None,
+            },
+        )
+        .await
+        .unwrap();
+        select_profile(app.state(), profile.id, None).await.unwrap();
+
+        let report = get_anomalies(app.state(), history).await.unwrap();
+        assert!(report.marker_for(6).is_some());
+        assert!(report.marker_for(1).is_none());
+    }
+
+    #[tokio::test]
+    async fn app_wide_changes_need_a_profile() {
+        let app = app().await;
+
+        refuses_app_wide_changes(&app).await;
+    }
+
+    #[tokio::test]
+    async fn app_wide_changes_are_refused_while_locked() {
+        let app = app().await;
+        let profile = create_profile(
+            app.state(),
+            CreateProfileDTO {
+                username: "alice".into(),
+                display_name: None,
+                profile_picture_bytes: None,
+                profile_picture_crop: None,
+            },
+        )
+        .await
+        .unwrap();
+        select_profile(app.state(), profile.id, None).await.unwrap();
+
+        let state = app.state::<AppState>();
+        let idle = Instant::now() + Duration::from_secs(24 * 60 * 60);
+        assert!(state.session_service.lock_if_idle(idle));
+
+        refuses_app_wide_changes(&app).await;
+    }
 }
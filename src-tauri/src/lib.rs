@@ -1,37 +1,236 @@
 pub mod command;
 pub mod models;
+pub mod protocol;
 pub mod repositories;
 pub mod services;
 pub mod state;
 pub mod utils;
 
-use dotenvy::dotenv;
-use sqlx::PgPool;
-use std::env;
-use tauri::{async_runtime, generate_context, generate_handler, Builder};
+use std::{sync::Arc, time::Duration};
+use tauri::{
+    async_runtime, generate_context, generate_handler, AppHandle, Builder, Emitter, Manager,
+};
+use tauri_plugin_dialog::{DialogExt, MessageDialogKind};
 
-use crate::state::AppState;
+use crate::{
+    services::{session_service::SessionService, settings_service::SettingsService},
+    state::AppState,
+    utils::{
+        db::{
+            connection::{ConnectionManager, ConnectionStatus},
+            pool::DbConfig,
+        },
+        error::mapping::{ErrorCode, ErrorResponse},
+        fs::common::set_data_dir_override,
+    },
+};
+
+pub const DATABASE_STATUS_EVENT: &str = "database-status-changed";
+pub const SESSION_CHANGED_EVENT: &str = "session-changed";
+
+const PROFILE_MAINTENANCE_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+// Backups are daily; checking more often catches up soon after a sleep or a late start.
+const BACKUP_CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Loads the settings and connects to the database if possible. Only errors that retrying cannot
+/// fix stop the app; an unreachable database is left to [`ConnectionManager::supervise`].
+async fn init_state() -> Result<AppState, ErrorResponse> {
+    let settings_service = SettingsService::load()?;
+    let settings = settings_service.running();
+
+    log::set_max_level(settings.log_level.into());
+    set_data_dir_override(settings.data_dir.clone());
+
+    let config = DbConfig::from_settings(&settings.database)?;
+    let db = ConnectionManager::new(config);
+
+    if let Err(err) = db.connect().await {
+        match err.code_name {
+            ErrorCode::IncompatibleSchemaError | ErrorCode::MigrationError => return Err(err),
+            _ => log::warn!("Starting without a database connection: {}", err.message),
+        }
+    }
+
+    Ok(AppState::new(db, settings_service))
+}
+
+fn watch_database(app: &AppHandle, db: Arc<ConnectionManager>) {
+    let handle = app.clone();
+    let mut status = db.subscribe();
+
+    async_runtime::spawn(async move {
+        while status.changed().await.is_ok() {
+            let current = *status.borrow_and_update();
+            let _ = handle.emit(DATABASE_STATUS_EVENT, current);
+        }
+    });
+
+    async_runtime::spawn(db.supervise());
+}
+
+/// Empties expired trash and sweeps avatar files left behind by earlier runs, once the database
+/// is reachable and then daily for as long as the app stays open.
+fn maintain_profiles(state: &AppState) {
+    let profile_service = state.profile_service.clone();
+    let settings_service = state.settings_service.clone();
+    let mut status = state.db.subscribe();
+
+    async_runtime::spawn(async move {
+        loop {
+            if status
+                .wait_for(|status| *status == ConnectionStatus::Connected)
+                .await
+                .is_err()
+            {
+                return;
+            }
+
+            let retention_days = settings_service.current().trash_retention_days;
+            match profile_service.purge_expired(retention_days).await {
+                Ok(0) => {}
+                Ok(purged) => log::info!("Purged {purged} profiles from the trash"),
+                Err(err) => log::warn!("Could not empty the trash: {}", err.message),
+            }
+
+            match profile_service.collect_orphaned_avatars().await {
+                Ok(0) => {}
+                Ok(removed) => log::info!("Removed {removed} orphaned avatar files"),
+                Err(err) => log::warn!("Could not clean up orphaned avatars: {}", err.message),
+            }
+
+            tokio::time::sleep(PROFILE_MAINTENANCE_INTERVAL).await;
+        }
+    });
+}
+
+/// Takes the daily backup and rotates old ones while the database is reachable.
+fn schedule_backups(state: &AppState) {
+    let backup_service = state.backup_service.clone();
+    let mut status = state.db.subscribe();
+
+    async_runtime::spawn(async move {
+        loop {
+            if status
+                .wait_for(|status| *status == ConnectionStatus::Connected)
+                .await
+                .is_err()
+            {
+                return;
+            }
+
+            match backup_service.run_scheduled().await {
+                Ok(Some(backup)) => log::info!("Backed up all data to {}", backup.name),
+                Ok(None) => {}
+                Err(err) => log::warn!("Could not back up: {}", err.message),
+            }
+
+            tokio::time::sleep(BACKUP_CHECK_INTERVAL).await;
+        }
+    });
+}
+
+fn watch_session(app: &AppHandle, session: SessionService) {
+    let handle = app.clone();
+    let mut status = session.subscribe();
+
+    async_runtime::spawn(async move {
+        while status.changed().await.is_ok() {
+            let current = status.borrow_and_update().clone();
+            let _ = handle.emit(SESSION_CHANGED_EVENT, current);
+        }
+    });
+
+    async_runtime::spawn(session.supervise_idle());
+}
+
+fn show_startup_error(app: &AppHandle, err: ErrorResponse) {
+    log::error!("Startup failed: {err:?}");
+
+    for window in app.webview_windows().values() {
+        let _ = window.hide();
+    }
+
+    let handle = app.clone();
+    app.dialog()
+        .message(format!("{}\n\nError code: {}", err.message, err.code))
+        .title("NoMoreBeans could not start")
+        .kind(MessageDialogKind::Error)
+        .show(move |_| handle.exit(1));
+}
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    dotenv().ok();
-    async_runtime::block_on(async {
-        let pool = PgPool::connect(&env::var("DATABASE_URL").expect("DATABASE_URL not set"))
-            .await
-            .expect("Failed to connect to database");
-
-        Builder::default()
-            .manage(AppState::new(pool))
-            .plugin(tauri_plugin_fs::init())
-            .invoke_handler(generate_handler![
-                command::get_profiles,
-                command::create_profile,
-                command::get_profile_by_id,
-                command::get_profile_by_username,
-                command::delete_profile,
-                command::update_profile
-            ])
-            .run(generate_context!())
-            .expect("error while running tauri application");
-    });
+    // Development only; desktop users configure the app through settings.toml.
+    #[cfg(debug_assertions)]
+    dotenvy::dotenv().ok();
+
+    Builder::default()
+        .plugin(
+            // Filtering happens through `log::set_max_level` so the level can change at runtime.
+            tauri_plugin_log::Builder::new()
+                .level(log::LevelFilter::Trace)
+                .build(),
+        )
+        .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_fs::init())
+        .register_asynchronous_uri_scheme_protocol(
+            protocol::AVATAR_SCHEME,
+            protocol::avatar_protocol,
+        )
+        .setup(|app| {
+            match async_runtime::block_on(init_state()) {
+                Ok(state) => {
+                    watch_database(app.handle(), state.db.clone());
+                    watch_session(app.handle(), state.session_service.clone());
+                    maintain_profiles(&state);
+                    schedule_backups(&state);
+                    app.manage(state);
+                }
+                Err(err) => show_startup_error(app.handle(), err),
+            }
+
+            Ok(())
+        })
+        .invoke_handler(generate_handler![
+            command::get_profiles,
+            command::create_profile,
+            command::get_profile_by_username,
+            command::select_profile,
+            command::unlock_profile,
+            command::leave_profile,
+            command::get_session,
+            command::touch_session,
+            command::get_active_profile,
+            command::delete_profile,
+            command::get_deleted_profiles,
+            command::restore_profile,
+            command::purge_profile,
+            command::update_profile,
+            command::set_profile_secret,
+            command::export_profile,
+            command::import_profile,
+            command::get_audit_log,
+            command::get_undo_status,
+            command::undo,
+            command::redo,
+            command::get_preferences,
+            command::set_preferences,
+            command::list_backups,
+            command::set_backup_passphrase,
+            command::restore_backup,
+            command::get_database_status,
+            command::get_settings,
+            command::update_settings,
+            command::get_notifications,
+            command::set_notification_read,
+            command::mark_all_notifications_read,
+            command::get_budget_alert_rules,
+            command::set_budget_alert_rule,
+            command::delete_budget_alert_rule,
+            command::check_budgets,
+            command::confirm_subscription,
+            command::get_anomalies
+        ])
+        .run(generate_context!())
+        .expect("error while running tauri application");
 }
@@ -1 +1,2 @@
+pub mod patch;
 pub mod v1;
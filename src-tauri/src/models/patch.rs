@@ -0,0 +1,100 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use validator::{AsRegex, Validate, ValidateLength, ValidateRegex, ValidationErrors};
+
+/// One field of a partial update: left out, explicitly `null`, or given a value.
+///
+/// Declare such fields with `#[serde(default)]` so a missing key becomes [`Patch::Missing`].
+/// Validators only look at [`Patch::Value`]; columns that cannot be cleared stay `Option`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Patch<T> {
+    #[default]
+    Missing,
+    Null,
+    Value(T),
+}
+
+impl<T> Patch<T> {
+    pub fn is_missing(&self) -> bool {
+        matches!(self, Patch::Missing)
+    }
+
+    pub fn value(&self) -> Option<&T> {
+        match self {
+            Patch::Value(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Patch<U> {
+        match self {
+            Patch::Missing => Patch::Missing,
+            Patch::Null => Patch::Null,
+            Patch::Value(value) => Patch::Value(f(value)),
+        }
+    }
+
+    /// `None` to leave the column alone, `Some(new)` to overwrite it, `NULL` included.
+    pub fn into_change(self) -> Option<Option<T>> {
+        match self {
+            Patch::Missing => None,
+            Patch::Null => Some(None),
+            Patch::Value(value) => Some(Some(value)),
+        }
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Patch<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(match Option::<T>::deserialize(deserializer)? {
+            Some(value) => Patch::Value(value),
+            None => Patch::Null,
+        })
+    }
+}
+
+// Validation errors carry the offending value, which needs this.
+impl<T: Serialize> Serialize for Patch<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.value().serialize(serializer)
+    }
+}
+
+impl<T: ValidateLength<u64>> ValidateLength<u64> for Patch<T> {
+    fn length(&self) -> Option<u64> {
+        self.value().and_then(T::length)
+    }
+}
+
+impl<T: ValidateRegex> ValidateRegex for Patch<T> {
+    fn validate_regex(&self, regex: impl AsRegex) -> bool {
+        self.value().is_none_or(|value| value.validate_regex(regex))
+    }
+}
+
+impl<T: Validate> Validate for Patch<T> {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        self.value().map_or(Ok(()), T::validate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct Body {
+        #[serde(default)]
+        name: Patch<String>,
+    }
+
+    fn parse(json: &str) -> Patch<String> {
+        serde_json::from_str::<Body>(json).unwrap().name
+    }
+
+    #[test]
+    fn deserializes_all_three_states() {
+        assert_eq!(parse("{}"), Patch::Missing);
+        assert_eq!(parse(r#"{"name":null}"#), Patch::Null);
+        assert_eq!(parse(r#"{"name":"bean"}"#), Patch::Value("bean".into()));
+    }
+}
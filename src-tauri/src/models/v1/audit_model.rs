@@ -0,0 +1,222 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sqlx::FromRow;
+
+#[derive(FromRow, Debug, Clone)]
+pub struct AuditEventModel {
+    pub id: i32,
+    pub created_at: chrono::NaiveDateTime,
+    pub actor_profile_id: Option<i32>, // None for changes the app makes on its own, such as emptying the trash.
+    pub entity: String,
+    pub entity_id: i32,
+    pub operation: String,
+    pub old_values: Option<String>, // JSON object of the fields that changed, as they were.
+    pub new_values: Option<String>, // JSON object of the same fields, as they are now.
+}
+
+/// Which events to read; every condition that is set must match. Newest first, `limit` at a time.
+#[derive(Debug, Clone, Default)]
+pub struct AuditFilterModel {
+    pub actor_profile_id: Option<i32>,
+    pub entity: Option<AuditEntity>,
+    pub entity_id: Option<i32>,
+    pub operation: Option<AuditOperation>,
+    pub from: Option<chrono::NaiveDateTime>,
+    pub until: Option<chrono::NaiveDateTime>, // Exclusive.
+    pub before_id: Option<i32>,               // Continues a page that ended at this event.
+    pub limit: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditEntity {
+    Profile,
+    Database, // All app data at once, as a backup restore replaces it. Its `entity_id` is 0.
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditOperation {
+    Create,
+    Update,
+    Delete,  // Soft delete, such as moving a profile to the trash.
+    Restore, // Undoes a soft delete.
+    Purge,   // Hard delete.
+}
+
+/// Rows whose changes are written to the audit log.
+pub trait Audited: Serialize {
+    const ENTITY: AuditEntity;
+    /// Fields kept out of the log: secrets, and bookkeeping that changes with every write.
+    const UNAUDITED_FIELDS: &'static [&'static str];
+
+    fn audit_id(&self) -> i32;
+}
+
+impl AuditEntity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditEntity::Profile => "profile",
+            AuditEntity::Database => "database",
+        }
+    }
+}
+
+impl AuditOperation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditOperation::Create => "create",
+            AuditOperation::Update => "update",
+            AuditOperation::Delete => "delete",
+            AuditOperation::Restore => "restore",
+            AuditOperation::Purge => "purge",
+        }
+    }
+}
+
+impl AuditFilterModel {
+    pub fn matches(&self, event: &AuditEventModel) -> bool {
+        self.actor_profile_id
+            .is_none_or(|actor| event.actor_profile_id == Some(actor))
+            && self
+                .entity
+                .is_none_or(|entity| event.entity == entity.as_str())
+            && self.entity_id.is_none_or(|id| event.entity_id == id)
+            && self
+                .operation
+                .is_none_or(|operation| event.operation == operation.as_str())
+            && self.from.is_none_or(|from| event.created_at >= from)
+            && self.until.is_none_or(|until| event.created_at < until)
+            && self.before_id.is_none_or(|id| event.id < id)
+    }
+}
+
+impl AuditEventModel {
+    /// Describes the change from `before` to `after` (None when the row did not or no longer
+    /// exists), keeping only the fields that differ. Returns None when nothing audited changed.
+    pub fn change<T: Audited>(
+        actor_profile_id: Option<i32>,
+        operation: AuditOperation,
+        before: Option<&T>,
+        after: Option<&T>,
+    ) -> Option<Self> {
+        let entity_id = after.or(before)?.audit_id();
+        let mut old_values = before.map(audited_fields).unwrap_or_default();
+        let mut new_values = after.map(audited_fields).unwrap_or_default();
+
+        if before.is_some() && after.is_some() {
+            let unchanged: Vec<String> = old_values
+                .iter()
+                .filter(|(field, value)| new_values.get(*field) == Some(*value))
+                .map(|(field, _)| field.clone())
+                .collect();
+            for field in unchanged {
+                old_values.remove(&field);
+                new_values.remove(&field);
+            }
+
+            if old_values.is_empty() && new_values.is_empty() {
+                return None;
+            }
+        }
+
+        let to_json = |values: Map<String, Value>| {
+            (!values.is_empty()).then(|| Value::Object(values).to_string())
+        };
+
+        Some(Self {
+            id: 0,
+            created_at: chrono::Utc::now().naive_utc(),
+            actor_profile_id,
+            entity: T::ENTITY.as_str().to_string(),
+            entity_id,
+            operation: operation.as_str().to_string(),
+            old_values: to_json(old_values),
+            new_values: to_json(new_values),
+        })
+    }
+
+    /// Describes an operation on the database as a whole, which has no row to diff.
+    pub fn database(
+        actor_profile_id: Option<i32>,
+        operation: AuditOperation,
+        old_values: Value,
+        new_values: Value,
+    ) -> Self {
+        Self {
+            id: 0,
+            created_at: chrono::Utc::now().naive_utc(),
+            actor_profile_id,
+            entity: AuditEntity::Database.as_str().to_string(),
+            entity_id: 0,
+            operation: operation.as_str().to_string(),
+            old_values: Some(old_values.to_string()),
+            new_values: Some(new_values.to_string()),
+        }
+    }
+}
+
+fn audited_fields<T: Audited>(row: &T) -> Map<String, Value> {
+    let Ok(Value::Object(mut fields)) = serde_json::to_value(row) else {
+        return Map::new();
+    };
+
+    for field in T::UNAUDITED_FIELDS {
+        fields.remove(*field);
+    }
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Row {
+        id: i32,
+        name: &'static str,
+        secret: &'static str,
+    }
+
+    impl Audited for Row {
+        const ENTITY: AuditEntity = AuditEntity::Profile;
+        const UNAUDITED_FIELDS: &'static [&'static str] = &["secret"];
+
+        fn audit_id(&self) -> i32 {
+            self.id
+        }
+    }
+
+    #[test]
+    fn change_keeps_only_changed_audited_fields() {
+        let before = Row {
+            id: 1,
+            name: "alice",
+            secret: "1234",
+        };
+        let after = Row {
+            name: "alicia",
+            secret: "5678",
+            ..before
+        };
+
+        let event =
+            AuditEventModel::change(Some(1), AuditOperation::Update, Some(&before), Some(&after))
+                .unwrap();
+
+        assert_eq!(event.old_values.as_deref(), Some(r#"{"name":"alice"}"#));
+        assert_eq!(event.new_values.as_deref(), Some(r#"{"name":"alicia"}"#));
+
+        let unchanged = Row {
+            secret: "0000",
+            ..after
+        };
+        let event = AuditEventModel::change(
+            Some(1),
+            AuditOperation::Update,
+            Some(&after),
+            Some(&unchanged),
+        );
+        assert!(event.is_none());
+    }
+}
@@ -0,0 +1,27 @@
+use crate::models::v1::{
+    notification_model::{BudgetAlertRuleModel, NotificationModel},
+    preferences_model::ProfilePreferencesModel,
+    profile_model::ProfileModel,
+};
+use serde::{Deserialize, Serialize};
+
+/// Every row of app data, as one backup holds it. Rows keep their ids so references between tables
+/// survive a restore.
+///
+/// The audit log is left out on purpose. It is append-only, and its events from before a restore
+/// still describe what happened to the data that was replaced, so it is kept as is; the restore
+/// itself is recorded in it.
+///
+/// Backups outlive the schema they were taken under: a column added later must be an `Option` or
+/// carry a `#[serde(default)]` so older backups still restore.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DatabaseSnapshotModel {
+    pub schema_version: i64, // Newest migration applied when the snapshot was taken.
+    pub profiles: Vec<ProfileModel>,
+    pub preferences: Vec<ProfilePreferencesModel>,
+    #[serde(default)]
+    pub budget_alert_rules: Vec<BudgetAlertRuleModel>,
+    #[serde(default)]
+    pub notifications: Vec<NotificationModel>,
+}
@@ -1 +1,6 @@
+pub mod audit_model;
+pub mod backup_model;
+pub mod notification_model;
+pub mod preferences_model;
 pub mod profile_model;
+pub mod settings_model;
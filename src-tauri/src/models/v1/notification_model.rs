@@ -0,0 +1,40 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+pub const DEFAULT_WARN_AT_PERCENT: i32 = 80;
+
+#[derive(FromRow, Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationModel {
+    pub id: i32,
+    pub profile_id: i32,
+    pub created_at: chrono::NaiveDateTime,
+    pub kind: String,
+    pub payload: String, // JSON the frontend words in the profile's language, by `kind`.
+    pub dedup_key: Option<String>,
+    pub read_at: Option<chrono::NaiveDateTime>, // None while unread.
+}
+
+/// Warns about spending in one category of a profile's budget. Reaching the whole allocation is
+/// always reported; `warn_at_percent` adds an earlier warning.
+#[derive(FromRow, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BudgetAlertRuleModel {
+    pub profile_id: i32,
+    pub category_id: i32,
+    pub warn_at_percent: i32,
+    pub notify_desktop: bool,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationKind {
+    BudgetThreshold,
+}
+
+impl NotificationKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotificationKind::BudgetThreshold => "budget_threshold",
+        }
+    }
+}
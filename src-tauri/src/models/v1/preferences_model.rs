@@ -0,0 +1,110 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// Bump when a stored preference is renamed or changes meaning, and teach
+/// [`PreferencesModel::from_stored`] to upgrade the older shape. New preferences only need a
+/// default.
+pub const PREFERENCES_VERSION: i32 = 1;
+
+#[derive(FromRow, Debug, Clone, Serialize, Deserialize)]
+pub struct ProfilePreferencesModel {
+    pub profile_id: i32,
+    pub version: i32,
+    pub preferences: String, // JSON of `PreferencesModel`, in the shape of `version`.
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+/// Preferences of one profile. Missing keys fall back to their defaults and unknown ones are kept
+/// in `extra`, so a newer app's preferences survive a round trip through an older one.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PreferencesModel {
+    pub theme: Theme,
+    pub layout_mode: LayoutMode,
+    pub locale: String,
+    pub date_format: DateFormat,
+    pub base_currency: String,
+    pub first_day_of_week: Weekday,
+    pub budget_month_start_day: u8,
+
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    System,
+    Light,
+    Dark,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LayoutMode {
+    Grid,
+    List,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DateFormat {
+    #[serde(rename = "locale")]
+    Locale, // Whatever the profile's locale uses.
+    #[serde(rename = "yyyy-mm-dd")]
+    YearMonthDay,
+    #[serde(rename = "dd/mm/yyyy")]
+    DayMonthYear,
+    #[serde(rename = "mm/dd/yyyy")]
+    MonthDayYear,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Weekday {
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
+
+impl PreferencesModel {
+    /// Reads preferences stored as `version`. Versions from a newer app are read as far as this
+    /// one understands them.
+    pub fn from_stored(
+        version: i32,
+        preferences: &str,
+        defaults: PreferencesModel,
+    ) -> Result<Self, serde_json::Error> {
+        if version > PREFERENCES_VERSION {
+            log::warn!("Preferences were saved by a newer version ({version}) of the app");
+        }
+
+        let stored: serde_json::Map<String, serde_json::Value> = serde_json::from_str(preferences)?;
+
+        // Keys that are absent take the caller's defaults rather than the static ones below.
+        let mut merged = serde_json::to_value(defaults)?;
+        if let serde_json::Value::Object(merged) = &mut merged {
+            merged.extend(stored);
+        }
+
+        serde_json::from_value(merged)
+    }
+}
+
+impl Default for PreferencesModel {
+    fn default() -> Self {
+        Self {
+            theme: Theme::System,
+            layout_mode: LayoutMode::List,
+            locale: "en-US".into(),
+            date_format: DateFormat::Locale,
+            base_currency: "USD".into(),
+            first_day_of_week: Weekday::Sunday,
+            budget_month_start_day: 1,
+            extra: serde_json::Map::new(),
+        }
+    }
+}
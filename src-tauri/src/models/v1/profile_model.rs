@@ -1,6 +1,8 @@
+use crate::models::v1::audit_model::{AuditEntity, AuditOperation, Audited};
+use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 
-#[derive(FromRow, Debug)]
+#[derive(FromRow, Debug, Clone, Serialize, Deserialize)]
 pub struct ProfileModel {
     pub id: i32,
 
@@ -9,6 +11,38 @@ pub struct ProfileModel {
     pub deleted_at: Option<chrono::NaiveDateTime>,
 
     pub username: String,
+    pub original_username: Option<String>, // Set while in the trash, where `username` is `deleted:<id>`.
     pub display_name: Option<String>,
     pub profile_picture_url: Option<String>,
+
+    pub secret_hash: Option<String>, // Argon2id PHC string.
+    pub secret_kind: Option<String>,
+    pub failed_unlock_attempts: i32,
+    pub unlock_blocked_until: Option<chrono::NaiveDateTime>,
+}
+
+impl ProfileModel {
+    /// How the row went from `self` to `after`: into or out of the trash, or an edit in place.
+    pub fn operation_to(&self, after: &ProfileModel) -> AuditOperation {
+        match (self.deleted_at, after.deleted_at) {
+            (None, Some(_)) => AuditOperation::Delete,
+            (Some(_), None) => AuditOperation::Restore,
+            _ => AuditOperation::Update,
+        }
+    }
+}
+
+impl Audited for ProfileModel {
+    const ENTITY: AuditEntity = AuditEntity::Profile;
+    // Locking and unlocking show up through `secret_kind`; the hash itself never does.
+    const UNAUDITED_FIELDS: &'static [&'static str] = &[
+        "updated_at",
+        "secret_hash",
+        "failed_unlock_attempts",
+        "unlock_blocked_until",
+    ];
+
+    fn audit_id(&self) -> i32 {
+        self.id
+    }
 }
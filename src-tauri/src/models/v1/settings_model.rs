@@ -0,0 +1,117 @@
+use crate::utils::db::pool::DbBackend;
+use serde::{Deserialize, Serialize};
+use std::{path::PathBuf, str::FromStr};
+
+pub const DEFAULT_POOL_SIZE: u32 = 5;
+pub const DEFAULT_SESSION_IDLE_MINUTES: u32 = 15;
+pub const DEFAULT_TRASH_RETENTION_DAYS: u32 = 30;
+pub const MAX_BACKUPS_KEPT: u32 = 365;
+
+/// Contents of `settings.toml`. Missing keys fall back to their defaults, so older files keep
+/// loading after new settings are added.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SettingsModel {
+    pub database: DatabaseSettingsModel,
+    pub log_level: LogLevel,
+    pub default_locale: String,
+    pub default_currency: String,
+    pub session_idle_minutes: u32, // 0 keeps sessions unlocked until the profile is left.
+    pub trash_retention_days: u32, // 0 keeps deleted profiles until they are purged by hand.
+    pub backups: BackupSettingsModel,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data_dir: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DatabaseSettingsModel {
+    pub backend: DbBackend,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>, // None means the embedded SQLite file in the data dir.
+    pub pool_size: u32,
+}
+
+/// How many scheduled backups to keep: the newest of each of the last `daily` days, `weekly` weeks
+/// and `monthly` months. All zero turns scheduled backups off.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackupSettingsModel {
+    pub daily: u32,
+    pub weekly: u32,
+    pub monthly: u32,
+    pub encrypted: bool, // Never write a backup without a passphrase; the passphrase is not stored.
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl Default for SettingsModel {
+    fn default() -> Self {
+        Self {
+            database: DatabaseSettingsModel::default(),
+            log_level: LogLevel::Info,
+            default_locale: "en-US".into(),
+            default_currency: "USD".into(),
+            session_idle_minutes: DEFAULT_SESSION_IDLE_MINUTES,
+            trash_retention_days: DEFAULT_TRASH_RETENTION_DAYS,
+            backups: BackupSettingsModel::default(),
+            data_dir: None,
+        }
+    }
+}
+
+impl Default for DatabaseSettingsModel {
+    fn default() -> Self {
+        Self {
+            backend: DbBackend::Sqlite,
+            url: None,
+            pool_size: DEFAULT_POOL_SIZE,
+        }
+    }
+}
+
+impl Default for BackupSettingsModel {
+    fn default() -> Self {
+        Self {
+            daily: 7,
+            weekly: 4,
+            monthly: 6,
+            encrypted: false,
+        }
+    }
+}
+
+impl FromStr for LogLevel {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "error" => Ok(LogLevel::Error),
+            "warn" => Ok(LogLevel::Warn),
+            "info" => Ok(LogLevel::Info),
+            "debug" => Ok(LogLevel::Debug),
+            "trace" => Ok(LogLevel::Trace),
+            _ => Err(format!("Unknown log level: {value}")),
+        }
+    }
+}
+
+impl From<LogLevel> for log::LevelFilter {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Error => log::LevelFilter::Error,
+            LogLevel::Warn => log::LevelFilter::Warn,
+            LogLevel::Info => log::LevelFilter::Info,
+            LogLevel::Debug => log::LevelFilter::Debug,
+            LogLevel::Trace => log::LevelFilter::Trace,
+        }
+    }
+}
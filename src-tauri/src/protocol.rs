@@ -0,0 +1,96 @@
+use crate::state::AppState;
+use tauri::{
+    async_runtime,
+    http::{header, Request, Response, StatusCode},
+    AppHandle, Manager, Runtime, UriSchemeContext, UriSchemeResponder,
+};
+
+pub const AVATAR_SCHEME: &str = "avatar";
+
+/// Serves profile pictures as `avatar://localhost/<profile-id>?size=<px>`, so DTOs carry a URL
+/// instead of the image itself. Files are read off the main thread and answered with an ETag;
+/// their names are unique per upload, which makes the name a stable tag.
+pub fn avatar_protocol<R: Runtime>(
+    ctx: UriSchemeContext<'_, R>,
+    request: Request<Vec<u8>>,
+    responder: UriSchemeResponder,
+) {
+    let app = ctx.app_handle().clone();
+
+    async_runtime::spawn(async move {
+        responder.respond(serve_avatar(&app, &request).await);
+    });
+}
+
+async fn serve_avatar<R: Runtime>(
+    app: &AppHandle<R>,
+    request: &Request<Vec<u8>>,
+) -> Response<Vec<u8>> {
+    let Some(state) = app.try_state::<AppState>() else {
+        return empty_response(StatusCode::SERVICE_UNAVAILABLE);
+    };
+
+    let Ok(profile_id) = request.uri().path().trim_matches('/').parse::<i32>() else {
+        return empty_response(StatusCode::BAD_REQUEST);
+    };
+
+    let size = request.uri().query().and_then(|query| {
+        query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(key, _)| *key == "size")
+            .and_then(|(_, value)| value.parse::<u32>().ok())
+    });
+
+    let path = match state.profile_service.avatar_path(profile_id, size).await {
+        Ok(Some(path)) => path,
+        Ok(None) => return empty_response(StatusCode::NOT_FOUND),
+        Err(err) => {
+            log::warn!(
+                "Could not look up avatar of profile {profile_id}: {}",
+                err.message
+            );
+            return empty_response(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    let etag = format!(
+        "\"{}\"",
+        path.file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default()
+    );
+
+    let if_none_match = request
+        .headers()
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok());
+    if if_none_match == Some(etag.as_str()) {
+        return cached_response(StatusCode::NOT_MODIFIED, &etag, Vec::new());
+    }
+
+    match tokio::fs::read(&path).await {
+        Ok(bytes) => cached_response(StatusCode::OK, &etag, bytes),
+        Err(err) => {
+            log::warn!("Could not read avatar {}: {err}", path.display());
+            empty_response(StatusCode::NOT_FOUND)
+        }
+    }
+}
+
+fn cached_response(status: StatusCode, etag: &str, body: Vec<u8>) -> Response<Vec<u8>> {
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "image/webp")
+        .header(header::ETAG, etag)
+        .header(header::CACHE_CONTROL, "no-cache")
+        .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+        .body(body)
+        .unwrap_or_else(|_| empty_response(StatusCode::INTERNAL_SERVER_ERROR))
+}
+
+fn empty_response(status: StatusCode) -> Response<Vec<u8>> {
+    let mut response = Response::new(Vec::new());
+    *response.status_mut() = status;
+    response
+}
@@ -0,0 +1,57 @@
+use async_trait::async_trait;
+use std::sync::Mutex;
+
+use crate::{
+    models::v1::audit_model::{AuditEventModel, AuditFilterModel},
+    repositories::v1::audit_repository::AuditRepository,
+    utils::error::mapping::ErrorResponse,
+};
+
+/// Keeps audit events in memory. The other in-memory repositories share one and [`record`] into
+/// it where the SQL ones write to `audit_events`.
+///
+/// [`record`]: InMemoryAuditRepository::record
+#[derive(Default)]
+pub struct InMemoryAuditRepository {
+    events: Mutex<Vec<AuditEventModel>>,
+}
+
+impl InMemoryAuditRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&self, event: Option<AuditEventModel>) {
+        let Some(mut event) = event else {
+            return;
+        };
+        let mut events = self
+            .events
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        event.id = events.len() as i32 + 1;
+        events.push(event);
+    }
+}
+
+#[async_trait]
+impl AuditRepository for InMemoryAuditRepository {
+    async fn get_events(
+        &self,
+        filter: AuditFilterModel,
+    ) -> Result<Vec<AuditEventModel>, ErrorResponse> {
+        let events = self
+            .events
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        Ok(events
+            .iter()
+            .rev()
+            .filter(|event| filter.matches(event))
+            .take(filter.limit as usize)
+            .cloned()
+            .collect())
+    }
+}
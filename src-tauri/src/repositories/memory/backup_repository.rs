@@ -0,0 +1,76 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+
+use crate::{
+    models::v1::{audit_model::AuditEventModel, backup_model::DatabaseSnapshotModel},
+    repositories::{
+        memory::{
+            audit_repository::InMemoryAuditRepository,
+            notification_repository::InMemoryNotificationRepository,
+            preferences_repository::InMemoryPreferencesRepository,
+            profile_repository::InMemoryProfileRepository,
+        },
+        v1::backup_repository::BackupRepository,
+    },
+    utils::error::mapping::ErrorResponse,
+};
+
+/// Snapshots and replaces the rows of the other in-memory repositories. There are no migrations
+/// in memory, so the schema version is whatever the test sets. Restores are recorded in `audit`.
+pub struct InMemoryBackupRepository {
+    profiles: Arc<InMemoryProfileRepository>,
+    preferences: Arc<InMemoryPreferencesRepository>,
+    notifications: Arc<InMemoryNotificationRepository>,
+    audit: Arc<InMemoryAuditRepository>,
+    schema_version: i64,
+}
+
+impl InMemoryBackupRepository {
+    pub fn new(
+        profiles: Arc<InMemoryProfileRepository>,
+        preferences: Arc<InMemoryPreferencesRepository>,
+        notifications: Arc<InMemoryNotificationRepository>,
+        audit: Arc<InMemoryAuditRepository>,
+        schema_version: i64,
+    ) -> Self {
+        Self {
+            profiles,
+            preferences,
+            notifications,
+            audit,
+            schema_version,
+        }
+    }
+}
+
+#[async_trait]
+impl BackupRepository for InMemoryBackupRepository {
+    async fn schema_version(&self) -> Result<i64, ErrorResponse> {
+        Ok(self.schema_version)
+    }
+
+    async fn snapshot(&self) -> Result<DatabaseSnapshotModel, ErrorResponse> {
+        let (budget_alert_rules, notifications) = self.notifications.rows();
+
+        Ok(DatabaseSnapshotModel {
+            schema_version: self.schema_version,
+            profiles: self.profiles.rows(),
+            preferences: self.preferences.rows(),
+            budget_alert_rules,
+            notifications,
+        })
+    }
+
+    async fn restore(
+        &self,
+        snapshot: DatabaseSnapshotModel,
+        event: AuditEventModel,
+    ) -> Result<(), ErrorResponse> {
+        self.profiles.replace_rows(snapshot.profiles);
+        self.preferences.replace_rows(snapshot.preferences);
+        self.notifications
+            .replace_rows(snapshot.budget_alert_rules, snapshot.notifications);
+        self.audit.record(Some(event));
+        Ok(())
+    }
+}
@@ -0,0 +1,175 @@
+use async_trait::async_trait;
+use std::sync::{Mutex, MutexGuard};
+
+use crate::{
+    models::v1::notification_model::{BudgetAlertRuleModel, NotificationKind, NotificationModel},
+    repositories::v1::notification_repository::NotificationRepository,
+    utils::error::mapping::ErrorResponse,
+};
+
+#[derive(Default)]
+struct Tables {
+    rules: Vec<BudgetAlertRuleModel>,
+    notifications: Vec<NotificationModel>,
+}
+
+/// Keeps alert rules and notifications in memory so services can be exercised without a
+/// database. Does not check that the profile exists.
+#[derive(Default)]
+pub struct InMemoryNotificationRepository {
+    tables: Mutex<Tables>,
+}
+
+impl InMemoryNotificationRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn rows(&self) -> (Vec<BudgetAlertRuleModel>, Vec<NotificationModel>) {
+        let tables = self.lock();
+        (tables.rules.clone(), tables.notifications.clone())
+    }
+
+    pub fn replace_rows(
+        &self,
+        rules: Vec<BudgetAlertRuleModel>,
+        notifications: Vec<NotificationModel>,
+    ) {
+        *self.lock() = Tables {
+            rules,
+            notifications,
+        };
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Tables> {
+        self.tables
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[async_trait]
+impl NotificationRepository for InMemoryNotificationRepository {
+    async fn get_alert_rules(
+        &self,
+        profile_id: i32,
+    ) -> Result<Vec<BudgetAlertRuleModel>, ErrorResponse> {
+        let mut rules: Vec<_> = self
+            .lock()
+            .rules
+            .iter()
+            .filter(|rule| rule.profile_id == profile_id)
+            .cloned()
+            .collect();
+        rules.sort_by_key(|rule| rule.category_id);
+        Ok(rules)
+    }
+
+    async fn save_alert_rule(
+        &self,
+        rule: BudgetAlertRuleModel,
+    ) -> Result<BudgetAlertRuleModel, ErrorResponse> {
+        let mut tables = self.lock();
+        tables.rules.retain(|existing| {
+            (existing.profile_id, existing.category_id) != (rule.profile_id, rule.category_id)
+        });
+        tables.rules.push(rule.clone());
+        Ok(rule)
+    }
+
+    async fn delete_alert_rule(
+        &self,
+        profile_id: i32,
+        category_id: i32,
+    ) -> Result<bool, ErrorResponse> {
+        let mut tables = self.lock();
+        let before = tables.rules.len();
+        tables
+            .rules
+            .retain(|rule| (rule.profile_id, rule.category_id) != (profile_id, category_id));
+        Ok(tables.rules.len() < before)
+    }
+
+    async fn get_notifications(
+        &self,
+        profile_id: i32,
+        unread_only: bool,
+    ) -> Result<Vec<NotificationModel>, ErrorResponse> {
+        Ok(self
+            .lock()
+            .notifications
+            .iter()
+            .rev()
+            .filter(|notification| notification.profile_id == profile_id)
+            .filter(|notification| !unread_only || notification.read_at.is_none())
+            .cloned()
+            .collect())
+    }
+
+    async fn insert_notification(
+        &self,
+        profile_id: i32,
+        kind: NotificationKind,
+        payload: String,
+        dedup_key: Option<String>,
+    ) -> Result<Option<NotificationModel>, ErrorResponse> {
+        let mut tables = self.lock();
+
+        let duplicate = dedup_key.is_some()
+            && tables.notifications.iter().any(|notification| {
+                notification.profile_id == profile_id && notification.dedup_key == dedup_key
+            });
+        if duplicate {
+            return Ok(None);
+        }
+
+        let notification = NotificationModel {
+            id: tables.notifications.iter().map(|n| n.id).max().unwrap_or(0) + 1,
+            profile_id,
+            created_at: chrono::Utc::now().naive_utc(),
+            kind: kind.as_str().into(),
+            payload,
+            dedup_key,
+            read_at: None,
+        };
+        tables.notifications.push(notification.clone());
+        Ok(Some(notification))
+    }
+
+    async fn set_read(
+        &self,
+        profile_id: i32,
+        id: i32,
+        read: bool,
+    ) -> Result<Option<NotificationModel>, ErrorResponse> {
+        let mut tables = self.lock();
+        let Some(notification) = tables
+            .notifications
+            .iter_mut()
+            .find(|notification| notification.id == id && notification.profile_id == profile_id)
+        else {
+            return Ok(None);
+        };
+
+        notification.read_at = match read {
+            true => notification
+                .read_at
+                .or_else(|| Some(chrono::Utc::now().naive_utc())),
+            false => None,
+        };
+        Ok(Some(notification.clone()))
+    }
+
+    async fn mark_all_read(&self, profile_id: i32) -> Result<u64, ErrorResponse> {
+        let now = chrono::Utc::now().naive_utc();
+        let mut marked = 0;
+
+        for notification in self.lock().notifications.iter_mut() {
+            if notification.profile_id == profile_id && notification.read_at.is_none() {
+                notification.read_at = Some(now);
+                marked += 1;
+            }
+        }
+        Ok(marked)
+    }
+}
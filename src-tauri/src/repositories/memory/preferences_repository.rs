@@ -0,0 +1,67 @@
+use async_trait::async_trait;
+use std::{collections::HashMap, sync::Mutex};
+
+use crate::{
+    models::v1::preferences_model::ProfilePreferencesModel,
+    repositories::v1::preferences_repository::PreferencesRepository,
+    utils::error::mapping::ErrorResponse,
+};
+
+/// Keeps preferences in memory so services can be exercised without a database. Does not check
+/// that the profile exists.
+#[derive(Default)]
+pub struct InMemoryPreferencesRepository {
+    preferences: Mutex<HashMap<i32, ProfilePreferencesModel>>,
+}
+
+impl InMemoryPreferencesRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn rows(&self) -> Vec<ProfilePreferencesModel> {
+        let mut rows: Vec<_> = self.lock().values().cloned().collect();
+        rows.sort_by_key(|preferences| preferences.profile_id);
+        rows
+    }
+
+    pub fn replace_rows(&self, rows: Vec<ProfilePreferencesModel>) {
+        *self.lock() = rows
+            .into_iter()
+            .map(|preferences| (preferences.profile_id, preferences))
+            .collect();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<i32, ProfilePreferencesModel>> {
+        self.preferences
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[async_trait]
+impl PreferencesRepository for InMemoryPreferencesRepository {
+    async fn get_by_profile_id(
+        &self,
+        profile_id: i32,
+    ) -> Result<Option<ProfilePreferencesModel>, ErrorResponse> {
+        Ok(self.lock().get(&profile_id).cloned())
+    }
+
+    async fn save(
+        &self,
+        profile_id: i32,
+        version: i32,
+        preferences: String,
+    ) -> Result<ProfilePreferencesModel, ErrorResponse> {
+        let saved = ProfilePreferencesModel {
+            profile_id,
+            version,
+            preferences,
+            updated_at: chrono::Utc::now().naive_utc(),
+        };
+
+        self.lock().insert(profile_id, saved.clone());
+        Ok(saved)
+    }
+}
@@ -0,0 +1,91 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+
+use crate::{
+    models::v1::{
+        notification_model::BudgetAlertRuleModel, preferences_model::ProfilePreferencesModel,
+        profile_model::ProfileModel,
+    },
+    repositories::{
+        memory::{
+            notification_repository::InMemoryNotificationRepository,
+            preferences_repository::InMemoryPreferencesRepository,
+            profile_repository::InMemoryProfileRepository,
+        },
+        v1::{
+            notification_repository::NotificationRepository,
+            preferences_repository::PreferencesRepository,
+            profile_archive_repository::ProfileArchiveRepository,
+            profile_repository::ProfileRepository,
+        },
+    },
+    utils::error::mapping::ErrorResponse,
+};
+
+/// Imports through the other in-memory repositories. Unlike the SQL one it is not atomic, does not
+/// keep the archived timestamps, and audits a locked profile as created and then locked.
+pub struct InMemoryProfileArchiveRepository {
+    profiles: Arc<InMemoryProfileRepository>,
+    preferences: Arc<InMemoryPreferencesRepository>,
+    notifications: Arc<InMemoryNotificationRepository>,
+}
+
+impl InMemoryProfileArchiveRepository {
+    pub fn new(
+        profiles: Arc<InMemoryProfileRepository>,
+        preferences: Arc<InMemoryPreferencesRepository>,
+        notifications: Arc<InMemoryNotificationRepository>,
+    ) -> Self {
+        Self {
+            profiles,
+            preferences,
+            notifications,
+        }
+    }
+}
+
+#[async_trait]
+impl ProfileArchiveRepository for InMemoryProfileArchiveRepository {
+    async fn import_profile(
+        &self,
+        profile: ProfileModel,
+        preferences: Option<ProfilePreferencesModel>,
+        budget_alert_rules: Vec<BudgetAlertRuleModel>,
+        actor: Option<i32>,
+    ) -> Result<ProfileModel, ErrorResponse> {
+        let created = self
+            .profiles
+            .create_profile(
+                profile.username,
+                profile.display_name,
+                profile.profile_picture_url,
+                actor,
+            )
+            .await?;
+
+        if profile.secret_hash.is_some() {
+            self.profiles
+                .set_secret(created.id, profile.secret_hash, profile.secret_kind, actor)
+                .await?;
+        }
+        if let Some(preferences) = preferences {
+            self.preferences
+                .save(created.id, preferences.version, preferences.preferences)
+                .await?;
+        }
+        for rule in budget_alert_rules {
+            self.notifications
+                .save_alert_rule(BudgetAlertRuleModel {
+                    profile_id: created.id,
+                    ..rule
+                })
+                .await?;
+        }
+
+        Ok(self
+            .profiles
+            .get_one_by_id(created.id)
+            .await?
+            .unwrap_or(created))
+    }
+}
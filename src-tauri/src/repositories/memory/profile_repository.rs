@@ -0,0 +1,443 @@
+use async_trait::async_trait;
+use sqlx::error::ErrorKind;
+use std::sync::{Arc, Mutex};
+
+use crate::{
+    models::{
+        patch::Patch,
+        v1::{
+            audit_model::{AuditEventModel, AuditOperation},
+            profile_model::ProfileModel,
+        },
+    },
+    repositories::{
+        memory::audit_repository::InMemoryAuditRepository,
+        v1::profile_repository::ProfileRepository,
+    },
+    utils::{
+        db,
+        error::mapping::{ErrorCode, ErrorResponse},
+    },
+};
+
+#[derive(Default)]
+struct Profiles {
+    next_id: i32,
+    rows: Vec<ProfileModel>,
+}
+
+/// Keeps profiles in memory and mimics the constraints of the `profiles` table, so services can be
+/// exercised without a database. Changes are recorded in `audit`.
+#[derive(Default)]
+pub struct InMemoryProfileRepository {
+    profiles: Mutex<Profiles>,
+    audit: Arc<InMemoryAuditRepository>,
+}
+
+impl InMemoryProfileRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_audit(audit: Arc<InMemoryAuditRepository>) -> Self {
+        Self {
+            profiles: Mutex::default(),
+            audit,
+        }
+    }
+
+    /// Every row, trashed ones included.
+    pub fn rows(&self) -> Vec<ProfileModel> {
+        lock(&self.profiles).rows.clone()
+    }
+
+    /// Replaces every row, keeping their ids. New profiles continue after the highest one.
+    pub fn replace_rows(&self, rows: Vec<ProfileModel>) {
+        let mut profiles = lock(&self.profiles);
+
+        profiles.next_id = rows.iter().map(|p| p.id).max().unwrap_or_default();
+        profiles.rows = rows;
+    }
+}
+
+fn unique_violation(field: &str) -> ErrorResponse {
+    ErrorResponse::keyed(
+        ErrorCode::UserInputValidationError,
+        Some(field.into()),
+        db::violation(ErrorKind::UniqueViolation).unwrap_or("db-unique-violation"),
+    )
+}
+
+fn lock(profiles: &Mutex<Profiles>) -> std::sync::MutexGuard<'_, Profiles> {
+    profiles
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+#[async_trait]
+impl ProfileRepository for InMemoryProfileRepository {
+    async fn create_profile(
+        &self,
+        username: String,
+        display_name: Option<String>,
+        profile_picture_url: Option<String>,
+        actor: Option<i32>,
+    ) -> Result<ProfileModel, ErrorResponse> {
+        let mut profiles = lock(&self.profiles);
+
+        if profiles.rows.iter().any(|p| p.username == username) {
+            return Err(unique_violation("username"));
+        }
+
+        profiles.next_id += 1;
+        let now = chrono::Utc::now().naive_utc();
+        let profile = ProfileModel {
+            id: profiles.next_id,
+            created_at: now,
+            updated_at: now,
+            deleted_at: None,
+            username,
+            original_username: None,
+            display_name,
+            profile_picture_url,
+            secret_hash: None,
+            secret_kind: None,
+            failed_unlock_attempts: 0,
+            unlock_blocked_until: None,
+        };
+
+        profiles.rows.push(profile.clone());
+        self.audit.record(AuditEventModel::change(
+            actor,
+            AuditOperation::Create,
+            None,
+            Some(&profile),
+        ));
+        Ok(profile)
+    }
+
+    async fn get_all(&self) -> Result<Vec<ProfileModel>, ErrorResponse> {
+        let profiles = lock(&self.profiles);
+
+        Ok(profiles
+            .rows
+            .iter()
+            .filter(|p| p.deleted_at.is_none())
+            .cloned()
+            .collect())
+    }
+
+    async fn get_one_by_id(&self, profile_id: i32) -> Result<Option<ProfileModel>, ErrorResponse> {
+        let profiles = lock(&self.profiles);
+
+        Ok(profiles
+            .rows
+            .iter()
+            .find(|p| p.id == profile_id && p.deleted_at.is_none())
+            .cloned())
+    }
+
+    async fn get_profile_picture_urls(&self) -> Result<Vec<String>, ErrorResponse> {
+        let profiles = lock(&self.profiles);
+
+        Ok(profiles
+            .rows
+            .iter()
+            .filter_map(|p| p.profile_picture_url.clone())
+            .collect())
+    }
+
+    async fn get_one_by_username(
+        &self,
+        profile_username: String,
+    ) -> Result<Option<ProfileModel>, ErrorResponse> {
+        let profiles = lock(&self.profiles);
+
+        Ok(profiles
+            .rows
+            .iter()
+            .find(|p| p.username == profile_username && p.deleted_at.is_none())
+            .cloned())
+    }
+
+    async fn delete_profile(
+        &self,
+        profile_id: i32,
+        actor: Option<i32>,
+    ) -> Result<bool, ErrorResponse> {
+        let mut profiles = lock(&self.profiles);
+
+        let Some(profile) = profiles
+            .rows
+            .iter_mut()
+            .find(|p| p.id == profile_id && p.deleted_at.is_none())
+        else {
+            return Ok(false);
+        };
+
+        let before = profile.clone();
+        let now = chrono::Utc::now().naive_utc();
+        profile.deleted_at = Some(now);
+        profile.updated_at = now;
+        profile.original_username = Some(profile.username.clone());
+        profile.username = format!("deleted:{}", profile.id);
+        self.audit.record(AuditEventModel::change(
+            actor,
+            AuditOperation::Delete,
+            Some(&before),
+            Some(profile),
+        ));
+        Ok(true)
+    }
+
+    async fn get_deleted(&self) -> Result<Vec<ProfileModel>, ErrorResponse> {
+        let profiles = lock(&self.profiles);
+
+        let mut deleted: Vec<_> = profiles
+            .rows
+            .iter()
+            .filter(|p| p.deleted_at.is_some())
+            .cloned()
+            .collect();
+        deleted.sort_by_key(|p| std::cmp::Reverse(p.deleted_at));
+        Ok(deleted)
+    }
+
+    async fn get_deleted_by_id(
+        &self,
+        profile_id: i32,
+    ) -> Result<Option<ProfileModel>, ErrorResponse> {
+        let profiles = lock(&self.profiles);
+
+        Ok(profiles
+            .rows
+            .iter()
+            .find(|p| p.id == profile_id && p.deleted_at.is_some())
+            .cloned())
+    }
+
+    async fn restore_profile(
+        &self,
+        profile_id: i32,
+        username: String,
+        actor: Option<i32>,
+    ) -> Result<Option<ProfileModel>, ErrorResponse> {
+        let mut profiles = lock(&self.profiles);
+
+        if profiles
+            .rows
+            .iter()
+            .any(|p| p.id != profile_id && p.username == username)
+        {
+            return Err(unique_violation("username"));
+        }
+
+        let Some(profile) = profiles
+            .rows
+            .iter_mut()
+            .find(|p| p.id == profile_id && p.deleted_at.is_some())
+        else {
+            return Ok(None);
+        };
+
+        let before = profile.clone();
+        profile.deleted_at = None;
+        profile.original_username = None;
+        profile.username = username;
+        profile.updated_at = chrono::Utc::now().naive_utc();
+        self.audit.record(AuditEventModel::change(
+            actor,
+            AuditOperation::Restore,
+            Some(&before),
+            Some(profile),
+        ));
+        Ok(Some(profile.clone()))
+    }
+
+    async fn purge_profile(
+        &self,
+        profile_id: i32,
+        actor: Option<i32>,
+    ) -> Result<bool, ErrorResponse> {
+        let mut profiles = lock(&self.profiles);
+
+        let Some(index) = profiles
+            .rows
+            .iter()
+            .position(|p| p.id == profile_id && p.deleted_at.is_some())
+        else {
+            return Ok(false);
+        };
+
+        let purged = profiles.rows.remove(index);
+        self.audit.record(AuditEventModel::change(
+            actor,
+            AuditOperation::Purge,
+            Some(&purged),
+            None,
+        ));
+        Ok(true)
+    }
+
+    async fn update_profile(
+        &self,
+        profile_id: i32,
+        username: Option<String>,
+        display_name: Patch<String>,
+        profile_picture_url: Patch<String>,
+        actor: Option<i32>,
+    ) -> Result<Option<ProfileModel>, ErrorResponse> {
+        let mut profiles = lock(&self.profiles);
+
+        if let Some(username) = &username {
+            if profiles
+                .rows
+                .iter()
+                .any(|p| p.id != profile_id && &p.username == username)
+            {
+                return Err(unique_violation("username"));
+            }
+        }
+
+        let Some(profile) = profiles
+            .rows
+            .iter_mut()
+            .find(|p| p.id == profile_id && p.deleted_at.is_none())
+        else {
+            return Ok(None);
+        };
+
+        let before = profile.clone();
+        if let Some(username) = username {
+            profile.username = username;
+        }
+        if let Some(display_name) = display_name.into_change() {
+            profile.display_name = display_name;
+        }
+        if let Some(profile_picture_url) = profile_picture_url.into_change() {
+            profile.profile_picture_url = profile_picture_url;
+        }
+        profile.updated_at = chrono::Utc::now().naive_utc();
+        self.audit.record(AuditEventModel::change(
+            actor,
+            AuditOperation::Update,
+            Some(&before),
+            Some(profile),
+        ));
+
+        Ok(Some(profile.clone()))
+    }
+
+    async fn set_secret(
+        &self,
+        profile_id: i32,
+        secret_hash: Option<String>,
+        secret_kind: Option<String>,
+        actor: Option<i32>,
+    ) -> Result<bool, ErrorResponse> {
+        let mut profiles = lock(&self.profiles);
+
+        let Some(profile) = profiles
+            .rows
+            .iter_mut()
+            .find(|p| p.id == profile_id && p.deleted_at.is_none())
+        else {
+            return Ok(false);
+        };
+
+        let before = profile.clone();
+        profile.secret_hash = secret_hash;
+        profile.secret_kind = secret_kind;
+        profile.failed_unlock_attempts = 0;
+        profile.unlock_blocked_until = None;
+        profile.updated_at = chrono::Utc::now().naive_utc();
+        self.audit.record(AuditEventModel::change(
+            actor,
+            AuditOperation::Update,
+            Some(&before),
+            Some(profile),
+        ));
+        Ok(true)
+    }
+
+    async fn set_unlock_attempts(
+        &self,
+        profile_id: i32,
+        failed_unlock_attempts: i32,
+        unlock_blocked_until: Option<chrono::NaiveDateTime>,
+    ) -> Result<(), ErrorResponse> {
+        let mut profiles = lock(&self.profiles);
+
+        if let Some(profile) = profiles.rows.iter_mut().find(|p| p.id == profile_id) {
+            profile.failed_unlock_attempts = failed_unlock_attempts;
+            profile.unlock_blocked_until = unlock_blocked_until;
+        }
+
+        Ok(())
+    }
+
+    async fn record_failed_unlock(
+        &self,
+        profile_id: i32,
+        now: chrono::NaiveDateTime,
+        lockout: fn(i32) -> Option<chrono::Duration>,
+    ) -> Result<Option<chrono::NaiveDateTime>, ErrorResponse> {
+        let mut profiles = lock(&self.profiles);
+
+        let Some(profile) = profiles.rows.iter_mut().find(|p| p.id == profile_id) else {
+            return Ok(None);
+        };
+        if profile
+            .unlock_blocked_until
+            .is_some_and(|until| until > now)
+        {
+            return Ok(profile.unlock_blocked_until);
+        }
+
+        profile.failed_unlock_attempts += 1;
+        let blocked_until = lockout(profile.failed_unlock_attempts).map(|lockout| now + lockout);
+        if blocked_until.is_some() {
+            profile.unlock_blocked_until = blocked_until;
+        }
+        Ok(blocked_until)
+    }
+    async fn revert_profile(
+        &self,
+        profile: ProfileModel,
+        expected_updated_at: chrono::NaiveDateTime,
+        actor: Option<i32>,
+    ) -> Result<Option<ProfileModel>, ErrorResponse> {
+        let mut profiles = lock(&self.profiles);
+
+        if profiles
+            .rows
+            .iter()
+            .any(|p| p.id != profile.id && p.username == profile.username)
+        {
+            return Err(unique_violation("username"));
+        }
+
+        let Some(row) = profiles
+            .rows
+            .iter_mut()
+            .find(|p| p.id == profile.id && p.updated_at == expected_updated_at)
+        else {
+            return Ok(None);
+        };
+
+        let before = row.clone();
+        row.username = profile.username;
+        row.original_username = profile.original_username;
+        row.display_name = profile.display_name;
+        row.profile_picture_url = profile.profile_picture_url;
+        row.deleted_at = profile.deleted_at;
+        row.updated_at = chrono::Utc::now().naive_utc();
+        self.audit.record(AuditEventModel::change(
+            actor,
+            before.operation_to(row),
+            Some(&before),
+            Some(row),
+        ));
+        Ok(Some(row.clone()))
+    }
+}
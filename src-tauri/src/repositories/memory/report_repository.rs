@@ -0,0 +1,93 @@
+use async_trait::async_trait;
+use std::sync::{Mutex, MutexGuard};
+
+use crate::{
+    repositories::v1::report_repository::ReportRepository,
+    services::report_service::{
+        self, CategorySpendingReport, CategoryTrendReport, HistoricalTransaction,
+        IncomeExpenseReport, PayeeSpending, ReportFilterDTO, ReportInterval,
+    },
+    utils::error::mapping::ErrorResponse,
+};
+
+/// Keeps transactions in memory, per profile, and aggregates them with the report functions so
+/// services can be exercised without a database.
+#[derive(Default)]
+pub struct InMemoryReportRepository {
+    transactions: Mutex<Vec<(i32, HistoricalTransaction)>>,
+}
+
+impl InMemoryReportRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&self, profile_id: i32, transaction: HistoricalTransaction) {
+        self.lock().push((profile_id, transaction));
+    }
+
+    fn history(&self, profile_id: i32) -> Vec<HistoricalTransaction> {
+        self.lock()
+            .iter()
+            .filter(|(owner, _)| *owner == profile_id)
+            .map(|(_, transaction)| transaction.clone())
+            .collect()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<(i32, HistoricalTransaction)>> {
+        self.transactions
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[async_trait]
+impl ReportRepository for InMemoryReportRepository {
+    async fn spending_by_category(
+        &self,
+        profile_id: i32,
+        filter: &ReportFilterDTO,
+    ) -> Result<CategorySpendingReport, ErrorResponse> {
+        Ok(report_service::spending_by_category(
+            &self.history(profile_id),
+            filter,
+        ))
+    }
+
+    async fn income_vs_expense(
+        &self,
+        profile_id: i32,
+        filter: &ReportFilterDTO,
+    ) -> Result<IncomeExpenseReport, ErrorResponse> {
+        Ok(report_service::income_vs_expense(
+            &self.history(profile_id),
+            filter,
+        ))
+    }
+
+    async fn top_payees(
+        &self,
+        profile_id: i32,
+        filter: &ReportFilterDTO,
+        limit: usize,
+    ) -> Result<Vec<PayeeSpending>, ErrorResponse> {
+        Ok(report_service::top_payees(
+            &self.history(profile_id),
+            filter,
+            limit,
+        ))
+    }
+
+    async fn category_trends(
+        &self,
+        profile_id: i32,
+        filter: &ReportFilterDTO,
+        interval: ReportInterval,
+    ) -> Result<CategoryTrendReport, ErrorResponse> {
+        Ok(report_service::category_trends(
+            &self.history(profile_id),
+            filter,
+            interval,
+        ))
+    }
+}
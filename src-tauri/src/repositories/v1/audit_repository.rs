@@ -0,0 +1,101 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+
+use crate::{
+    models::v1::audit_model,
+    utils::{
+        db::{connection::ConnectionManager, pool::with_pool},
+        error::mapping::ErrorResponse,
+    },
+};
+
+/// Appends `$event` (an `Option<AuditEventModel>`; `None` records nothing) through `$conn`.
+///
+/// Writers pass the transaction of the change being described, so the change and its event are
+/// committed or rolled back together. There is deliberately no repository method for this.
+macro_rules! record_audit_event {
+    ($conn:expr, $event:expr) => {
+        if let Some(event) = $event {
+            sqlx::query(
+                r#"
+                INSERT INTO audit_events (
+                    created_at, actor_profile_id, entity, entity_id,
+                    operation, old_values, new_values
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                "#,
+            )
+            .bind(event.created_at)
+            .bind(event.actor_profile_id)
+            .bind(&event.entity)
+            .bind(event.entity_id)
+            .bind(&event.operation)
+            .bind(&event.old_values)
+            .bind(&event.new_values)
+            .execute($conn)
+            .await?;
+        }
+    };
+}
+
+pub(crate) use record_audit_event;
+
+#[async_trait]
+pub trait AuditRepository: Send + Sync {
+    async fn get_events(
+        &self,
+        filter: audit_model::AuditFilterModel,
+    ) -> Result<Vec<audit_model::AuditEventModel>, ErrorResponse>;
+}
+
+#[derive(Clone)]
+pub struct SqlAuditRepository {
+    db: Arc<ConnectionManager>,
+}
+
+impl SqlAuditRepository {
+    pub fn new(db: Arc<ConnectionManager>) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl AuditRepository for SqlAuditRepository {
+    async fn get_events(
+        &self,
+        filter: audit_model::AuditFilterModel,
+    ) -> Result<Vec<audit_model::AuditEventModel>, ErrorResponse> {
+        let pool = self.db.pool()?;
+        let entity = filter.entity.map(|entity| entity.as_str());
+        let operation = filter.operation.map(|operation| operation.as_str());
+
+        let events = with_pool!(&pool, |pool| {
+            sqlx::query_as::<_, audit_model::AuditEventModel>(
+                r#"
+                SELECT * FROM audit_events
+                WHERE ($1 IS NULL OR actor_profile_id = $1)
+                  AND ($2 IS NULL OR entity = $2)
+                  AND ($3 IS NULL OR entity_id = $3)
+                  AND ($4 IS NULL OR operation = $4)
+                  AND ($5 IS NULL OR created_at >= $5)
+                  AND ($6 IS NULL OR created_at < $6)
+                  AND ($7 IS NULL OR id < $7)
+                ORDER BY id DESC
+                LIMIT $8
+                "#,
+            )
+            .bind(filter.actor_profile_id)
+            .bind(entity)
+            .bind(filter.entity_id)
+            .bind(operation)
+            .bind(filter.from)
+            .bind(filter.until)
+            .bind(filter.before_id)
+            .bind(i64::from(filter.limit))
+            .fetch_all(pool)
+            .await?
+        });
+
+        Ok(events)
+    }
+}
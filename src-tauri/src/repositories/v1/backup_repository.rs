@@ -0,0 +1,259 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+
+use crate::{
+    models::v1::{
+        audit_model::AuditEventModel, backup_model, notification_model, preferences_model,
+        profile_model,
+    },
+    repositories::v1::audit_repository::record_audit_event,
+    utils::{
+        db::{
+            connection::ConnectionManager,
+            pool::{with_pool, DbBackend},
+        },
+        error::mapping::ErrorResponse,
+    },
+};
+
+#[async_trait]
+pub trait BackupRepository: Send + Sync {
+    /// Newest migration applied to the database.
+    async fn schema_version(&self) -> Result<i64, ErrorResponse>;
+
+    /// Reads every table in one transaction, so the snapshot is consistent even while the app
+    /// keeps writing.
+    async fn snapshot(&self) -> Result<backup_model::DatabaseSnapshotModel, ErrorResponse>;
+
+    /// Replaces all app data with `snapshot` in one transaction, which also records `event`; on
+    /// failure nothing changes. The audit log is not app data and is kept.
+    async fn restore(
+        &self,
+        snapshot: backup_model::DatabaseSnapshotModel,
+        event: AuditEventModel,
+    ) -> Result<(), ErrorResponse>;
+}
+
+#[derive(Clone)]
+pub struct SqlBackupRepository {
+    db: Arc<ConnectionManager>,
+}
+
+impl SqlBackupRepository {
+    pub fn new(db: Arc<ConnectionManager>) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl BackupRepository for SqlBackupRepository {
+    async fn schema_version(&self) -> Result<i64, ErrorResponse> {
+        let pool = self.db.pool()?;
+        let version = with_pool!(&pool, |pool| {
+            sqlx::query_scalar::<_, Option<i64>>(
+                r#"
+                SELECT MAX(version) FROM _sqlx_migrations WHERE success
+                "#,
+            )
+            .fetch_one(pool)
+            .await?
+        });
+
+        Ok(version.unwrap_or_default())
+    }
+
+    async fn snapshot(&self) -> Result<backup_model::DatabaseSnapshotModel, ErrorResponse> {
+        let pool = self.db.pool()?;
+        let backend = pool.backend();
+
+        let snapshot = with_pool!(&pool, |pool| {
+            let mut tx = pool.begin().await?;
+
+            // Postgres otherwise lets each statement see the rows committed before it started.
+            if backend == DbBackend::Postgres {
+                sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY")
+                    .execute(&mut *tx)
+                    .await?;
+            }
+
+            let schema_version = sqlx::query_scalar::<_, Option<i64>>(
+                r#"
+                SELECT MAX(version) FROM _sqlx_migrations WHERE success
+                "#,
+            )
+            .fetch_one(&mut *tx)
+            .await?;
+
+            let profiles = sqlx::query_as::<_, profile_model::ProfileModel>(
+                r#"
+                SELECT * FROM profiles ORDER BY id
+                "#,
+            )
+            .fetch_all(&mut *tx)
+            .await?;
+
+            let preferences = sqlx::query_as::<_, preferences_model::ProfilePreferencesModel>(
+                r#"
+                SELECT * FROM profile_preferences ORDER BY profile_id
+                "#,
+            )
+            .fetch_all(&mut *tx)
+            .await?;
+
+            let budget_alert_rules = sqlx::query_as::<_, notification_model::BudgetAlertRuleModel>(
+                r#"
+                SELECT * FROM budget_alert_rules ORDER BY profile_id, category_id
+                "#,
+            )
+            .fetch_all(&mut *tx)
+            .await?;
+
+            let notifications = sqlx::query_as::<_, notification_model::NotificationModel>(
+                r#"
+                SELECT * FROM notifications ORDER BY id
+                "#,
+            )
+            .fetch_all(&mut *tx)
+            .await?;
+
+            tx.commit().await?;
+            backup_model::DatabaseSnapshotModel {
+                schema_version: schema_version.unwrap_or_default(),
+                profiles,
+                preferences,
+                budget_alert_rules,
+                notifications,
+            }
+        });
+
+        Ok(snapshot)
+    }
+
+    async fn restore(
+        &self,
+        snapshot: backup_model::DatabaseSnapshotModel,
+        event: AuditEventModel,
+    ) -> Result<(), ErrorResponse> {
+        let pool = self.db.pool()?;
+        let backend = pool.backend();
+
+        with_pool!(&pool, |pool| {
+            let mut tx = pool.begin().await?;
+
+            // Children first, in case foreign keys are not enforced.
+            sqlx::query("DELETE FROM notifications")
+                .execute(&mut *tx)
+                .await?;
+            sqlx::query("DELETE FROM budget_alert_rules")
+                .execute(&mut *tx)
+                .await?;
+            sqlx::query("DELETE FROM profile_preferences")
+                .execute(&mut *tx)
+                .await?;
+            sqlx::query("DELETE FROM profiles")
+                .execute(&mut *tx)
+                .await?;
+
+            for profile in &snapshot.profiles {
+                sqlx::query(
+                    r#"
+                    INSERT INTO profiles (
+                        id, created_at, updated_at, deleted_at,
+                        username, original_username, display_name, profile_picture_url,
+                        secret_hash, secret_kind, failed_unlock_attempts, unlock_blocked_until
+                    )
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+                    "#,
+                )
+                .bind(profile.id)
+                .bind(profile.created_at)
+                .bind(profile.updated_at)
+                .bind(profile.deleted_at)
+                .bind(&profile.username)
+                .bind(&profile.original_username)
+                .bind(&profile.display_name)
+                .bind(&profile.profile_picture_url)
+                .bind(&profile.secret_hash)
+                .bind(&profile.secret_kind)
+                .bind(profile.failed_unlock_attempts)
+                .bind(profile.unlock_blocked_until)
+                .execute(&mut *tx)
+                .await?;
+            }
+
+            for preferences in &snapshot.preferences {
+                sqlx::query(
+                    r#"
+                    INSERT INTO profile_preferences (profile_id, version, preferences, updated_at)
+                    VALUES ($1, $2, $3, $4)
+                    "#,
+                )
+                .bind(preferences.profile_id)
+                .bind(preferences.version)
+                .bind(&preferences.preferences)
+                .bind(preferences.updated_at)
+                .execute(&mut *tx)
+                .await?;
+            }
+
+            for rule in &snapshot.budget_alert_rules {
+                sqlx::query(
+                    r#"
+                    INSERT INTO budget_alert_rules (
+                        profile_id, category_id, warn_at_percent, notify_desktop, updated_at
+                    )
+                    VALUES ($1, $2, $3, $4, $5)
+                    "#,
+                )
+                .bind(rule.profile_id)
+                .bind(rule.category_id)
+                .bind(rule.warn_at_percent)
+                .bind(rule.notify_desktop)
+                .bind(rule.updated_at)
+                .execute(&mut *tx)
+                .await?;
+            }
+
+            for notification in &snapshot.notifications {
+                sqlx::query(
+                    r#"
+                    INSERT INTO notifications (
+                        id, profile_id, created_at, kind, payload, dedup_key, read_at
+                    )
+                    VALUES ($1, $2, $3, $4, $5, $6, $7)
+                    "#,
+                )
+                .bind(notification.id)
+                .bind(notification.profile_id)
+                .bind(notification.created_at)
+                .bind(&notification.kind)
+                .bind(&notification.payload)
+                .bind(&notification.dedup_key)
+                .bind(notification.read_at)
+                .execute(&mut *tx)
+                .await?;
+            }
+
+            // Explicit ids do not advance a SERIAL sequence; SQLite keeps up on its own.
+            if backend == DbBackend::Postgres {
+                for table in ["profiles", "notifications"] {
+                    sqlx::query(&format!(
+                        r#"
+                        SELECT setval(
+                            pg_get_serial_sequence('{table}', 'id'), COALESCE(MAX(id), 0) + 1, false
+                        )
+                        FROM {table}
+                        "#
+                    ))
+                    .execute(&mut *tx)
+                    .await?;
+                }
+            }
+
+            record_audit_event!(&mut *tx, Some(event));
+            tx.commit().await?;
+        });
+
+        Ok(())
+    }
+}
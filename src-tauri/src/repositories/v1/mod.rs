@@ -1 +1,7 @@
+pub mod audit_repository;
+pub mod backup_repository;
+pub mod notification_repository;
+pub mod preferences_repository;
+pub mod profile_archive_repository;
 pub mod profile_repository;
+pub mod report_repository;
@@ -0,0 +1,249 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+
+use crate::{
+    models::v1::notification_model,
+    utils::{
+        db::{connection::ConnectionManager, pool::with_pool},
+        error::mapping::ErrorResponse,
+    },
+};
+
+#[async_trait]
+pub trait NotificationRepository: Send + Sync {
+    async fn get_alert_rules(
+        &self,
+        profile_id: i32,
+    ) -> Result<Vec<notification_model::BudgetAlertRuleModel>, ErrorResponse>;
+
+    /// Inserts or replaces the rule of the category.
+    async fn save_alert_rule(
+        &self,
+        rule: notification_model::BudgetAlertRuleModel,
+    ) -> Result<notification_model::BudgetAlertRuleModel, ErrorResponse>;
+
+    /// Whether there was a rule to delete.
+    async fn delete_alert_rule(
+        &self,
+        profile_id: i32,
+        category_id: i32,
+    ) -> Result<bool, ErrorResponse>;
+
+    /// Newest first.
+    async fn get_notifications(
+        &self,
+        profile_id: i32,
+        unread_only: bool,
+    ) -> Result<Vec<notification_model::NotificationModel>, ErrorResponse>;
+
+    /// Adds a notification, unless the profile already has one with the same `dedup_key`; then
+    /// nothing changes and `None` is returned.
+    async fn insert_notification(
+        &self,
+        profile_id: i32,
+        kind: notification_model::NotificationKind,
+        payload: String,
+        dedup_key: Option<String>,
+    ) -> Result<Option<notification_model::NotificationModel>, ErrorResponse>;
+
+    /// Marks one notification of the profile as read or unread; `None` when there is no such
+    /// notification.
+    async fn set_read(
+        &self,
+        profile_id: i32,
+        id: i32,
+        read: bool,
+    ) -> Result<Option<notification_model::NotificationModel>, ErrorResponse>;
+
+    /// Marks every unread notification of the profile as read and returns how many there were.
+    async fn mark_all_read(&self, profile_id: i32) -> Result<u64, ErrorResponse>;
+}
+
+#[derive(Clone)]
+pub struct SqlNotificationRepository {
+    db: Arc<ConnectionManager>,
+}
+
+impl SqlNotificationRepository {
+    pub fn new(db: Arc<ConnectionManager>) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl NotificationRepository for SqlNotificationRepository {
+    async fn get_alert_rules(
+        &self,
+        profile_id: i32,
+    ) -> Result<Vec<notification_model::BudgetAlertRuleModel>, ErrorResponse> {
+        let pool = self.db.pool()?;
+        let rules = with_pool!(&pool, |pool| {
+            sqlx::query_as::<_, notification_model::BudgetAlertRuleModel>(
+                r#"
+                SELECT * FROM budget_alert_rules WHERE profile_id = $1 ORDER BY category_id
+                "#,
+            )
+            .bind(profile_id)
+            .fetch_all(pool)
+            .await?
+        });
+
+        Ok(rules)
+    }
+
+    async fn save_alert_rule(
+        &self,
+        rule: notification_model::BudgetAlertRuleModel,
+    ) -> Result<notification_model::BudgetAlertRuleModel, ErrorResponse> {
+        let pool = self.db.pool()?;
+        let saved = with_pool!(&pool, |pool| {
+            sqlx::query_as::<_, notification_model::BudgetAlertRuleModel>(
+                r#"
+                INSERT INTO budget_alert_rules (
+                    profile_id, category_id, warn_at_percent, notify_desktop, updated_at
+                )
+                VALUES ($1, $2, $3, $4, $5)
+                ON CONFLICT (profile_id, category_id) DO UPDATE
+                SET warn_at_percent = excluded.warn_at_percent,
+                    notify_desktop = excluded.notify_desktop,
+                    updated_at = excluded.updated_at
+                RETURNING *
+                "#,
+            )
+            .bind(rule.profile_id)
+            .bind(rule.category_id)
+            .bind(rule.warn_at_percent)
+            .bind(rule.notify_desktop)
+            .bind(rule.updated_at)
+            .fetch_one(pool)
+            .await?
+        });
+
+        Ok(saved)
+    }
+
+    async fn delete_alert_rule(
+        &self,
+        profile_id: i32,
+        category_id: i32,
+    ) -> Result<bool, ErrorResponse> {
+        let pool = self.db.pool()?;
+        let result = with_pool!(&pool, |pool| {
+            sqlx::query(
+                r#"
+                DELETE FROM budget_alert_rules WHERE profile_id = $1 AND category_id = $2
+                "#,
+            )
+            .bind(profile_id)
+            .bind(category_id)
+            .execute(pool)
+            .await?
+            .rows_affected()
+        });
+
+        Ok(result > 0)
+    }
+
+    async fn get_notifications(
+        &self,
+        profile_id: i32,
+        unread_only: bool,
+    ) -> Result<Vec<notification_model::NotificationModel>, ErrorResponse> {
+        let pool = self.db.pool()?;
+        let notifications = with_pool!(&pool, |pool| {
+            sqlx::query_as::<_, notification_model::NotificationModel>(
+                r#"
+                SELECT * FROM notifications
+                WHERE profile_id = $1 AND (NOT $2 OR read_at IS NULL)
+                ORDER BY id DESC
+                "#,
+            )
+            .bind(profile_id)
+            .bind(unread_only)
+            .fetch_all(pool)
+            .await?
+        });
+
+        Ok(notifications)
+    }
+
+    async fn insert_notification(
+        &self,
+        profile_id: i32,
+        kind: notification_model::NotificationKind,
+        payload: String,
+        dedup_key: Option<String>,
+    ) -> Result<Option<notification_model::NotificationModel>, ErrorResponse> {
+        let pool = self.db.pool()?;
+        let now = chrono::Utc::now().naive_utc();
+
+        let inserted = with_pool!(&pool, |pool| {
+            sqlx::query_as::<_, notification_model::NotificationModel>(
+                r#"
+                INSERT INTO notifications (profile_id, created_at, kind, payload, dedup_key)
+                VALUES ($1, $2, $3, $4, $5)
+                ON CONFLICT (profile_id, dedup_key) DO NOTHING
+                RETURNING *
+                "#,
+            )
+            .bind(profile_id)
+            .bind(now)
+            .bind(kind.as_str())
+            .bind(&payload)
+            .bind(&dedup_key)
+            .fetch_optional(pool)
+            .await?
+        });
+
+        Ok(inserted)
+    }
+
+    async fn set_read(
+        &self,
+        profile_id: i32,
+        id: i32,
+        read: bool,
+    ) -> Result<Option<notification_model::NotificationModel>, ErrorResponse> {
+        let pool = self.db.pool()?;
+        let read_at = read.then(|| chrono::Utc::now().naive_utc());
+
+        let updated = with_pool!(&pool, |pool| {
+            sqlx::query_as::<_, notification_model::NotificationModel>(
+                r#"
+                UPDATE notifications
+                SET read_at = CASE WHEN $3 IS NULL THEN NULL ELSE COALESCE(read_at, $3) END
+                WHERE id = $1 AND profile_id = $2
+                RETURNING *
+                "#,
+            )
+            .bind(id)
+            .bind(profile_id)
+            .bind(read_at)
+            .fetch_optional(pool)
+            .await?
+        });
+
+        Ok(updated)
+    }
+
+    async fn mark_all_read(&self, profile_id: i32) -> Result<u64, ErrorResponse> {
+        let pool = self.db.pool()?;
+        let now = chrono::Utc::now().naive_utc();
+
+        let marked = with_pool!(&pool, |pool| {
+            sqlx::query(
+                r#"
+                UPDATE notifications SET read_at = $2
+                WHERE profile_id = $1 AND read_at IS NULL
+                "#,
+            )
+            .bind(profile_id)
+            .bind(now)
+            .execute(pool)
+            .await?
+            .rows_affected()
+        });
+
+        Ok(marked)
+    }
+}
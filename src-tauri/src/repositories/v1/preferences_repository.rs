@@ -0,0 +1,91 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+
+use crate::{
+    models::v1::preferences_model,
+    utils::{
+        db::{connection::ConnectionManager, pool::with_pool},
+        error::mapping::ErrorResponse,
+    },
+};
+
+#[async_trait]
+pub trait PreferencesRepository: Send + Sync {
+    async fn get_by_profile_id(
+        &self,
+        profile_id: i32,
+    ) -> Result<Option<preferences_model::ProfilePreferencesModel>, ErrorResponse>;
+
+    /// Inserts or replaces the profile's preferences.
+    async fn save(
+        &self,
+        profile_id: i32,
+        version: i32,
+        preferences: String,
+    ) -> Result<preferences_model::ProfilePreferencesModel, ErrorResponse>;
+}
+
+#[derive(Clone)]
+pub struct SqlPreferencesRepository {
+    db: Arc<ConnectionManager>,
+}
+
+impl SqlPreferencesRepository {
+    pub fn new(db: Arc<ConnectionManager>) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl PreferencesRepository for SqlPreferencesRepository {
+    async fn get_by_profile_id(
+        &self,
+        profile_id: i32,
+    ) -> Result<Option<preferences_model::ProfilePreferencesModel>, ErrorResponse> {
+        let pool = self.db.pool()?;
+        let preferences = with_pool!(&pool, |pool| {
+            sqlx::query_as::<_, preferences_model::ProfilePreferencesModel>(
+                r#"
+                SELECT * FROM profile_preferences WHERE profile_id = $1
+                "#,
+            )
+            .bind(profile_id)
+            .fetch_optional(pool)
+            .await?
+        });
+
+        Ok(preferences)
+    }
+
+    async fn save(
+        &self,
+        profile_id: i32,
+        version: i32,
+        preferences: String,
+    ) -> Result<preferences_model::ProfilePreferencesModel, ErrorResponse> {
+        let pool = self.db.pool()?;
+        let now = chrono::Utc::now().naive_utc();
+
+        let saved = with_pool!(&pool, |pool| {
+            sqlx::query_as::<_, preferences_model::ProfilePreferencesModel>(
+                r#"
+                INSERT INTO profile_preferences (profile_id, version, preferences, updated_at)
+                VALUES ($1, $2, $3, $4)
+                ON CONFLICT (profile_id) DO UPDATE
+                SET version = excluded.version,
+                    preferences = excluded.preferences,
+                    updated_at = excluded.updated_at
+                RETURNING *
+                "#,
+            )
+            .bind(profile_id)
+            .bind(version)
+            .bind(&preferences)
+            .bind(now)
+            .fetch_one(pool)
+            .await?
+        });
+
+        Ok(saved)
+    }
+}
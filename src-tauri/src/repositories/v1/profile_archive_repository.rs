@@ -0,0 +1,122 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+
+use crate::{
+    models::v1::{
+        audit_model::{AuditEventModel, AuditOperation},
+        notification_model, preferences_model, profile_model,
+    },
+    repositories::v1::audit_repository::record_audit_event,
+    utils::{
+        db::{connection::ConnectionManager, pool::with_pool},
+        error::mapping::ErrorResponse,
+    },
+};
+
+#[async_trait]
+pub trait ProfileArchiveRepository: Send + Sync {
+    /// Inserts an imported profile and everything it owns in one transaction. The ids in the
+    /// models are ignored: the profile gets a fresh one and its records follow it. The import is
+    /// audited as a creation by `actor`.
+    async fn import_profile(
+        &self,
+        profile: profile_model::ProfileModel,
+        preferences: Option<preferences_model::ProfilePreferencesModel>,
+        budget_alert_rules: Vec<notification_model::BudgetAlertRuleModel>,
+        actor: Option<i32>,
+    ) -> Result<profile_model::ProfileModel, ErrorResponse>;
+}
+
+#[derive(Clone)]
+pub struct SqlProfileArchiveRepository {
+    db: Arc<ConnectionManager>,
+}
+
+impl SqlProfileArchiveRepository {
+    pub fn new(db: Arc<ConnectionManager>) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl ProfileArchiveRepository for SqlProfileArchiveRepository {
+    async fn import_profile(
+        &self,
+        profile: profile_model::ProfileModel,
+        preferences: Option<preferences_model::ProfilePreferencesModel>,
+        budget_alert_rules: Vec<notification_model::BudgetAlertRuleModel>,
+        actor: Option<i32>,
+    ) -> Result<profile_model::ProfileModel, ErrorResponse> {
+        let pool = self.db.pool()?;
+        let imported_profile = with_pool!(&pool, |pool| {
+            let mut tx = pool.begin().await?;
+
+            let imported_profile = sqlx::query_as::<_, profile_model::ProfileModel>(
+                r#"
+                INSERT INTO profiles (
+                    username, display_name, profile_picture_url,
+                    created_at, updated_at, secret_hash, secret_kind
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                RETURNING *
+                "#,
+            )
+            .bind(&profile.username)
+            .bind(&profile.display_name)
+            .bind(&profile.profile_picture_url)
+            .bind(profile.created_at)
+            .bind(profile.updated_at)
+            .bind(&profile.secret_hash)
+            .bind(&profile.secret_kind)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            if let Some(preferences) = &preferences {
+                sqlx::query(
+                    r#"
+                    INSERT INTO profile_preferences (profile_id, version, preferences, updated_at)
+                    VALUES ($1, $2, $3, $4)
+                    "#,
+                )
+                .bind(imported_profile.id)
+                .bind(preferences.version)
+                .bind(&preferences.preferences)
+                .bind(preferences.updated_at)
+                .execute(&mut *tx)
+                .await?;
+            }
+
+            for rule in &budget_alert_rules {
+                sqlx::query(
+                    r#"
+                    INSERT INTO budget_alert_rules (
+                        profile_id, category_id, warn_at_percent, notify_desktop, updated_at
+                    )
+                    VALUES ($1, $2, $3, $4, $5)
+                    "#,
+                )
+                .bind(imported_profile.id)
+                .bind(rule.category_id)
+                .bind(rule.warn_at_percent)
+                .bind(rule.notify_desktop)
+                .bind(rule.updated_at)
+                .execute(&mut *tx)
+                .await?;
+            }
+
+            record_audit_event!(
+                &mut *tx,
+                AuditEventModel::change(
+                    actor,
+                    AuditOperation::Create,
+                    None,
+                    Some(&imported_profile)
+                )
+            );
+            tx.commit().await?;
+            imported_profile
+        });
+
+        Ok(imported_profile)
+    }
+}
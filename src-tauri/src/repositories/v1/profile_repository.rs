@@ -1,147 +1,682 @@
-use sqlx::PgPool;
+use async_trait::async_trait;
+use std::sync::Arc;
 
 use crate::{
-    models::v1::profile_model,
-    utils::{error::mapping::ErrorResponse, fs::profile_picture},
+    models::{
+        patch::Patch,
+        v1::{
+            audit_model::{AuditEventModel, AuditOperation},
+            profile_model,
+        },
+    },
+    repositories::v1::audit_repository::record_audit_event,
+    utils::{
+        db::{
+            connection::ConnectionManager,
+            pool::{with_pool, DbBackend},
+        },
+        error::mapping::ErrorResponse,
+    },
 };
 
+/// Methods that change a profile record the change in the audit log, in the same transaction, as
+/// made by `actor` (the active profile, or `None` for the app itself).
+#[async_trait]
+pub trait ProfileRepository: Send + Sync {
+    async fn create_profile(
+        &self,
+        username: String,
+        display_name: Option<String>,
+        profile_picture_url: Option<String>,
+        actor: Option<i32>,
+    ) -> Result<profile_model::ProfileModel, ErrorResponse>;
+
+    async fn get_all(&self) -> Result<Vec<profile_model::ProfileModel>, ErrorResponse>;
+
+    async fn get_one_by_id(
+        &self,
+        profile_id: i32,
+    ) -> Result<Option<profile_model::ProfileModel>, ErrorResponse>;
+
+    /// Picture paths of active and trashed profiles; anything else in the pfps dir is garbage.
+    async fn get_profile_picture_urls(&self) -> Result<Vec<String>, ErrorResponse>;
+
+    async fn get_one_by_username(
+        &self,
+        profile_username: String,
+    ) -> Result<Option<profile_model::ProfileModel>, ErrorResponse>;
+
+    /// Moves the profile to the trash. Returns `false` when there was no active profile with that id.
+    async fn delete_profile(
+        &self,
+        profile_id: i32,
+        actor: Option<i32>,
+    ) -> Result<bool, ErrorResponse>;
+
+    async fn get_deleted(&self) -> Result<Vec<profile_model::ProfileModel>, ErrorResponse>;
+
+    async fn get_deleted_by_id(
+        &self,
+        profile_id: i32,
+    ) -> Result<Option<profile_model::ProfileModel>, ErrorResponse>;
+
+    /// Takes the profile out of the trash under `username`.
+    async fn restore_profile(
+        &self,
+        profile_id: i32,
+        username: String,
+        actor: Option<i32>,
+    ) -> Result<Option<profile_model::ProfileModel>, ErrorResponse>;
+
+    /// Hard-deletes a trashed profile. Tables owned by a profile reference it with
+    /// `ON DELETE CASCADE`, so their rows go with it.
+    async fn purge_profile(
+        &self,
+        profile_id: i32,
+        actor: Option<i32>,
+    ) -> Result<bool, ErrorResponse>;
+
+    /// Applies the given changes and bumps `updated_at`.
+    async fn update_profile(
+        &self,
+        profile_id: i32,
+        username: Option<String>,
+        display_name: Patch<String>,
+        profile_picture_url: Patch<String>,
+        actor: Option<i32>,
+    ) -> Result<Option<profile_model::ProfileModel>, ErrorResponse>;
+
+    /// Replaces the secret (`None` removes it) and clears any unlock lockout.
+    async fn set_secret(
+        &self,
+        profile_id: i32,
+        secret_hash: Option<String>,
+        secret_kind: Option<String>,
+        actor: Option<i32>,
+    ) -> Result<bool, ErrorResponse>;
+
+    async fn set_unlock_attempts(
+        &self,
+        profile_id: i32,
+        failed_unlock_attempts: i32,
+        unlock_blocked_until: Option<chrono::NaiveDateTime>,
+    ) -> Result<(), ErrorResponse>;
+
+    /// Counts a failed unlock attempt, unless attempts are already blocked at `now`, and blocks
+    /// further ones for the `lockout` the new count calls for. Both happen in one transaction, so
+    /// concurrent attempts cannot overwrite each other's count. Returns until when attempts are
+    /// blocked, if they are.
+    async fn record_failed_unlock(
+        &self,
+        profile_id: i32,
+        now: chrono::NaiveDateTime,
+        lockout: fn(i32) -> Option<chrono::Duration>,
+    ) -> Result<Option<chrono::NaiveDateTime>, ErrorResponse>;
+
+    /// Writes back the names, picture and trash state of `profile`, provided the row was last
+    /// changed at `expected_updated_at`. The secret and unlock attempts stay as they are. Returns
+    /// `None` when the row changed since or is gone.
+    async fn revert_profile(
+        &self,
+        profile: profile_model::ProfileModel,
+        expected_updated_at: chrono::NaiveDateTime,
+        actor: Option<i32>,
+    ) -> Result<Option<profile_model::ProfileModel>, ErrorResponse>;
+}
+
 #[derive(Clone)]
-pub struct ProfileRepository {
-    pool: PgPool,
+pub struct SqlProfileRepository {
+    db: Arc<ConnectionManager>,
 }
 
-impl ProfileRepository {
-    pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+impl SqlProfileRepository {
+    pub fn new(db: Arc<ConnectionManager>) -> Self {
+        Self { db }
     }
+}
+
+/// Reads a profile as it is before a change. Postgres locks the row so nothing else changes it
+/// before the transaction ends; SQLite fails the transaction instead when another write got in.
+fn select_for_change(backend: DbBackend) -> &'static str {
+    match backend {
+        DbBackend::Postgres => "SELECT * FROM profiles WHERE id = $1 FOR UPDATE",
+        DbBackend::Sqlite => "SELECT * FROM profiles WHERE id = $1",
+    }
+}
 
-    pub async fn create_profile(
+#[async_trait]
+impl ProfileRepository for SqlProfileRepository {
+    async fn create_profile(
         &self,
         username: String,
         display_name: Option<String>,
-        profile_picture_bytes: Option<Vec<u8>>,
+        profile_picture_url: Option<String>,
+        actor: Option<i32>,
     ) -> Result<profile_model::ProfileModel, ErrorResponse> {
-        let profile_picture_url = if let Some(bytes) = profile_picture_bytes {
-            let path = profile_picture::save_profile_picture(&bytes)?;
-            Some(path.to_string_lossy().to_string())
-        } else {
-            None
-        };
-
-        let display_name = if let Some(display_name) = display_name {
-            Some(display_name.trim().to_string())
-        } else {
-            None
-        };
-
-        let created_profile = sqlx::query_as::<_, profile_model::ProfileModel>(
-            r#"
-            INSERT INTO profiles (username, display_name, profile_picture_url)
-            VALUES ($1, $2, $3)
-            RETURNING *
-            "#,
-        )
-        .bind(username)
-        .bind(display_name)
-        .bind(&profile_picture_url)
-        .fetch_one(&self.pool)
-        .await?;
+        let pool = self.db.pool()?;
+        let created_profile = with_pool!(&pool, |pool| {
+            let mut tx = pool.begin().await?;
+
+            let created_profile = sqlx::query_as::<_, profile_model::ProfileModel>(
+                r#"
+                INSERT INTO profiles (username, display_name, profile_picture_url)
+                VALUES ($1, $2, $3)
+                RETURNING *
+                "#,
+            )
+            .bind(&username)
+            .bind(&display_name)
+            .bind(&profile_picture_url)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            record_audit_event!(
+                &mut *tx,
+                AuditEventModel::change(
+                    actor,
+                    AuditOperation::Create,
+                    None,
+                    Some(&created_profile)
+                )
+            );
+            tx.commit().await?;
+            created_profile
+        });
 
         Ok(created_profile)
     }
 
-    pub async fn get_all(&self) -> Result<Vec<profile_model::ProfileModel>, ErrorResponse> {
-        let profiles = sqlx::query_as::<_, profile_model::ProfileModel>(
-            r#"
-            SELECT * FROM profiles WHERE deleted_at IS NULL
-            "#,
-        )
-        .fetch_all(&self.pool)
-        .await?;
+    async fn get_all(&self) -> Result<Vec<profile_model::ProfileModel>, ErrorResponse> {
+        let pool = self.db.pool()?;
+        let profiles = with_pool!(&pool, |pool| {
+            sqlx::query_as::<_, profile_model::ProfileModel>(
+                r#"
+                SELECT * FROM profiles WHERE deleted_at IS NULL
+                "#,
+            )
+            .fetch_all(pool)
+            .await?
+        });
 
         Ok(profiles)
     }
 
-    pub async fn get_one_by_id(
+    async fn get_one_by_id(
         &self,
         profile_id: i32,
     ) -> Result<Option<profile_model::ProfileModel>, ErrorResponse> {
-        let profile = sqlx::query_as::<_, profile_model::ProfileModel>(
-            r#"
-            SELECT * FROM profiles WHERE id = $1 AND deleted_at IS NULL
-            "#,
-        )
-        .bind(profile_id)
-        .fetch_optional(&self.pool)
-        .await?;
+        let pool = self.db.pool()?;
+        let profile = with_pool!(&pool, |pool| {
+            sqlx::query_as::<_, profile_model::ProfileModel>(
+                r#"
+                SELECT * FROM profiles WHERE id = $1 AND deleted_at IS NULL
+                "#,
+            )
+            .bind(profile_id)
+            .fetch_optional(pool)
+            .await?
+        });
 
         Ok(profile)
     }
 
-    pub async fn get_one_by_username(
+    async fn get_profile_picture_urls(&self) -> Result<Vec<String>, ErrorResponse> {
+        let pool = self.db.pool()?;
+        let urls = with_pool!(&pool, |pool| {
+            sqlx::query_scalar::<_, String>(
+                r#"
+                SELECT profile_picture_url FROM profiles
+                WHERE profile_picture_url IS NOT NULL
+                "#,
+            )
+            .fetch_all(pool)
+            .await?
+        });
+
+        Ok(urls)
+    }
+
+    async fn get_one_by_username(
         &self,
         profile_username: String,
     ) -> Result<Option<profile_model::ProfileModel>, ErrorResponse> {
-        let profile = sqlx::query_as::<_, profile_model::ProfileModel>(
-            r#"
-            SELECT * FROM profiles WHERE username = $1 AND deleted_at IS NULL
-            "#,
-        )
-        .bind(profile_username)
-        .fetch_optional(&self.pool)
-        .await?;
+        let pool = self.db.pool()?;
+        let profile = with_pool!(&pool, |pool| {
+            sqlx::query_as::<_, profile_model::ProfileModel>(
+                r#"
+                SELECT * FROM profiles WHERE username = $1 AND deleted_at IS NULL
+                "#,
+            )
+            .bind(&profile_username)
+            .fetch_optional(pool)
+            .await?
+        });
 
         Ok(profile)
     }
 
-    pub async fn delete_profile(&self, profile_id: i32) -> Result<(), ErrorResponse> {
-        sqlx::query(
-            r#"
-            UPDATE profiles
-            SET deleted_at = NOW(),
-                username = CONCAT('deleted:', id)
-            WHERE id = $1 AND deleted_at IS NULL;
-            "#,
-        )
-        .bind(profile_id)
-        .execute(&self.pool)
-        .await?;
+    async fn delete_profile(
+        &self,
+        profile_id: i32,
+        actor: Option<i32>,
+    ) -> Result<bool, ErrorResponse> {
+        let pool = self.db.pool()?;
+        let select_before = select_for_change(pool.backend());
+        let now = chrono::Utc::now().naive_utc();
 
-        Ok(())
+        let deleted = with_pool!(&pool, |pool| {
+            let mut tx = pool.begin().await?;
+
+            let before = sqlx::query_as::<_, profile_model::ProfileModel>(select_before)
+                .bind(profile_id)
+                .fetch_optional(&mut *tx)
+                .await?;
+            let deleted_profile = sqlx::query_as::<_, profile_model::ProfileModel>(
+                r#"
+                UPDATE profiles
+                SET deleted_at = $1,
+                    updated_at = $1,
+                    original_username = username,
+                    username = 'deleted:' || id
+                WHERE id = $2 AND deleted_at IS NULL
+                RETURNING *
+                "#,
+            )
+            .bind(now)
+            .bind(profile_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+            if deleted_profile.is_some() {
+                record_audit_event!(
+                    &mut *tx,
+                    AuditEventModel::change(
+                        actor,
+                        AuditOperation::Delete,
+                        before.as_ref(),
+                        deleted_profile.as_ref()
+                    )
+                );
+            }
+            tx.commit().await?;
+            deleted_profile.is_some()
+        });
+
+        Ok(deleted)
+    }
+
+    async fn get_deleted(&self) -> Result<Vec<profile_model::ProfileModel>, ErrorResponse> {
+        let pool = self.db.pool()?;
+        let profiles = with_pool!(&pool, |pool| {
+            sqlx::query_as::<_, profile_model::ProfileModel>(
+                r#"
+                SELECT * FROM profiles WHERE deleted_at IS NOT NULL ORDER BY deleted_at DESC
+                "#,
+            )
+            .fetch_all(pool)
+            .await?
+        });
+
+        Ok(profiles)
     }
 
-    pub async fn update_profile(
+    async fn get_deleted_by_id(
+        &self,
+        profile_id: i32,
+    ) -> Result<Option<profile_model::ProfileModel>, ErrorResponse> {
+        let pool = self.db.pool()?;
+        let profile = with_pool!(&pool, |pool| {
+            sqlx::query_as::<_, profile_model::ProfileModel>(
+                r#"
+                SELECT * FROM profiles WHERE id = $1 AND deleted_at IS NOT NULL
+                "#,
+            )
+            .bind(profile_id)
+            .fetch_optional(pool)
+            .await?
+        });
+
+        Ok(profile)
+    }
+
+    async fn restore_profile(
+        &self,
+        profile_id: i32,
+        username: String,
+        actor: Option<i32>,
+    ) -> Result<Option<profile_model::ProfileModel>, ErrorResponse> {
+        let pool = self.db.pool()?;
+        let select_before = select_for_change(pool.backend());
+        let now = chrono::Utc::now().naive_utc();
+
+        let restored_profile = with_pool!(&pool, |pool| {
+            let mut tx = pool.begin().await?;
+
+            let before = sqlx::query_as::<_, profile_model::ProfileModel>(select_before)
+                .bind(profile_id)
+                .fetch_optional(&mut *tx)
+                .await?;
+            let restored_profile = sqlx::query_as::<_, profile_model::ProfileModel>(
+                r#"
+                UPDATE profiles
+                SET deleted_at = NULL,
+                    original_username = NULL,
+                    username = $1,
+                    updated_at = $2
+                WHERE id = $3 AND deleted_at IS NOT NULL
+                RETURNING *
+                "#,
+            )
+            .bind(&username)
+            .bind(now)
+            .bind(profile_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+            if restored_profile.is_some() {
+                record_audit_event!(
+                    &mut *tx,
+                    AuditEventModel::change(
+                        actor,
+                        AuditOperation::Restore,
+                        before.as_ref(),
+                        restored_profile.as_ref()
+                    )
+                );
+            }
+            tx.commit().await?;
+            restored_profile
+        });
+
+        Ok(restored_profile)
+    }
+
+    async fn purge_profile(
+        &self,
+        profile_id: i32,
+        actor: Option<i32>,
+    ) -> Result<bool, ErrorResponse> {
+        let pool = self.db.pool()?;
+        let purged = with_pool!(&pool, |pool| {
+            let mut tx = pool.begin().await?;
+
+            let purged_profile = sqlx::query_as::<_, profile_model::ProfileModel>(
+                r#"
+                DELETE FROM profiles WHERE id = $1 AND deleted_at IS NOT NULL
+                RETURNING *
+                "#,
+            )
+            .bind(profile_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+            record_audit_event!(
+                &mut *tx,
+                AuditEventModel::change(
+                    actor,
+                    AuditOperation::Purge,
+                    purged_profile.as_ref(),
+                    None
+                )
+            );
+            tx.commit().await?;
+            purged_profile.is_some()
+        });
+
+        Ok(purged)
+    }
+
+    async fn update_profile(
         &self,
         profile_id: i32,
         username: Option<String>,
-        display_name: Option<String>,
-        profile_picture_bytes: Option<Vec<u8>>,
-    ) -> Result<profile_model::ProfileModel, ErrorResponse> {
-        let profile_picture_url = if let Some(bytes) = profile_picture_bytes {
-            let path = profile_picture::save_profile_picture(&bytes)?;
-            Some(path.to_string_lossy().to_string())
-        } else {
-            None
-        };
-
-        let updated_profile = sqlx::query_as::<_, profile_model::ProfileModel>(
-            r#"
-            UPDATE profiles
-            SET
-                username = COALESCE($1, username),
-                display_name = COALESCE($2, display_name),
-                profile_picture_url = COALESCE($3, profile_picture_url)
-            WHERE id = $4 AND deleted_at IS NULL
-            RETURNING *
-            "#,
-        )
-        .bind(username)
-        .bind(display_name)
-        .bind(&profile_picture_url)
-        .bind(profile_id)
-        .fetch_one(&self.pool)
-        .await?;
+        display_name: Patch<String>,
+        profile_picture_url: Patch<String>,
+        actor: Option<i32>,
+    ) -> Result<Option<profile_model::ProfileModel>, ErrorResponse> {
+        let pool = self.db.pool()?;
+        let select_before = select_for_change(pool.backend());
+        let now = chrono::Utc::now().naive_utc();
+        let display_name = display_name.into_change();
+        let profile_picture_url = profile_picture_url.into_change();
+
+        let updated_profile = with_pool!(&pool, |pool| {
+            let mut tx = pool.begin().await?;
+
+            let before = sqlx::query_as::<_, profile_model::ProfileModel>(select_before)
+                .bind(profile_id)
+                .fetch_optional(&mut *tx)
+                .await?;
+            let updated_profile = sqlx::query_as::<_, profile_model::ProfileModel>(
+                r#"
+                UPDATE profiles
+                SET
+                    username = COALESCE($1, username),
+                    display_name = CASE WHEN $2 THEN $3 ELSE display_name END,
+                    profile_picture_url = CASE WHEN $4 THEN $5 ELSE profile_picture_url END,
+                    updated_at = $6
+                WHERE id = $7 AND deleted_at IS NULL
+                RETURNING *
+                "#,
+            )
+            .bind(&username)
+            .bind(display_name.is_some())
+            .bind(display_name.as_ref().and_then(Option::as_deref))
+            .bind(profile_picture_url.is_some())
+            .bind(profile_picture_url.as_ref().and_then(Option::as_deref))
+            .bind(now)
+            .bind(profile_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+            if updated_profile.is_some() {
+                record_audit_event!(
+                    &mut *tx,
+                    AuditEventModel::change(
+                        actor,
+                        AuditOperation::Update,
+                        before.as_ref(),
+                        updated_profile.as_ref()
+                    )
+                );
+            }
+            tx.commit().await?;
+            updated_profile
+        });
 
         Ok(updated_profile)
     }
+
+    async fn set_secret(
+        &self,
+        profile_id: i32,
+        secret_hash: Option<String>,
+        secret_kind: Option<String>,
+        actor: Option<i32>,
+    ) -> Result<bool, ErrorResponse> {
+        let pool = self.db.pool()?;
+        let select_before = select_for_change(pool.backend());
+        let now = chrono::Utc::now().naive_utc();
+
+        let updated = with_pool!(&pool, |pool| {
+            let mut tx = pool.begin().await?;
+
+            let before = sqlx::query_as::<_, profile_model::ProfileModel>(select_before)
+                .bind(profile_id)
+                .fetch_optional(&mut *tx)
+                .await?;
+            let updated_profile = sqlx::query_as::<_, profile_model::ProfileModel>(
+                r#"
+                UPDATE profiles
+                SET secret_hash = $1,
+                    secret_kind = $2,
+                    failed_unlock_attempts = 0,
+                    unlock_blocked_until = NULL,
+                    updated_at = $3
+                WHERE id = $4 AND deleted_at IS NULL
+                RETURNING *
+                "#,
+            )
+            .bind(&secret_hash)
+            .bind(&secret_kind)
+            .bind(now)
+            .bind(profile_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+            if updated_profile.is_some() {
+                record_audit_event!(
+                    &mut *tx,
+                    AuditEventModel::change(
+                        actor,
+                        AuditOperation::Update,
+                        before.as_ref(),
+                        updated_profile.as_ref()
+                    )
+                );
+            }
+            tx.commit().await?;
+            updated_profile.is_some()
+        });
+
+        Ok(updated)
+    }
+
+    async fn set_unlock_attempts(
+        &self,
+        profile_id: i32,
+        failed_unlock_attempts: i32,
+        unlock_blocked_until: Option<chrono::NaiveDateTime>,
+    ) -> Result<(), ErrorResponse> {
+        let pool = self.db.pool()?;
+        with_pool!(&pool, |pool| {
+            sqlx::query(
+                r#"
+                UPDATE profiles
+                SET failed_unlock_attempts = $1,
+                    unlock_blocked_until = $2
+                WHERE id = $3
+                "#,
+            )
+            .bind(failed_unlock_attempts)
+            .bind(unlock_blocked_until)
+            .bind(profile_id)
+            .execute(pool)
+            .await?;
+        });
+
+        Ok(())
+    }
+
+    async fn record_failed_unlock(
+        &self,
+        profile_id: i32,
+        now: chrono::NaiveDateTime,
+        lockout: fn(i32) -> Option<chrono::Duration>,
+    ) -> Result<Option<chrono::NaiveDateTime>, ErrorResponse> {
+        let pool = self.db.pool()?;
+        let blocked_until = with_pool!(&pool, |pool| {
+            let mut tx = pool.begin().await?;
+
+            // Leaves the row alone when a concurrent attempt started a lockout in the meantime.
+            let failed_unlock_attempts = sqlx::query_scalar::<_, i32>(
+                r#"
+                UPDATE profiles
+                SET failed_unlock_attempts = failed_unlock_attempts + 1
+                WHERE id = $1 AND (unlock_blocked_until IS NULL OR unlock_blocked_until <= $2)
+                RETURNING failed_unlock_attempts
+                "#,
+            )
+            .bind(profile_id)
+            .bind(now)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+            let blocked_until = match failed_unlock_attempts {
+                Some(failed_unlock_attempts) => {
+                    let blocked_until =
+                        lockout(failed_unlock_attempts).map(|lockout| now + lockout);
+
+                    if blocked_until.is_some() {
+                        sqlx::query("UPDATE profiles SET unlock_blocked_until = $1 WHERE id = $2")
+                            .bind(blocked_until)
+                            .bind(profile_id)
+                            .execute(&mut *tx)
+                            .await?;
+                    }
+                    blocked_until
+                }
+                None => sqlx::query_scalar::<_, Option<chrono::NaiveDateTime>>(
+                    "SELECT unlock_blocked_until FROM profiles WHERE id = $1",
+                )
+                .bind(profile_id)
+                .fetch_optional(&mut *tx)
+                .await?
+                .flatten(),
+            };
+            tx.commit().await?;
+            blocked_until
+        });
+
+        Ok(blocked_until)
+    }
+
+    async fn revert_profile(
+        &self,
+        profile: profile_model::ProfileModel,
+        expected_updated_at: chrono::NaiveDateTime,
+        actor: Option<i32>,
+    ) -> Result<Option<profile_model::ProfileModel>, ErrorResponse> {
+        let pool = self.db.pool()?;
+        let select_before = select_for_change(pool.backend());
+        let now = chrono::Utc::now().naive_utc();
+
+        let reverted_profile = with_pool!(&pool, |pool| {
+            let mut tx = pool.begin().await?;
+
+            let before = sqlx::query_as::<_, profile_model::ProfileModel>(select_before)
+                .bind(profile.id)
+                .fetch_optional(&mut *tx)
+                .await?;
+            let reverted_profile = sqlx::query_as::<_, profile_model::ProfileModel>(
+                r#"
+                UPDATE profiles
+                SET username = $1,
+                    original_username = $2,
+                    display_name = $3,
+                    profile_picture_url = $4,
+                    deleted_at = $5,
+                    updated_at = $6
+                WHERE id = $7 AND updated_at = $8
+                RETURNING *
+                "#,
+            )
+            .bind(&profile.username)
+            .bind(&profile.original_username)
+            .bind(&profile.display_name)
+            .bind(&profile.profile_picture_url)
+            .bind(profile.deleted_at)
+            .bind(now)
+            .bind(profile.id)
+            .bind(expected_updated_at)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+            if let (Some(before), Some(after)) = (&before, &reverted_profile) {
+                record_audit_event!(
+                    &mut *tx,
+                    AuditEventModel::change(
+                        actor,
+                        before.operation_to(after),
+                        Some(before),
+                        Some(after)
+                    )
+                );
+            }
+            tx.commit().await?;
+            reverted_profile
+        });
+
+        Ok(reverted_profile)
+    }
 }
@@ -0,0 +1,40 @@
+use async_trait::async_trait;
+
+use crate::{
+    services::report_service::{
+        CategorySpendingReport, CategoryTrendReport, IncomeExpenseReport, PayeeSpending,
+        ReportFilterDTO, ReportInterval,
+    },
+    utils::error::mapping::ErrorResponse,
+};
+
+/// Aggregates over the transactions of one profile. The SQL implementation, with the grouping
+/// done by the database, comes with the ledger tables.
+#[async_trait]
+pub trait ReportRepository: Send + Sync {
+    async fn spending_by_category(
+        &self,
+        profile_id: i32,
+        filter: &ReportFilterDTO,
+    ) -> Result<CategorySpendingReport, ErrorResponse>;
+
+    async fn income_vs_expense(
+        &self,
+        profile_id: i32,
+        filter: &ReportFilterDTO,
+    ) -> Result<IncomeExpenseReport, ErrorResponse>;
+
+    async fn top_payees(
+        &self,
+        profile_id: i32,
+        filter: &ReportFilterDTO,
+        limit: usize,
+    ) -> Result<Vec<PayeeSpending>, ErrorResponse>;
+
+    async fn category_trends(
+        &self,
+        profile_id: i32,
+        filter: &ReportFilterDTO,
+        interval: ReportInterval,
+    ) -> Result<CategoryTrendReport, ErrorResponse>;
+}
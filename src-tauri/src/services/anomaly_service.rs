@@ -0,0 +1,337 @@
+use crate::services::{
+    forecast_service::Cents,
+    report_service::{normalize_payee, HistoricalTransaction},
+};
+use chrono::{Datelike, Months, NaiveDate};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+const MIN_GROUP_SIZE: usize = 5;
+const MODIFIED_Z_THRESHOLD: f64 = 3.5; // Iglewicz and Hoaglin's cut-off for the modified z-score.
+const TRAILING_MONTHS: usize = 6;
+const MIN_TRAILING_MONTHS: usize = 3;
+const MIN_MONTHLY_DEVIATION_PERCENT: f64 = 25.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AnomalyScope {
+    Category,
+    Payee,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionAnomaly {
+    pub transaction_id: i32,
+    pub scope: AnomalyScope,
+    pub amount: Cents,
+    pub typical_amount: Cents,
+    pub score: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MonthlyCategoryAnomaly {
+    pub category_id: i32,
+    pub month: NaiveDate,
+    pub spent: Cents,
+    pub trailing_average: Cents,
+    pub deviation_percent: f64,
+}
+
+/// Compact marker meant to be attached to a transaction DTO.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnomalyMarker {
+    pub scopes: Vec<AnomalyScope>,
+    pub typical_amount: Cents,
+    pub score: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnomalyReport {
+    pub transactions: Vec<TransactionAnomaly>,
+    pub months: Vec<MonthlyCategoryAnomaly>,
+}
+
+impl AnomalyReport {
+    /// Marker for a transaction listing: every scope the transaction stands out in, with the
+    /// figures of the strongest one. `None` when the transaction is not unusual.
+    pub fn marker_for(&self, transaction_id: i32) -> Option<AnomalyMarker> {
+        let anomalies: Vec<&TransactionAnomaly> = self
+            .transactions
+            .iter()
+            .filter(|anomaly| anomaly.transaction_id == transaction_id)
+            .collect();
+
+        let strongest = anomalies
+            .iter()
+            .max_by(|a, b| a.score.abs().total_cmp(&b.score.abs()))?;
+
+        Some(AnomalyMarker {
+            scopes: anomalies.iter().map(|anomaly| anomaly.scope).collect(),
+            typical_amount: strongest.typical_amount,
+            score: strongest.score,
+        })
+    }
+}
+
+/// Flags spending that is unusual for the profile's own history.
+///
+/// Single transactions are compared with the rest of their category and payee using the modified
+/// z-score (median absolute deviation), which a handful of large purchases cannot skew. Monthly
+/// category totals are compared with the average of the months before them.
+pub fn detect_anomalies(history: &[HistoricalTransaction]) -> AnomalyReport {
+    let spending: Vec<&HistoricalTransaction> = history.iter().filter(|t| t.amount < 0).collect();
+
+    let mut by_category: HashMap<i32, Vec<&HistoricalTransaction>> = HashMap::new();
+    let mut by_payee: HashMap<String, Vec<&HistoricalTransaction>> = HashMap::new();
+
+    for transaction in &spending {
+        if let Some(category_id) = transaction.category_id {
+            by_category.entry(category_id).or_default().push(transaction);
+        }
+
+        by_payee
+            .entry(normalize_payee(&transaction.payee))
+            .or_default()
+            .push(transaction);
+    }
+
+    let mut transactions: Vec<TransactionAnomaly> = by_category
+        .values()
+        .flat_map(|group| outliers(group, AnomalyScope::Category))
+        .chain(
+            by_payee
+                .values()
+                .flat_map(|group| outliers(group, AnomalyScope::Payee)),
+        )
+        .collect();
+
+    transactions.sort_by_key(|anomaly| (anomaly.transaction_id, anomaly.scope as u8));
+
+    let mut months: Vec<MonthlyCategoryAnomaly> = by_category
+        .iter()
+        .flat_map(|(category_id, group)| monthly_outliers(*category_id, group))
+        .collect();
+
+    months.sort_by_key(|anomaly| (anomaly.month, anomaly.category_id));
+
+    AnomalyReport {
+        transactions,
+        months,
+    }
+}
+
+fn outliers(group: &[&HistoricalTransaction], scope: AnomalyScope) -> Vec<TransactionAnomaly> {
+    if group.len() < MIN_GROUP_SIZE {
+        return Vec::new();
+    }
+
+    let amounts: Vec<f64> = group.iter().map(|t| t.amount.abs() as f64).collect();
+    let typical = median(&amounts);
+    let deviations: Vec<f64> = amounts.iter().map(|amount| (amount - typical).abs()).collect();
+    let mad = median(&deviations);
+    let mean_deviation = deviations.iter().sum::<f64>() / deviations.len() as f64;
+
+    group
+        .iter()
+        .zip(&amounts)
+        .filter_map(|(transaction, amount)| {
+            // When more than half of the amounts are identical the MAD collapses to zero, so fall
+            // back to the mean absolute deviation scaled to the same units.
+            let score = if mad > 0.0 {
+                0.6745 * (amount - typical) / mad
+            } else if mean_deviation > 0.0 {
+                (amount - typical) / (1.253314 * mean_deviation)
+            } else {
+                0.0
+            };
+
+            (score.abs() > MODIFIED_Z_THRESHOLD).then(|| TransactionAnomaly {
+                transaction_id: transaction.id,
+                scope,
+                amount: transaction.amount.abs(),
+                typical_amount: typical.round() as Cents,
+                score,
+            })
+        })
+        .collect()
+}
+
+fn monthly_outliers(
+    category_id: i32,
+    group: &[&HistoricalTransaction],
+) -> Vec<MonthlyCategoryAnomaly> {
+    let mut totals: BTreeMap<NaiveDate, Cents> = BTreeMap::new();
+
+    for transaction in group {
+        if let Some(month) = transaction.date.with_day(1) {
+            *totals.entry(month).or_default() += transaction.amount.abs();
+        }
+    }
+
+    let (Some(first), Some(last)) = (
+        totals.keys().next().copied(),
+        totals.keys().next_back().copied(),
+    ) else {
+        return Vec::new();
+    };
+
+    // Months without spending count as zero, otherwise a quiet month would be skipped entirely.
+    let all_months: Vec<(NaiveDate, Cents)> = (0u32..)
+        .map_while(|n| first.checked_add_months(Months::new(n)))
+        .take_while(|month| *month <= last)
+        .map(|month| (month, totals.get(&month).copied().unwrap_or(0)))
+        .collect();
+
+    all_months
+        .iter()
+        .enumerate()
+        .filter_map(|(index, (month, spent))| {
+            let trailing = &all_months[index.saturating_sub(TRAILING_MONTHS)..index];
+
+            if trailing.len() < MIN_TRAILING_MONTHS {
+                return None;
+            }
+
+            let values: Vec<f64> = trailing.iter().map(|(_, total)| *total as f64).collect();
+            let mean = values.iter().sum::<f64>() / values.len() as f64;
+            let variance =
+                values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64;
+            let difference = *spent as f64 - mean;
+
+            if mean <= 0.0 || difference.abs() <= 2.0 * variance.sqrt() {
+                return None;
+            }
+
+            let deviation_percent = difference / mean * 100.0;
+
+            (deviation_percent.abs() >= MIN_MONTHLY_DEVIATION_PERCENT).then(|| {
+                MonthlyCategoryAnomaly {
+                    category_id,
+                    month: *month,
+                    spent: *spent,
+                    trailing_average: mean.round() as Cents,
+                    deviation_percent,
+                }
+            })
+        })
+        .collect()
+}
+
+fn median(values: &[f64]) -> f64 {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+
+    let middle = sorted.len() / 2;
+
+    if sorted.len() % 2 == 0 {
+        (sorted[middle - 1] + sorted[middle]) / 2.0
+    } else {
+        sorted[middle]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spending(entries: &[(&str, &str, Cents)]) -> Vec<HistoricalTransaction> {
+        entries
+            .iter()
+            .enumerate()
+            .map(|(index, (payee, on, amount))| HistoricalTransaction {
+                id: index as i32 + 1,
+                account_id: 1,
+                category_id: Some(7),
+                payee: (*payee).into(),
+                amount: -amount,
+                date: on.parse().unwrap(),
+                tag_ids: Vec::new(),
+            })
+            .collect()
+    }
+
+    fn same_day(payee: &str, amounts: &[Cents]) -> Vec<HistoricalTransaction> {
+        let entries: Vec<_> = amounts
+            .iter()
+            .map(|amount| (payee, "2025-03-10", *amount))
+            .collect();
+        spending(&entries)
+    }
+
+    #[test]
+    fn outliers_use_the_modified_z_score() {
+        let history = same_day("Market", &[1000, 1100, 900, 1050, 950, 5000]);
+        let report = detect_anomalies(&history);
+
+        // Median 1025, median absolute deviation 75.
+        let scopes: Vec<_> = report
+            .transactions
+            .iter()
+            .map(|anomaly| (anomaly.transaction_id, anomaly.scope))
+            .collect();
+        assert_eq!(
+            scopes,
+            [(6, AnomalyScope::Category), (6, AnomalyScope::Payee)]
+        );
+        assert_eq!(report.transactions[0].typical_amount, 1025);
+        assert!((report.transactions[0].score - 0.6745 * 3975.0 / 75.0).abs() < 1e-9);
+
+        let marker = report.marker_for(6).unwrap();
+        assert_eq!(marker.scopes, [AnomalyScope::Category, AnomalyScope::Payee]);
+        assert_eq!(marker.typical_amount, 1025);
+        assert!(report.marker_for(2).is_none());
+        assert!(report.months.is_empty());
+
+        // Too few transactions to tell what is typical.
+        assert!(detect_anomalies(&history[2..]).transactions.is_empty());
+    }
+
+    #[test]
+    fn identical_amounts_fall_back_to_the_mean_absolute_deviation() {
+        // The MAD is zero; the mean absolute deviation is 50, then about 33.
+        let report = detect_anomalies(&same_day("Bus", &[1000, 1000, 1000, 1000, 1000, 1300]));
+        assert_eq!(report.transactions.len(), 2);
+        assert!((report.transactions[0].score - 300.0 / (1.253314 * 50.0)).abs() < 1e-9);
+
+        let report = detect_anomalies(&same_day("Bus", &[1000, 1000, 1000, 1000, 1100, 1100]));
+        assert!(report.transactions.is_empty());
+
+        let report = detect_anomalies(&same_day("Bus", &[1000; 6]));
+        assert!(report.transactions.is_empty());
+    }
+
+    #[test]
+    fn monthly_spikes_compare_with_the_trailing_months_only() {
+        let mut entries = vec![("Store", "2025-01-05", 50_000)];
+        for on in [
+            "2025-02-05",
+            "2025-03-05",
+            "2025-04-05",
+            "2025-05-05",
+            "2025-06-05",
+            "2025-07-05",
+        ] {
+            entries.push(("Store", on, 1000));
+        }
+
+        // The January outlier is more than six months behind August.
+        let mut history = entries.clone();
+        history.push(("Store", "2025-08-05", 2000));
+        let report = detect_anomalies(&spending(&history));
+        assert_eq!(report.months.len(), 1);
+        let spike = &report.months[0];
+        assert_eq!(spike.month, "2025-08-01".parse::<NaiveDate>().unwrap());
+        assert_eq!(spike.spent, 2000);
+        assert_eq!(spike.trailing_average, 1000);
+        assert_eq!(spike.deviation_percent, 100.0);
+
+        // Beyond two standard deviations but under the minimum deviation.
+        let mut history = entries;
+        history.push(("Store", "2025-08-05", 1200));
+        assert!(detect_anomalies(&spending(&history)).months.is_empty());
+    }
+}
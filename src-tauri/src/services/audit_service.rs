@@ -0,0 +1,178 @@
+use crate::{
+    models::v1::audit_model::AuditFilterModel,
+    repositories::v1::audit_repository::AuditRepository,
+    services::dto::audit_dto::{AuditEventDTO, AuditLogFilterDTO},
+    utils::error::mapping::ErrorResponse,
+};
+use std::sync::Arc;
+use validator::Validate;
+
+const DEFAULT_AUDIT_PAGE_SIZE: u32 = 100;
+
+/// Reads the audit log. Events are written by the repositories, in the transaction of the change
+/// they describe, and are never changed or removed afterwards.
+#[derive(Clone)]
+pub struct AuditService {
+    repo: Arc<dyn AuditRepository>,
+}
+
+impl AuditService {
+    pub fn new(repo: Arc<dyn AuditRepository>) -> Self {
+        Self { repo }
+    }
+
+    pub async fn get_audit_log(
+        &self,
+        filter: AuditLogFilterDTO,
+    ) -> Result<Vec<AuditEventDTO>, ErrorResponse> {
+        filter.validate()?;
+
+        let events = self
+            .repo
+            .get_events(AuditFilterModel {
+                actor_profile_id: filter.actor_profile_id,
+                entity: filter.entity,
+                entity_id: filter.entity_id,
+                operation: filter.operation,
+                from: filter.from,
+                until: filter.until,
+                before_id: filter.before_id,
+                limit: filter.limit.unwrap_or(DEFAULT_AUDIT_PAGE_SIZE),
+            })
+            .await?;
+
+        Ok(events.into_iter().map(AuditEventDTO::from).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::{patch::Patch, v1::audit_model::AuditOperation},
+        repositories::memory::{
+            audit_repository::InMemoryAuditRepository,
+            profile_repository::InMemoryProfileRepository,
+        },
+        services::{
+            dto::profile_dto::{CreateProfileDTO, UpdateProfileDTO},
+            profile_service::ProfileService,
+        },
+        utils::error::mapping::ErrorCode,
+    };
+    use serde_json::json;
+
+    fn services() -> (AuditService, ProfileService) {
+        let audit_repo = Arc::new(InMemoryAuditRepository::new());
+        let profile_repo = Arc::new(InMemoryProfileRepository::with_audit(audit_repo.clone()));
+
+        (
+            AuditService::new(audit_repo),
+            ProfileService::new(profile_repo),
+        )
+    }
+
+    async fn create_profile(profiles: &ProfileService, username: &str) -> i32 {
+        profiles
+            .create_profile(CreateProfileDTO {
+                username: username.into(),
+                display_name: None,
+                profile_picture_bytes: None,
+                profile_picture_crop: None,
+            })
+            .await
+            .unwrap()
+            .id
+    }
+
+    #[tokio::test]
+    async fn profile_changes_are_audited_with_their_actor() {
+        let (audit, profiles) = services();
+        let alice = create_profile(&profiles, "alice").await;
+        let as_alice = profiles.acting_as(Some(alice));
+
+        as_alice
+            .update_profile(
+                alice,
+                UpdateProfileDTO {
+                    display_name: Patch::Value("Alice".into()),
+                    ..UpdateProfileDTO::default()
+                },
+            )
+            .await
+            .unwrap();
+        // A rejected change leaves no trace.
+        as_alice
+            .update_profile(
+                alice,
+                UpdateProfileDTO {
+                    username: Some("x".into()),
+                    ..UpdateProfileDTO::default()
+                },
+            )
+            .await
+            .unwrap_err();
+        as_alice.delete_profile(alice).await.unwrap();
+
+        let events = audit
+            .get_audit_log(AuditLogFilterDTO::default())
+            .await
+            .unwrap();
+        let operations: Vec<_> = events.iter().map(|e| e.operation.as_str()).collect();
+        assert_eq!(operations, ["delete", "update", "create"]);
+
+        let update = &events[1];
+        assert_eq!(update.actor_profile_id, Some(alice));
+        assert_eq!(update.entity_id, alice);
+        assert_eq!(update.old_values, Some(json!({ "display_name": null })));
+        assert_eq!(update.new_values, Some(json!({ "display_name": "Alice" })));
+        assert_eq!(events[2].actor_profile_id, None);
+        assert!(events[2].old_values.is_none());
+    }
+
+    #[tokio::test]
+    async fn audit_log_filters_and_pages() {
+        let (audit, profiles) = services();
+        let alice = create_profile(&profiles, "alice").await;
+        create_profile(&profiles, "bob").await;
+        profiles.delete_profile(alice).await.unwrap();
+
+        let alice_events = audit
+            .get_audit_log(AuditLogFilterDTO {
+                entity_id: Some(alice),
+                ..AuditLogFilterDTO::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(alice_events.len(), 2);
+
+        let created = audit
+            .get_audit_log(AuditLogFilterDTO {
+                operation: Some(AuditOperation::Create),
+                limit: Some(1),
+                ..AuditLogFilterDTO::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(created.len(), 1);
+        let next = audit
+            .get_audit_log(AuditLogFilterDTO {
+                operation: Some(AuditOperation::Create),
+                before_id: Some(created[0].id),
+                ..AuditLogFilterDTO::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(next.len(), 1);
+        assert_eq!(next[0].entity_id, alice);
+
+        let err = audit
+            .get_audit_log(AuditLogFilterDTO {
+                limit: Some(0),
+                ..AuditLogFilterDTO::default()
+            })
+            .await
+            .unwrap_err();
+        assert!(matches!(err.code_name, ErrorCode::UserInputValidationError));
+    }
+}
@@ -0,0 +1,692 @@
+use crate::{
+    models::v1::{
+        audit_model::{AuditEventModel, AuditOperation},
+        backup_model::DatabaseSnapshotModel,
+        settings_model::BackupSettingsModel,
+    },
+    repositories::v1::backup_repository::BackupRepository,
+    services::{dto::backup_dto::BackupDTO, settings_service::SettingsService},
+    utils::{
+        crypto::encryption,
+        error::mapping::{ErrorCode, ErrorResponse},
+        fs::{
+            archive::{self, BACKUP_ARCHIVE},
+            common::{get_app_dir, AppDirs},
+            profile_picture::{self, AVATAR_VARIANT_SIZES},
+        },
+    },
+};
+use chrono::{Datelike, NaiveDateTime};
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs,
+    hash::Hash,
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+};
+
+const SNAPSHOT_FILE: &str = "snapshot.json";
+const AVATAR_DIR: &str = "pfps/";
+
+const NAME_FORMAT: &str = "backup-%Y%m%d-%H%M%S-%3f.zip";
+
+/// Whole-database backups: a JSON snapshot of every table plus the avatar files, in an archive
+/// checksummed like profile exports.
+///
+/// Scheduled backups run at most once a day and are thinned out by the rotation in the settings.
+/// Restoring replaces all data, so the current state is backed up first. The audit log is not
+/// part of backups and survives restores, which it records along with the backups involved.
+///
+/// Backups are encrypted while a passphrase is set. It is only kept in memory, so after a restart
+/// it has to be entered again; until then the `encrypted` setting stops plain backups from being
+/// written. The copies taken before a destructive migration are the exception: they are written
+/// at startup, before a passphrase can be entered, and are always plain.
+#[derive(Clone)]
+pub struct BackupService {
+    repo: Arc<dyn BackupRepository>,
+    settings: SettingsService,
+    passphrase: Arc<RwLock<Option<String>>>,
+    dir: Option<PathBuf>, // None means the app's backups dir.
+    actor: Option<i32>,   // Who restores are audited as; None for the app itself.
+}
+
+impl BackupService {
+    pub fn new(repo: Arc<dyn BackupRepository>, settings: SettingsService) -> Self {
+        Self {
+            repo,
+            settings,
+            passphrase: Arc::new(RwLock::new(None)),
+            dir: None,
+            actor: None,
+        }
+    }
+
+    /// The same service, auditing restores as made by `actor`.
+    pub fn acting_as(&self, actor: Option<i32>) -> Self {
+        Self {
+            actor,
+            ..self.clone()
+        }
+    }
+
+    /// Keeps backups in `dir` instead of the app's backups dir.
+    pub fn with_dir(mut self, dir: PathBuf) -> Self {
+        self.dir = Some(dir);
+        self
+    }
+
+    /// Backups in the backups dir, newest first. Their contents are only checked on restore.
+    pub async fn list_backups(&self) -> Result<Vec<BackupDTO>, ErrorResponse> {
+        let dir = self.dir()?;
+
+        tokio::task::spawn_blocking(move || list_backups_in(&dir))
+            .await
+            .map_err(|_| ErrorResponse::unhandled())?
+    }
+
+    /// Sets the passphrase backups are encrypted with from now on, or clears it.
+    pub fn set_passphrase(&self, passphrase: Option<String>) -> Result<(), ErrorResponse> {
+        if let Some(passphrase) = &passphrase {
+            encryption::check_passphrase(passphrase)?;
+        }
+
+        *self
+            .passphrase
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = passphrase;
+        Ok(())
+    }
+
+    pub async fn create_backup(&self) -> Result<BackupDTO, ErrorResponse> {
+        self.write_backup(self.passphrase()).await
+    }
+
+    /// Makes today's backup unless there already is one, then drops the backups the rotation no
+    /// longer keeps. Does nothing while scheduled backups are turned off.
+    pub async fn run_scheduled(&self) -> Result<Option<BackupDTO>, ErrorResponse> {
+        let keep = self.settings.current().backups;
+        if keep.daily == 0 && keep.weekly == 0 && keep.monthly == 0 {
+            return Ok(None);
+        }
+
+        let today = chrono::Utc::now().date_naive();
+        let backups = self.list_backups().await?;
+        let created = match backups.first() {
+            Some(newest) if newest.created_at.date() == today => None,
+            _ => Some(self.create_backup().await?),
+        };
+
+        let removed = self.rotate(&keep).await?;
+        if removed > 0 {
+            log::info!("Removed {removed} old backups");
+        }
+
+        Ok(created)
+    }
+
+    /// Replaces all data with the backup called `name`, decrypting it with `passphrase` when it
+    /// is encrypted. The backup is read and checked in full before anything is touched.
+    pub async fn restore_backup(
+        &self,
+        name: String,
+        passphrase: Option<String>,
+    ) -> Result<(), ErrorResponse> {
+        let dir = self.dir()?;
+        let read_passphrase = passphrase.clone();
+        let read_name = name.clone();
+        let (snapshot, avatars) = tokio::task::spawn_blocking(move || {
+            let path = backup_path(&dir, &read_name)?;
+            read_backup(&path, read_passphrase.as_deref())
+        })
+        .await
+        .map_err(|_| ErrorResponse::unhandled())??;
+
+        if snapshot.schema_version > self.repo.schema_version().await? {
+            return Err(ErrorResponse::keyed(
+                ErrorCode::UnsupportedVersionError,
+                None,
+                "backup-newer-version",
+            ));
+        }
+
+        // Without a passphrase of its own, the data being replaced is protected like the backup.
+        let safety = self.write_backup(self.passphrase().or(passphrase)).await?;
+        log::info!("Backed up current data to {} before restoring", safety.name);
+
+        let event = AuditEventModel::database(
+            self.actor,
+            AuditOperation::Restore,
+            serde_json::json!({ "backup": safety.name }),
+            serde_json::json!({
+                "backup": name,
+                "schemaVersion": snapshot.schema_version,
+                "profiles": snapshot.profiles.len(),
+            }),
+        );
+
+        let snapshot = tokio::task::spawn_blocking(move || restore_avatars(snapshot, avatars))
+            .await
+            .map_err(|_| ErrorResponse::unhandled())??;
+        self.repo.restore(snapshot, event).await
+    }
+
+    async fn write_backup(&self, passphrase: Option<String>) -> Result<BackupDTO, ErrorResponse> {
+        if passphrase.is_none() && self.settings.current().backups.encrypted {
+            return Err(ErrorResponse::keyed(
+                ErrorCode::WrongPassphraseError,
+                Some("passphrase".into()),
+                "backup-passphrase-needed",
+            ));
+        }
+
+        let snapshot = self.repo.snapshot().await?;
+        let dir = self.dir()?;
+
+        tokio::task::spawn_blocking(move || {
+            let files = backup_files(&snapshot)?;
+            let created_at = chrono::Utc::now().naive_utc();
+            let name = created_at.format(NAME_FORMAT).to_string();
+            let path = dir.join(&name);
+
+            archive::write_archive(&BACKUP_ARCHIVE, &path, &files, passphrase.as_deref())?;
+
+            Ok(BackupDTO {
+                name,
+                created_at,
+                size: fs::metadata(&path)?.len(),
+                encrypted: passphrase.is_some(),
+            })
+        })
+        .await
+        .map_err(|_| ErrorResponse::unhandled())?
+    }
+
+    async fn rotate(&self, keep: &BackupSettingsModel) -> Result<usize, ErrorResponse> {
+        let backups = self.list_backups().await?;
+        let kept = retained(&backups, keep);
+        let dir = self.dir()?;
+
+        tokio::task::spawn_blocking(move || {
+            let mut removed = 0;
+            for backup in backups.iter().filter(|backup| !kept.contains(&backup.name)) {
+                match fs::remove_file(dir.join(&backup.name)) {
+                    Ok(()) => removed += 1,
+                    Err(err) => log::warn!("Could not remove backup {}: {err}", backup.name),
+                }
+            }
+            removed
+        })
+        .await
+        .map_err(|_| ErrorResponse::unhandled())
+    }
+
+    fn passphrase(&self) -> Option<String> {
+        self.passphrase
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    fn dir(&self) -> Result<PathBuf, ErrorResponse> {
+        match &self.dir {
+            Some(dir) => Ok(dir.clone()),
+            None => Ok(get_app_dir(AppDirs::Backups)?),
+        }
+    }
+}
+
+fn list_backups_in(dir: &Path) -> Result<Vec<BackupDTO>, ErrorResponse> {
+    let mut backups = Vec::new();
+
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        let Ok(created_at) = NaiveDateTime::parse_from_str(&name, NAME_FORMAT) else {
+            continue;
+        };
+
+        backups.push(BackupDTO {
+            encrypted: archive::is_encrypted(&entry.path())?,
+            name,
+            created_at,
+            size: entry.metadata()?.len(),
+        });
+    }
+
+    backups.sort_by_key(|backup| std::cmp::Reverse(backup.created_at));
+    Ok(backups)
+}
+
+/// Names of the backups the rotation keeps: the newest of each of the last `daily` days, `weekly`
+/// ISO weeks and `monthly` months that have one. `backups` must be newest first.
+fn retained(backups: &[BackupDTO], keep: &BackupSettingsModel) -> HashSet<String> {
+    let mut kept = HashSet::new();
+
+    keep_newest_per(backups, keep.daily, &mut kept, |at| at.date());
+    keep_newest_per(backups, keep.weekly, &mut kept, |at| {
+        let week = at.iso_week();
+        (week.year(), week.week())
+    });
+    keep_newest_per(backups, keep.monthly, &mut kept, |at| {
+        (at.year(), at.month())
+    });
+
+    kept
+}
+
+fn keep_newest_per<K: Eq + Hash>(
+    backups: &[BackupDTO],
+    count: u32,
+    kept: &mut HashSet<String>,
+    period: impl Fn(&NaiveDateTime) -> K,
+) {
+    let mut periods = HashSet::new();
+
+    for backup in backups {
+        if periods.len() >= count as usize {
+            break;
+        }
+        if periods.insert(period(&backup.created_at)) {
+            kept.insert(backup.name.clone());
+        }
+    }
+}
+
+/// Only plain names of backups in `dir`; anything else could point outside of it.
+fn backup_path(dir: &Path, name: &str) -> Result<PathBuf, ErrorResponse> {
+    let path = dir.join(name);
+
+    if NaiveDateTime::parse_from_str(name, NAME_FORMAT).is_err() || !path.is_file() {
+        return Err(ErrorResponse::keyed(
+            ErrorCode::SearchObjectNotFoundError,
+            Some("name".into()),
+            "backup-not-found",
+        ));
+    }
+
+    Ok(path)
+}
+
+fn backup_files(
+    snapshot: &DatabaseSnapshotModel,
+) -> Result<BTreeMap<String, Vec<u8>>, ErrorResponse> {
+    let mut files = BTreeMap::new();
+    files.insert(
+        SNAPSHOT_FILE.to_string(),
+        serde_json::to_vec(snapshot).map_err(|_| ErrorResponse::unhandled())?,
+    );
+
+    let avatars = snapshot
+        .profiles
+        .iter()
+        .filter_map(|profile| profile.profile_picture_url.as_deref())
+        .map(PathBuf::from);
+    for avatar in avatars {
+        let variants =
+            AVATAR_VARIANT_SIZES.map(|size| profile_picture::variant_path(&avatar, size));
+
+        for path in std::iter::once(avatar.clone()).chain(variants) {
+            let Some(file_name) = path.file_name() else {
+                continue;
+            };
+            match fs::read(&path) {
+                Ok(bytes) => {
+                    files.insert(
+                        format!("{AVATAR_DIR}{}", file_name.to_string_lossy()),
+                        bytes,
+                    );
+                }
+                Err(err) => log::warn!("Backing up without avatar {}: {err}", path.display()),
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+type AvatarFiles = BTreeMap<String, Vec<u8>>;
+
+fn read_backup(
+    path: &Path,
+    passphrase: Option<&str>,
+) -> Result<(DatabaseSnapshotModel, AvatarFiles), ErrorResponse> {
+    let mut files = archive::read_archive(&BACKUP_ARCHIVE, path, passphrase)?;
+
+    let snapshot: DatabaseSnapshotModel = files
+        .remove(SNAPSHOT_FILE)
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .ok_or_else(|| corrupted("backup-no-snapshot"))?;
+
+    let mut avatars = BTreeMap::new();
+    for (name, bytes) in files {
+        let file_name = name
+            .strip_prefix(AVATAR_DIR)
+            .filter(|file_name| Path::new(file_name).file_name() == Some(file_name.as_ref()))
+            .ok_or_else(|| corrupted("backup-unexpected-files"))?;
+
+        avatars.insert(file_name.to_string(), bytes);
+    }
+
+    Ok((snapshot, avatars))
+}
+
+/// Puts the backed up avatars in this install's pfps dir and points the profiles at them there,
+/// since the backup may come from another data dir. Files left unused are swept like any other
+/// orphaned avatar.
+fn restore_avatars(
+    mut snapshot: DatabaseSnapshotModel,
+    avatars: AvatarFiles,
+) -> Result<DatabaseSnapshotModel, ErrorResponse> {
+    if snapshot
+        .profiles
+        .iter()
+        .all(|profile| profile.profile_picture_url.is_none())
+    {
+        return Ok(snapshot);
+    }
+
+    let pfps = get_app_dir(AppDirs::Pfps)?;
+    for (file_name, bytes) in &avatars {
+        let path = pfps.join(file_name);
+        if !path.exists() {
+            fs::write(path, bytes)?;
+        }
+    }
+
+    for profile in &mut snapshot.profiles {
+        if let Some(url) = &profile.profile_picture_url {
+            if let Some(file_name) = Path::new(url).file_name() {
+                profile.profile_picture_url =
+                    Some(pfps.join(file_name).to_string_lossy().to_string());
+            }
+        }
+    }
+
+    Ok(snapshot)
+}
+
+fn corrupted(message_id: &'static str) -> ErrorResponse {
+    ErrorResponse::keyed(ErrorCode::CorruptedFileError, None, message_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::v1::{
+            audit_model::{AuditEntity, AuditFilterModel},
+            notification_model::NotificationKind,
+        },
+        repositories::{
+            memory::{
+                audit_repository::InMemoryAuditRepository,
+                backup_repository::InMemoryBackupRepository,
+                notification_repository::InMemoryNotificationRepository,
+                preferences_repository::InMemoryPreferencesRepository,
+                profile_repository::InMemoryProfileRepository,
+            },
+            v1::{
+                audit_repository::AuditRepository, notification_repository::NotificationRepository,
+                preferences_repository::PreferencesRepository,
+                profile_repository::ProfileRepository,
+            },
+        },
+    };
+
+    const SCHEMA_VERSION: i64 = 20251126153045;
+
+    struct Install {
+        service: BackupService,
+        profiles: Arc<InMemoryProfileRepository>,
+        preferences: Arc<InMemoryPreferencesRepository>,
+        notifications: Arc<InMemoryNotificationRepository>,
+        audit: Arc<InMemoryAuditRepository>,
+        dir: PathBuf,
+    }
+
+    fn install() -> Install {
+        let dir =
+            std::env::temp_dir().join(format!("nomorebeans-backups-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let settings = SettingsService::load_from(dir.join("settings.toml")).unwrap();
+        let profiles = Arc::new(InMemoryProfileRepository::new());
+        let preferences = Arc::new(InMemoryPreferencesRepository::new());
+        let notifications = Arc::new(InMemoryNotificationRepository::new());
+        let audit = Arc::new(InMemoryAuditRepository::new());
+        let repo = Arc::new(InMemoryBackupRepository::new(
+            profiles.clone(),
+            preferences.clone(),
+            notifications.clone(),
+            audit.clone(),
+            SCHEMA_VERSION,
+        ));
+
+        Install {
+            service: BackupService::new(repo, settings).with_dir(dir.clone()),
+            profiles,
+            preferences,
+            notifications,
+            audit,
+            dir,
+        }
+    }
+
+    fn backup(created_at: &str) -> BackupDTO {
+        let created_at = NaiveDateTime::parse_from_str(created_at, "%Y-%m-%d %H:%M").unwrap();
+
+        BackupDTO {
+            name: created_at.format(NAME_FORMAT).to_string(),
+            created_at,
+            size: 0,
+            encrypted: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn restore_brings_back_backed_up_data() {
+        let install = install();
+        let alice = install
+            .profiles
+            .create_profile("alice".into(), None, None, None)
+            .await
+            .unwrap();
+        install
+            .preferences
+            .save(alice.id, 1, r#"{"theme":"dark"}"#.into())
+            .await
+            .unwrap();
+        let notification = install
+            .notifications
+            .insert_notification(
+                alice.id,
+                NotificationKind::BudgetThreshold,
+                "{}".into(),
+                None,
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        let backup = install.service.create_backup().await.unwrap();
+
+        install.notifications.mark_all_read(alice.id).await.unwrap();
+
+        install
+            .profiles
+            .delete_profile(alice.id, None)
+            .await
+            .unwrap();
+        install
+            .profiles
+            .create_profile("bob".into(), None, None, None)
+            .await
+            .unwrap();
+
+        install
+            .service
+            .acting_as(Some(alice.id))
+            .restore_backup(backup.name.clone(), None)
+            .await
+            .unwrap();
+
+        let restored = install.profiles.get_all().await.unwrap();
+        assert_eq!(restored.len(), 1);
+        assert_eq!(restored[0].id, alice.id);
+        assert_eq!(restored[0].username, "alice");
+        let preferences = install
+            .preferences
+            .get_by_profile_id(alice.id)
+            .await
+            .unwrap();
+        assert_eq!(preferences.unwrap().preferences, r#"{"theme":"dark"}"#);
+        let notifications = install
+            .notifications
+            .get_notifications(alice.id, true)
+            .await
+            .unwrap();
+        assert_eq!(notifications.len(), 1);
+        assert_eq!(notifications[0].id, notification.id);
+        // The data that was replaced got a backup of its own, which the audit log points to.
+        let backups = install.service.list_backups().await.unwrap();
+        assert_eq!(backups.len(), 2);
+        let safety = backups
+            .iter()
+            .find(|other| other.name != backup.name)
+            .unwrap();
+
+        let events = install
+            .audit
+            .get_events(AuditFilterModel {
+                entity: Some(AuditEntity::Database),
+                limit: 10,
+                ..AuditFilterModel::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].actor_profile_id, Some(alice.id));
+        assert_eq!(events[0].operation, "restore");
+        let old_values: serde_json::Value =
+            serde_json::from_str(events[0].old_values.as_deref().unwrap()).unwrap();
+        assert_eq!(old_values["backup"], safety.name.as_str());
+        let new_values: serde_json::Value =
+            serde_json::from_str(events[0].new_values.as_deref().unwrap()).unwrap();
+        assert_eq!(new_values["backup"], backup.name.as_str());
+        assert_eq!(new_values["profiles"], 1);
+    }
+
+    #[tokio::test]
+    async fn restore_refuses_damaged_backups() {
+        let install = install();
+        install
+            .profiles
+            .create_profile("alice".into(), None, None, None)
+            .await
+            .unwrap();
+        let name = backup("2025-11-20 03:00").name;
+        fs::write(install.dir.join(&name), "not a backup").unwrap();
+
+        let err = install
+            .service
+            .restore_backup(name, None)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err.code_name, ErrorCode::CorruptedFileError));
+        assert_eq!(install.profiles.get_all().await.unwrap().len(), 1);
+        assert_eq!(install.service.list_backups().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn restore_refuses_newer_schemas_and_other_names() {
+        let install = install();
+        let name = backup("2025-11-20 03:00").name;
+        let snapshot = DatabaseSnapshotModel {
+            schema_version: SCHEMA_VERSION + 1,
+            ..DatabaseSnapshotModel::default()
+        };
+        archive::write_archive(
+            &BACKUP_ARCHIVE,
+            &install.dir.join(&name),
+            &backup_files(&snapshot).unwrap(),
+            None,
+        )
+        .unwrap();
+
+        let err = install
+            .service
+            .restore_backup(name, None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err.code_name, ErrorCode::UnsupportedVersionError));
+
+        let err = install
+            .service
+            .restore_backup("../settings.toml".into(), None)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.code_name,
+            ErrorCode::SearchObjectNotFoundError
+        ));
+    }
+
+    #[tokio::test]
+    async fn encrypted_backups_need_the_passphrase() {
+        let install = install();
+        install
+            .profiles
+            .create_profile("alice".into(), None, None, None)
+            .await
+            .unwrap();
+        install
+            .service
+            .set_passphrase(Some("correct horse".into()))
+            .unwrap();
+        let backup = install.service.create_backup().await.unwrap();
+        assert!(backup.encrypted);
+
+        let err = install
+            .service
+            .restore_backup(backup.name.clone(), Some("battery staple".into()))
+            .await
+            .unwrap_err();
+        assert!(matches!(err.code_name, ErrorCode::WrongPassphraseError));
+
+        install
+            .service
+            .restore_backup(backup.name, Some("correct horse".into()))
+            .await
+            .unwrap();
+        let backups = install.service.list_backups().await.unwrap();
+        assert!(backups.iter().all(|backup| backup.encrypted));
+    }
+
+    #[test]
+    fn rotation_keeps_newest_backup_of_each_period() {
+        // Newest first: two on Nov 20, one a day for the rest of that week, then one a month.
+        let backups = [
+            backup("2025-11-20 18:00"),
+            backup("2025-11-20 03:00"),
+            backup("2025-11-19 03:00"),
+            backup("2025-11-18 03:00"),
+            backup("2025-11-17 03:00"),
+            backup("2025-10-15 03:00"),
+            backup("2025-09-15 03:00"),
+        ];
+        let keep = BackupSettingsModel {
+            daily: 2,
+            weekly: 2,
+            monthly: 2,
+            ..BackupSettingsModel::default()
+        };
+
+        let kept = retained(&backups, &keep);
+
+        let expected: HashSet<String> = [0, 2, 5]
+            .into_iter()
+            .map(|index| backups[index].name.clone())
+            .collect();
+        assert_eq!(kept, expected);
+    }
+}
@@ -0,0 +1,53 @@
+use crate::models::v1::audit_model::{AuditEntity, AuditEventModel, AuditOperation};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+pub const MAX_AUDIT_PAGE_SIZE: u32 = 500;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditEventDTO {
+    pub id: i32,
+    pub created_at: chrono::NaiveDateTime,
+    pub actor_profile_id: Option<i32>,
+    pub entity: String,
+    pub entity_id: i32,
+    pub operation: String,
+    pub old_values: Option<serde_json::Value>, // Changed fields as they were; None on create.
+    pub new_values: Option<serde_json::Value>, // The same fields afterwards; None on purge.
+}
+
+/// Every condition that is given must match. Events come newest first; pass the id of the last
+/// one as `before_id` for the next page.
+#[derive(Debug, Clone, Default, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditLogFilterDTO {
+    pub actor_profile_id: Option<i32>,
+    pub entity: Option<AuditEntity>,
+    pub entity_id: Option<i32>,
+    pub operation: Option<AuditOperation>,
+    pub from: Option<chrono::NaiveDateTime>,
+    pub until: Option<chrono::NaiveDateTime>, // Exclusive.
+    pub before_id: Option<i32>,
+
+    #[validate(range(min = 1, max = MAX_AUDIT_PAGE_SIZE))]
+    pub limit: Option<u32>,
+}
+
+impl From<AuditEventModel> for AuditEventDTO {
+    fn from(model: AuditEventModel) -> Self {
+        let parse =
+            |values: Option<String>| values.and_then(|values| serde_json::from_str(&values).ok());
+
+        Self {
+            id: model.id,
+            created_at: model.created_at,
+            actor_profile_id: model.actor_profile_id,
+            entity: model.entity,
+            entity_id: model.entity_id,
+            operation: model.operation,
+            old_values: parse(model.old_values),
+            new_values: parse(model.new_values),
+        }
+    }
+}
@@ -0,0 +1,10 @@
+use serde::Serialize;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupDTO {
+    pub name: String, // File name in the backups dir; what `restore_backup` takes.
+    pub created_at: chrono::NaiveDateTime,
+    pub size: u64,       // Bytes.
+    pub encrypted: bool, // Restoring needs the passphrase it was made with.
+}
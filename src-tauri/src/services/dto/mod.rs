@@ -1 +1,9 @@
+pub mod audit_dto;
+pub mod backup_dto;
+pub mod notification_dto;
+pub mod preferences_dto;
+pub mod profile_archive_dto;
 pub mod profile_dto;
+pub mod session_dto;
+pub mod settings_dto;
+pub mod undo_dto;
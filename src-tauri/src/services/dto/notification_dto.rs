@@ -0,0 +1,65 @@
+use crate::models::v1::notification_model::{BudgetAlertRuleModel, NotificationModel};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationDTO {
+    pub id: i32,
+    pub created_at: chrono::NaiveDateTime,
+    pub kind: String,
+    pub payload: serde_json::Value,
+    pub read_at: Option<chrono::NaiveDateTime>,
+}
+
+/// A notification just raised, and whether the profile also wants it on the desktop.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RaisedNotificationDTO {
+    pub notification: NotificationDTO,
+    pub notify_desktop: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetAlertRuleDTO {
+    pub category_id: i32,
+    pub warn_at_percent: i32,
+    pub notify_desktop: bool,
+}
+
+/// Creates or replaces the rule of the category. Without `warn_at_percent` the warning comes at
+/// 80% of the allocation.
+#[derive(Debug, Clone, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct SetBudgetAlertRuleDTO {
+    pub category_id: i32,
+
+    #[validate(range(min = 1, max = 99))]
+    pub warn_at_percent: Option<i32>,
+
+    #[serde(default)]
+    pub notify_desktop: bool,
+}
+
+impl From<NotificationModel> for NotificationDTO {
+    fn from(model: NotificationModel) -> Self {
+        Self {
+            id: model.id,
+            created_at: model.created_at,
+            kind: model.kind,
+            payload: serde_json::from_str(&model.payload).unwrap_or_default(),
+            read_at: model.read_at,
+        }
+    }
+}
+
+impl From<BudgetAlertRuleModel> for BudgetAlertRuleDTO {
+    fn from(model: BudgetAlertRuleModel) -> Self {
+        Self {
+            category_id: model.category_id,
+            warn_at_percent: model.warn_at_percent,
+            notify_desktop: model.notify_desktop,
+        }
+    }
+}
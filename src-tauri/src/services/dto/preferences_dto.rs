@@ -0,0 +1,80 @@
+use crate::{
+    models::v1::preferences_model::{DateFormat, LayoutMode, PreferencesModel, Theme, Weekday},
+    services::dto::settings_dto::{CURRENCY_REGEX, LOCALE_REGEX},
+};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreferencesDTO {
+    pub theme: Theme,
+    pub layout_mode: LayoutMode,
+    pub locale: String,
+    pub date_format: DateFormat,
+    pub base_currency: String,
+    pub first_day_of_week: Weekday,
+    pub budget_month_start_day: u8,
+}
+
+/// Only the preferences that are given change.
+#[derive(Debug, Clone, Default, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct SetPreferencesDTO {
+    pub theme: Option<Theme>,
+    pub layout_mode: Option<LayoutMode>,
+
+    #[validate(regex(path = *LOCALE_REGEX))]
+    pub locale: Option<String>,
+
+    pub date_format: Option<DateFormat>,
+
+    #[validate(regex(path = *CURRENCY_REGEX))]
+    pub base_currency: Option<String>,
+
+    pub first_day_of_week: Option<Weekday>,
+
+    // Later days do not exist in every month.
+    #[validate(range(min = 1, max = 28))]
+    pub budget_month_start_day: Option<u8>,
+}
+
+impl SetPreferencesDTO {
+    pub fn apply_to(self, preferences: &mut PreferencesModel) {
+        if let Some(theme) = self.theme {
+            preferences.theme = theme;
+        }
+        if let Some(layout_mode) = self.layout_mode {
+            preferences.layout_mode = layout_mode;
+        }
+        if let Some(locale) = self.locale {
+            preferences.locale = locale;
+        }
+        if let Some(date_format) = self.date_format {
+            preferences.date_format = date_format;
+        }
+        if let Some(base_currency) = self.base_currency {
+            preferences.base_currency = base_currency;
+        }
+        if let Some(first_day_of_week) = self.first_day_of_week {
+            preferences.first_day_of_week = first_day_of_week;
+        }
+        if let Some(budget_month_start_day) = self.budget_month_start_day {
+            preferences.budget_month_start_day = budget_month_start_day;
+        }
+    }
+}
+
+impl From<PreferencesModel> for PreferencesDTO {
+    fn from(model: PreferencesModel) -> Self {
+        Self {
+            theme: model.theme,
+            layout_mode: model.layout_mode,
+            locale: model.locale,
+            date_format: model.date_format,
+            base_currency: model.base_currency,
+            first_day_of_week: model.first_day_of_week,
+            budget_month_start_day: model.budget_month_start_day,
+        }
+    }
+}
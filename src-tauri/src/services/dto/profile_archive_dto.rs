@@ -0,0 +1,41 @@
+use crate::services::dto::profile_dto::ProfileSecretKind;
+use serde::{Deserialize, Serialize};
+
+/// `profile.json` inside a profile archive. Ids are left out, since the importing database hands
+/// out its own.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileArchiveDTO {
+    pub profile: ArchivedProfileDTO,
+    pub preferences: Option<ArchivedPreferencesDTO>,
+    #[serde(default)] // Archives exported before the rules travelled have none.
+    pub budget_alert_rules: Vec<ArchivedBudgetAlertRuleDTO>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchivedProfileDTO {
+    pub username: String,
+    pub display_name: Option<String>,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+    pub avatar: Option<String>, // Name of the full-size avatar inside the archive.
+    pub secret_kind: Option<ProfileSecretKind>,
+    pub secret_hash: Option<String>, // Argon2id PHC string; the secret itself never leaves.
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchivedPreferencesDTO {
+    pub version: i32,
+    pub preferences: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchivedBudgetAlertRuleDTO {
+    pub category_id: i32,
+    pub warn_at_percent: i32,
+    pub notify_desktop: bool,
+    pub updated_at: chrono::NaiveDateTime,
+}
@@ -1,11 +1,31 @@
-use crate::models::v1::profile_model::ProfileModel;
-use base64::{engine::general_purpose, Engine as _};
+use crate::{
+    models::{patch::Patch, v1::profile_model::ProfileModel},
+    utils::{
+        error::mapping::{ErrorCode, ErrorResponse},
+        fs::profile_picture::CropRect,
+    },
+};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
-use std::{convert::TryFrom, fs, sync::LazyLock};
+use std::{path::Path, sync::LazyLock};
 use validator::{Validate, ValidationError};
 
 static USERNAME_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^[a-z0-9]+$").unwrap());
+// WebView2 and Android only reach custom schemes through http://<scheme>.localhost.
+#[cfg(any(windows, target_os = "android"))]
+const AVATAR_URL_BASE: &str = "http://avatar.localhost";
+#[cfg(not(any(windows, target_os = "android")))]
+const AVATAR_URL_BASE: &str = "avatar://localhost";
+
+static PIN_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^[0-9]{4,8}$").unwrap());
+const MIN_PASSPHRASE_LENGTH: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProfileSecretKind {
+    Pin,
+    Passphrase,
+}
 
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -15,89 +35,215 @@ pub struct GetProfileDTO {
     pub updated_at: chrono::NaiveDateTime,
     pub username: String,
     pub display_name: Option<String>,
+    pub avatar: Option<String>, // URL served by the avatar protocol; append `&size=` for a variant.
+    pub secret_kind: Option<ProfileSecretKind>,
+    pub locked: bool,
+}
+
+/// A profile in the trash. `username` is the one it had before deletion; rows trashed before it
+/// was recorded have none and need a new one to be restored.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeletedProfileDTO {
+    pub id: i32,
+    pub username: Option<String>,
+    pub display_name: Option<String>,
     pub avatar: Option<String>,
+    pub deleted_at: chrono::NaiveDateTime,
+    pub purge_at: Option<chrono::NaiveDateTime>, // None when the trash is never emptied automatically.
 }
 
 #[derive(Debug, Clone, Deserialize, Validate)]
 #[serde(rename_all = "camelCase")]
 pub struct CreateProfileDTO {
-    #[validate(
-        length(
-            min = 3,
-            max = 16,
-            message = "Username must be between 3 and 16 characters"
-        ),
-        regex(
-            path = *USERNAME_REGEX,
-            message = "Username can only contain lowercase letters and numbers"
-        )
-    )]
+    #[validate(length(min = 3, max = 16), regex(path = *USERNAME_REGEX))]
     pub username: String,
 
-    #[validate(length(
-        min = 1,
-        max = 32,
-        message = "Display name must be between 1 and 32 characters"
-    ))]
+    #[validate(length(min = 1, max = 32))]
     pub display_name: Option<String>,
 
     #[validate(custom(function = "validate_profile_picture_size"))]
     pub profile_picture_bytes: Option<Vec<u8>>,
+
+    #[validate(nested)]
+    pub profile_picture_crop: Option<ProfilePictureCropDTO>,
 }
 
-#[derive(Debug, Clone, Deserialize, Validate)]
+/// Fields left out stay as they are; `null` clears the display name or the picture.
+#[derive(Debug, Clone, Default, Deserialize, Validate)]
 #[serde(rename_all = "camelCase")]
 pub struct UpdateProfileDTO {
-    #[validate(
-        length(
-            min = 3,
-            max = 16,
-            message = "Username must be between 3 and 16 characters"
-        ),
-        regex(
-            path = *USERNAME_REGEX,
-            message = "Username can only contain lowercase letters and numbers"
-        )
-    )]
+    #[validate(length(min = 3, max = 16), regex(path = *USERNAME_REGEX))]
     pub username: Option<String>,
 
-    #[validate(length(
-        min = 1,
-        max = 32,
-        message = "Display name must be between 1 and 32 characters"
-    ))]
-    pub display_name: Option<String>,
+    #[validate(length(min = 1, max = 32))]
+    #[serde(default)]
+    pub display_name: Patch<String>,
 
-    #[validate(custom(function = "validate_profile_picture_size"))]
-    pub profile_picture_bytes: Option<Vec<u8>>,
+    #[validate(custom(function = "validate_profile_picture_patch"))]
+    #[serde(default)]
+    pub profile_picture_bytes: Patch<Vec<u8>>,
+
+    #[validate(nested)]
+    pub profile_picture_crop: Option<ProfilePictureCropDTO>,
+}
+
+/// Restores under the original username unless another one is given.
+#[derive(Debug, Clone, Default, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreProfileDTO {
+    #[validate(length(min = 3, max = 16), regex(path = *USERNAME_REGEX))]
+    pub username: Option<String>,
+}
+
+/// Area of the picture to keep, in pixels of the image as displayed (after EXIF rotation).
+#[derive(Debug, Clone, Copy, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfilePictureCropDTO {
+    pub x: u32,
+    pub y: u32,
+
+    #[validate(range(min = 1))]
+    pub width: u32,
+
+    #[validate(range(min = 1))]
+    pub height: u32,
+}
+
+// No Debug on the secret DTOs so a stray `{:?}` cannot log a PIN.
+#[derive(Clone, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct SetProfileSecretDTO {
+    pub kind: ProfileSecretKind,
+
+    #[validate(length(min = 1, max = 128))]
+    pub secret: String,
+}
+
+#[derive(Clone, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct UnlockProfileDTO {
+    #[validate(length(min = 1, max = 128))]
+    pub secret: String,
+}
+
+impl ProfileSecretKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProfileSecretKind::Pin => "pin",
+            ProfileSecretKind::Passphrase => "passphrase",
+        }
+    }
+
+    pub(crate) fn from_column(value: &str) -> Option<Self> {
+        match value {
+            "pin" => Some(ProfileSecretKind::Pin),
+            "passphrase" => Some(ProfileSecretKind::Passphrase),
+            _ => None,
+        }
+    }
+}
+
+impl SetProfileSecretDTO {
+    /// The accepted format depends on `kind`, which a per-field validator cannot see.
+    pub fn validate_secret(&self) -> Result<(), ErrorResponse> {
+        let field = Some("secret".into());
+
+        match self.kind {
+            ProfileSecretKind::Pin if !PIN_REGEX.is_match(&self.secret) => {
+                Err(ErrorResponse::keyed(
+                    ErrorCode::UserInputValidationError,
+                    field,
+                    "profile-pin-format",
+                ))
+            }
+            ProfileSecretKind::Passphrase
+                if self.secret.chars().count() < MIN_PASSPHRASE_LENGTH =>
+            {
+                Err(ErrorResponse::keyed(
+                    ErrorCode::UserInputValidationError,
+                    field,
+                    "profile-passphrase-too-short",
+                )
+                .with_arg("min", MIN_PASSPHRASE_LENGTH))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl UpdateProfileDTO {
+    pub fn is_empty(&self) -> bool {
+        self.username.is_none()
+            && self.display_name.is_missing()
+            && self.profile_picture_bytes.is_missing()
+    }
 }
 
-impl TryFrom<ProfileModel> for GetProfileDTO {
-    type Error = String;
+impl From<ProfilePictureCropDTO> for CropRect {
+    fn from(crop: ProfilePictureCropDTO) -> Self {
+        Self {
+            x: crop.x,
+            y: crop.y,
+            width: crop.width,
+            height: crop.height,
+        }
+    }
+}
 
-    fn try_from(model: ProfileModel) -> Result<Self, Self::Error> {
-        let avatar = model.profile_picture_url.as_ref().and_then(|path| {
-            fs::read(path)
-                .ok()
-                .map(|bytes| general_purpose::STANDARD.encode(&bytes))
-        });
+impl From<ProfileModel> for GetProfileDTO {
+    fn from(model: ProfileModel) -> Self {
+        let avatar = avatar_url(model.id, model.profile_picture_url.as_deref());
 
-        Ok(Self {
+        Self {
             id: model.id,
             created_at: model.created_at,
             updated_at: model.updated_at,
             username: model.username,
             display_name: model.display_name,
             avatar,
-        })
+            secret_kind: model
+                .secret_kind
+                .as_deref()
+                .and_then(ProfileSecretKind::from_column),
+            locked: model.secret_hash.is_some(),
+        }
+    }
+}
+
+impl DeletedProfileDTO {
+    pub fn new(model: ProfileModel, purge_at: Option<chrono::NaiveDateTime>) -> Self {
+        Self {
+            id: model.id,
+            avatar: avatar_url(model.id, model.profile_picture_url.as_deref()),
+            username: model.original_username,
+            display_name: model.display_name,
+            deleted_at: model.deleted_at.unwrap_or(model.updated_at),
+            purge_at,
+        }
     }
 }
 
+fn avatar_url(id: i32, profile_picture_url: Option<&str>) -> Option<String> {
+    // The file name changes with every upload, so it doubles as a cache buster.
+    profile_picture_url.map(|path| {
+        let version = Path::new(path)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        format!("{AVATAR_URL_BASE}/{id}?v={version}")
+    })
+}
+
 fn validate_profile_picture_size(bytes: &Vec<u8>) -> Result<(), ValidationError> {
     if bytes.len() > 2 * 1024 * 1024 {
-        return Err(ValidationError::new("profile_picture_size")
-            .with_message("Profile picture must be less than 2MB".into()));
+        return Err(ValidationError::new("profile_picture_size"));
     }
 
     Ok(())
 }
+
+fn validate_profile_picture_patch(bytes: &Patch<Vec<u8>>) -> Result<(), ValidationError> {
+    bytes.value().map_or(Ok(()), validate_profile_picture_size)
+}
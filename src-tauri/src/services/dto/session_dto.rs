@@ -0,0 +1,9 @@
+use serde::Serialize;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionDTO {
+    pub profile_id: Option<i32>,
+    pub locked: bool,
+    pub unlocked_at: Option<chrono::NaiveDateTime>,
+}
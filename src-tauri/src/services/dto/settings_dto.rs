@@ -0,0 +1,140 @@
+use crate::{
+    models::v1::settings_model::{
+        BackupSettingsModel, DatabaseSettingsModel, LogLevel, SettingsModel,
+    },
+    utils::{
+        db::pool::DbBackend,
+        error::mapping::{ErrorCode, ErrorResponse},
+    },
+};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::{path::PathBuf, sync::LazyLock};
+use validator::{Validate, ValidationError};
+
+pub(crate) static LOCALE_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[a-z]{2,3}(-[A-Z]{2})?$").unwrap());
+pub(crate) static CURRENCY_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[A-Z]{3}$").unwrap());
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetSettingsDTO {
+    pub settings: SettingsDTO,
+    pub overridden: Vec<String>, // Fields currently replaced by environment variables.
+    pub restart_required: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsDTO {
+    pub database_backend: DbBackend,
+
+    #[validate(length(min = 1, max = 2048))]
+    pub database_url: Option<String>,
+
+    #[validate(range(min = 1, max = 64))]
+    pub database_pool_size: u32,
+
+    pub log_level: LogLevel,
+
+    #[validate(regex(path = *LOCALE_REGEX))]
+    pub default_locale: String,
+
+    #[validate(regex(path = *CURRENCY_REGEX))]
+    pub default_currency: String,
+
+    #[validate(range(max = 1440))]
+    pub session_idle_minutes: u32,
+
+    #[validate(range(max = 3650))]
+    pub trash_retention_days: u32,
+
+    #[validate(range(max = 365))]
+    pub backup_keep_daily: u32,
+
+    #[validate(range(max = 365))]
+    pub backup_keep_weekly: u32,
+
+    #[validate(range(max = 365))]
+    pub backup_keep_monthly: u32,
+
+    pub backup_encrypted: bool,
+
+    #[validate(custom(function = "validate_data_dir"))]
+    pub data_dir: Option<PathBuf>,
+}
+
+impl SettingsDTO {
+    /// Checks the URL against the chosen backend, which a per-field validator cannot see.
+    pub fn validate_database_url(&self) -> Result<(), ErrorResponse> {
+        let message_id = match (self.database_backend, self.database_url.as_deref()) {
+            (DbBackend::Postgres, None) => "settings-postgres-needs-url",
+            (DbBackend::Postgres, Some(url)) if DbBackend::from_url(url) != DbBackend::Postgres => {
+                "settings-postgres-url-scheme"
+            }
+            (DbBackend::Sqlite, Some(url)) if !url.starts_with("sqlite:") => {
+                "settings-sqlite-url-scheme"
+            }
+            _ => return Ok(()),
+        };
+
+        Err(ErrorResponse::keyed(
+            ErrorCode::UserInputValidationError,
+            Some("database_url".into()),
+            message_id,
+        ))
+    }
+}
+
+impl From<SettingsModel> for SettingsDTO {
+    fn from(model: SettingsModel) -> Self {
+        Self {
+            database_backend: model.database.backend,
+            database_url: model.database.url,
+            database_pool_size: model.database.pool_size,
+            log_level: model.log_level,
+            default_locale: model.default_locale,
+            default_currency: model.default_currency,
+            session_idle_minutes: model.session_idle_minutes,
+            trash_retention_days: model.trash_retention_days,
+            backup_keep_daily: model.backups.daily,
+            backup_keep_weekly: model.backups.weekly,
+            backup_keep_monthly: model.backups.monthly,
+            backup_encrypted: model.backups.encrypted,
+            data_dir: model.data_dir,
+        }
+    }
+}
+
+impl From<SettingsDTO> for SettingsModel {
+    fn from(dto: SettingsDTO) -> Self {
+        Self {
+            database: DatabaseSettingsModel {
+                backend: dto.database_backend,
+                url: dto.database_url,
+                pool_size: dto.database_pool_size,
+            },
+            log_level: dto.log_level,
+            default_locale: dto.default_locale,
+            default_currency: dto.default_currency,
+            session_idle_minutes: dto.session_idle_minutes,
+            trash_retention_days: dto.trash_retention_days,
+            backups: BackupSettingsModel {
+                daily: dto.backup_keep_daily,
+                weekly: dto.backup_keep_weekly,
+                monthly: dto.backup_keep_monthly,
+                encrypted: dto.backup_encrypted,
+            },
+            data_dir: dto.data_dir,
+        }
+    }
+}
+
+fn validate_data_dir(path: &PathBuf) -> Result<(), ValidationError> {
+    if !path.is_absolute() {
+        return Err(ValidationError::new("absolute"));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,9 @@
+use crate::models::v1::audit_model::AuditOperation;
+use serde::Serialize;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UndoStatusDTO {
+    pub undo: Option<AuditOperation>, // What `undo` would take back; None when there is nothing.
+    pub redo: Option<AuditOperation>, // What `redo` would make again.
+}
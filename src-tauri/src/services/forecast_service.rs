@@ -0,0 +1,511 @@
+use chrono::{Datelike, Days, Months, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Amounts are stored in minor units (cents). Positive values are inflows, negative are outflows.
+pub type Cents = i64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ForecastHorizon {
+    ThirtyDays,
+    NinetyDays,
+    OneYear,
+}
+
+impl ForecastHorizon {
+    pub fn days(self) -> u64 {
+        match self {
+            ForecastHorizon::ThirtyDays => 30,
+            ForecastHorizon::NinetyDays => 90,
+            ForecastHorizon::OneYear => 365,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+#[derive(Debug, Clone)]
+pub struct ForecastAccount {
+    pub id: i32,
+    pub balance: Cents,
+    pub floor: Option<Cents>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduledTransaction {
+    pub account_id: i32,
+    pub amount: Cents,
+    pub start_date: NaiveDate,
+    pub end_date: Option<NaiveDate>,
+    pub frequency: Option<Frequency>, // None means a one-off transaction on `start_date`.
+    pub interval: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct Bill {
+    pub account_id: i32,
+    pub amount: Cents, // Amount owed, always subtracted from the balance.
+    pub due_day: u32,
+    pub paid_through: Option<NaiveDate>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CategoryAverage {
+    pub account_id: i32,
+    pub category_id: i32,
+    pub monthly_average: Cents,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ForecastInput {
+    pub accounts: Vec<ForecastAccount>,
+    pub scheduled: Vec<ScheduledTransaction>,
+    pub bills: Vec<Bill>,
+    pub category_averages: Vec<CategoryAverage>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForecastPoint {
+    pub date: NaiveDate,
+    pub balance: Cents,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum BalanceAlertKind {
+    BelowZero,
+    BelowFloor,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BalanceAlert {
+    pub date: NaiveDate,
+    pub balance: Cents,
+    pub kind: BalanceAlertKind,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountForecast {
+    pub account_id: i32,
+    pub starting_balance: Cents,
+    pub points: Vec<ForecastPoint>,
+    pub lowest: ForecastPoint,
+    pub alerts: Vec<BalanceAlert>,
+}
+
+/// Projects the end-of-day balance of every account for each day after `today` up to the horizon.
+///
+/// Balances in `input.accounts` are taken as of the end of `today`, so nothing dated on or before
+/// `today` is applied again. Alerts are raised on the day a balance crosses below zero or below the
+/// account floor, not on every day it stays there; a balance already below on `today` is reported
+/// on `today`.
+pub fn project_balances(
+    input: &ForecastInput,
+    today: NaiveDate,
+    horizon: ForecastHorizon,
+) -> Vec<AccountForecast> {
+    let days = horizon.days();
+    let end = today + Days::new(days);
+
+    let mut deltas: HashMap<i32, Vec<Cents>> = input
+        .accounts
+        .iter()
+        .map(|account| (account.id, vec![0; days as usize]))
+        .collect();
+
+    let mut apply = |account_id: i32, date: NaiveDate, amount: Cents| {
+        if date <= today || date > end {
+            return;
+        }
+
+        if let Some(account_deltas) = deltas.get_mut(&account_id) {
+            let index = (date - today).num_days() as usize - 1;
+            account_deltas[index] += amount;
+        }
+    };
+
+    for scheduled in &input.scheduled {
+        for date in scheduled_occurrences(scheduled, today, end) {
+            apply(scheduled.account_id, date, scheduled.amount);
+        }
+    }
+
+    for bill in &input.bills {
+        for date in bill_occurrences(bill, today, end) {
+            apply(bill.account_id, date, -bill.amount.abs());
+        }
+    }
+
+    for average in &input.category_averages {
+        // Spread the monthly average evenly over the year, carrying the remainder so the total
+        // does not drift on longer horizons.
+        let yearly = average.monthly_average as i128 * 12;
+        let mut spent_so_far: i128 = 0;
+
+        for day in 1..=days {
+            let spent_until_today = yearly * day as i128 / 365;
+            apply(
+                average.account_id,
+                today + Days::new(day),
+                (spent_until_today - spent_so_far) as Cents,
+            );
+            spent_so_far = spent_until_today;
+        }
+    }
+
+    input
+        .accounts
+        .iter()
+        .map(|account| {
+            let account_deltas = &deltas[&account.id];
+            let mut balance = account.balance;
+            let mut points = Vec::with_capacity(account_deltas.len());
+            let mut alerts = Vec::new();
+            let mut lowest = ForecastPoint {
+                date: today,
+                balance,
+            };
+
+            if balance < 0 {
+                alerts.push(BalanceAlert {
+                    date: today,
+                    balance,
+                    kind: BalanceAlertKind::BelowZero,
+                });
+            }
+
+            if account.floor.is_some_and(|floor| balance < floor) {
+                alerts.push(BalanceAlert {
+                    date: today,
+                    balance,
+                    kind: BalanceAlertKind::BelowFloor,
+                });
+            }
+
+            for (offset, delta) in account_deltas.iter().enumerate() {
+                let previous = balance;
+                let date = today + Days::new(offset as u64 + 1);
+                balance += delta;
+
+                if previous >= 0 && balance < 0 {
+                    alerts.push(BalanceAlert {
+                        date,
+                        balance,
+                        kind: BalanceAlertKind::BelowZero,
+                    });
+                }
+
+                if let Some(floor) = account.floor {
+                    if previous >= floor && balance < floor {
+                        alerts.push(BalanceAlert {
+                            date,
+                            balance,
+                            kind: BalanceAlertKind::BelowFloor,
+                        });
+                    }
+                }
+
+                if balance < lowest.balance {
+                    lowest = ForecastPoint { date, balance };
+                }
+
+                points.push(ForecastPoint { date, balance });
+            }
+
+            AccountForecast {
+                account_id: account.id,
+                starting_balance: account.balance,
+                points,
+                lowest,
+                alerts,
+            }
+        })
+        .collect()
+}
+
+fn scheduled_occurrences(
+    scheduled: &ScheduledTransaction,
+    today: NaiveDate,
+    end: NaiveDate,
+) -> Vec<NaiveDate> {
+    let last = scheduled.end_date.map_or(end, |end_date| end_date.min(end));
+    let interval = scheduled.interval.max(1);
+
+    let Some(frequency) = scheduled.frequency else {
+        return if scheduled.start_date > today && scheduled.start_date <= last {
+            vec![scheduled.start_date]
+        } else {
+            Vec::new()
+        };
+    };
+
+    let mut occurrences = Vec::new();
+
+    // Occurrences are always computed from the start date so monthly schedules anchored on the
+    // 31st come back to the 31st after a short month.
+    for n in 0u32.. {
+        let step = n.saturating_mul(interval);
+        let date = match frequency {
+            Frequency::Daily => scheduled
+                .start_date
+                .checked_add_days(Days::new(step as u64)),
+            Frequency::Weekly => scheduled
+                .start_date
+                .checked_add_days(Days::new(step as u64 * 7)),
+            Frequency::Monthly => scheduled.start_date.checked_add_months(Months::new(step)),
+            Frequency::Yearly => scheduled
+                .start_date
+                .checked_add_months(Months::new(step.saturating_mul(12))),
+        };
+
+        let Some(date) = date else { break };
+
+        if date > last {
+            break;
+        }
+
+        if date > today {
+            occurrences.push(date);
+        }
+    }
+
+    occurrences
+}
+
+fn bill_occurrences(bill: &Bill, today: NaiveDate, end: NaiveDate) -> Vec<NaiveDate> {
+    let first_of_month = today.with_day0(0).unwrap_or(today);
+    let mut occurrences = Vec::new();
+
+    for n in 0u32.. {
+        let Some(month) = first_of_month.checked_add_months(Months::new(n)) else {
+            break;
+        };
+
+        if month > end {
+            break;
+        }
+
+        // Bills due on the 29th-31st fall on the last day of shorter months.
+        let days_in_month = (month + Months::new(1) - Days::new(1)).day();
+        let Some(due) = month.with_day(bill.due_day.clamp(1, days_in_month)) else {
+            continue;
+        };
+
+        let already_paid = bill.paid_through.is_some_and(|paid| due <= paid);
+
+        if due > today && due <= end && !already_paid {
+            occurrences.push(due);
+        }
+    }
+
+    occurrences
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(value: &str) -> NaiveDate {
+        value.parse().unwrap()
+    }
+
+    fn account(balance: Cents, floor: Option<Cents>) -> ForecastAccount {
+        ForecastAccount {
+            id: 1,
+            balance,
+            floor,
+        }
+    }
+
+    fn one_off(on: NaiveDate, amount: Cents) -> ScheduledTransaction {
+        ScheduledTransaction {
+            account_id: 1,
+            amount,
+            start_date: on,
+            end_date: None,
+            frequency: None,
+            interval: 1,
+        }
+    }
+
+    fn monthly(start_date: NaiveDate) -> ScheduledTransaction {
+        ScheduledTransaction {
+            frequency: Some(Frequency::Monthly),
+            ..one_off(start_date, -100)
+        }
+    }
+
+    fn bill(due_day: u32, paid_through: Option<NaiveDate>) -> Bill {
+        Bill {
+            account_id: 1,
+            amount: 500,
+            due_day,
+            paid_through,
+        }
+    }
+
+    #[test]
+    fn monthly_schedules_keep_their_day_through_short_months() {
+        let occurrences = scheduled_occurrences(
+            &monthly(date("2025-01-31")),
+            date("2025-01-31"),
+            date("2025-05-01"),
+        );
+        assert_eq!(
+            occurrences,
+            [date("2025-02-28"), date("2025-03-31"), date("2025-04-30")]
+        );
+
+        let occurrences = scheduled_occurrences(
+            &monthly(date("2024-01-30")),
+            date("2024-01-30"),
+            date("2024-04-29"),
+        );
+        assert_eq!(occurrences, [date("2024-02-29"), date("2024-03-30")]);
+
+        let occurrences = scheduled_occurrences(
+            &monthly(date("2023-12-29")),
+            date("2024-12-31"),
+            date("2025-03-31"),
+        );
+        assert_eq!(
+            occurrences,
+            [date("2025-01-29"), date("2025-02-28"), date("2025-03-29")]
+        );
+    }
+
+    #[test]
+    fn bills_fall_on_the_last_day_of_short_months_unless_paid() {
+        let today = date("2025-01-15");
+        let end = date("2025-04-15");
+
+        assert_eq!(
+            bill_occurrences(&bill(31, None), today, end),
+            [date("2025-01-31"), date("2025-02-28"), date("2025-03-31")]
+        );
+        assert_eq!(
+            bill_occurrences(&bill(31, Some(date("2025-02-28"))), today, end),
+            [date("2025-03-31")]
+        );
+        // Paid up to a date before the due day leaves that month's bill open.
+        assert_eq!(
+            bill_occurrences(&bill(10, Some(date("2025-02-09"))), today, end),
+            [date("2025-02-10"), date("2025-03-10"), date("2025-04-10")]
+        );
+    }
+
+    #[test]
+    fn alerts_fire_on_the_day_the_balance_crosses() {
+        let today = date("2025-01-31");
+        let day = |n: u64| today + Days::new(n);
+        let input = ForecastInput {
+            accounts: vec![account(100, Some(50))],
+            scheduled: vec![
+                one_off(day(1), -30),
+                one_off(day(2), -30),
+                one_off(day(3), -30),
+                one_off(day(4), -30),
+                one_off(day(5), 100),
+                one_off(day(6), -100),
+                // Already reflected in today's balance.
+                one_off(today, -1000),
+            ],
+            ..ForecastInput::default()
+        };
+
+        let forecast = &project_balances(&input, today, ForecastHorizon::ThirtyDays)[0];
+
+        let alerts: Vec<_> = forecast
+            .alerts
+            .iter()
+            .map(|alert| (alert.date, alert.balance, alert.kind))
+            .collect();
+        assert_eq!(
+            alerts,
+            [
+                (day(2), 40, BalanceAlertKind::BelowFloor),
+                (day(4), -20, BalanceAlertKind::BelowZero),
+                (day(6), -20, BalanceAlertKind::BelowZero),
+                (day(6), -20, BalanceAlertKind::BelowFloor),
+            ]
+        );
+        assert_eq!(forecast.points.len(), 30);
+        assert_eq!(forecast.lowest.date, day(4));
+        assert_eq!(forecast.points.last().unwrap().balance, -20);
+    }
+
+    #[test]
+    fn alerts_report_a_starting_balance_already_below() {
+        let today = date("2025-01-31");
+        let day = |n: u64| today + Days::new(n);
+        let alerts = |balance: Cents| {
+            let input = ForecastInput {
+                accounts: vec![account(balance, Some(50))],
+                scheduled: vec![one_off(day(2), 100), one_off(day(3), -200)],
+                ..ForecastInput::default()
+            };
+
+            project_balances(&input, today, ForecastHorizon::ThirtyDays)[0]
+                .alerts
+                .iter()
+                .map(|alert| (alert.date, alert.balance, alert.kind))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            alerts(-10),
+            [
+                (today, -10, BalanceAlertKind::BelowZero),
+                (today, -10, BalanceAlertKind::BelowFloor),
+                (day(3), -110, BalanceAlertKind::BelowZero),
+                (day(3), -110, BalanceAlertKind::BelowFloor),
+            ]
+        );
+        assert_eq!(
+            alerts(20),
+            [
+                (today, 20, BalanceAlertKind::BelowFloor),
+                (day(3), -80, BalanceAlertKind::BelowZero),
+                (day(3), -80, BalanceAlertKind::BelowFloor),
+            ]
+        );
+    }
+
+    #[test]
+    fn category_averages_spread_over_days_without_drift() {
+        let today = date("2025-01-31");
+        let input = ForecastInput {
+            accounts: vec![account(0, None)],
+            category_averages: vec![CategoryAverage {
+                account_id: 1,
+                category_id: 7,
+                monthly_average: -3000,
+            }],
+            ..ForecastInput::default()
+        };
+
+        let forecast = &project_balances(&input, today, ForecastHorizon::OneYear)[0];
+
+        let mut previous = 0;
+        for point in &forecast.points {
+            assert!(matches!(point.balance - previous, -99..=-98));
+            previous = point.balance;
+        }
+        assert_eq!(forecast.points.last().unwrap().balance, -36_000);
+
+        let month = &project_balances(&input, today, ForecastHorizon::ThirtyDays)[0];
+        assert_eq!(month.points.last().unwrap().balance, -36_000 * 30 / 365);
+    }
+}
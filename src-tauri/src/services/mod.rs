@@ -1,2 +1,14 @@
+pub mod anomaly_service;
+pub mod audit_service;
+pub mod backup_service;
 pub mod dto;
+pub mod forecast_service;
+pub mod notification_service;
+pub mod preferences_service;
+pub mod profile_archive_service;
 pub mod profile_service;
+pub mod report_service;
+pub mod session_service;
+pub mod settings_service;
+pub mod subscription_service;
+pub mod undo_service;
@@ -0,0 +1,372 @@
+use crate::{
+    models::v1::notification_model::{
+        BudgetAlertRuleModel, NotificationKind, DEFAULT_WARN_AT_PERCENT,
+    },
+    repositories::v1::notification_repository::NotificationRepository,
+    services::{
+        dto::notification_dto::{
+            BudgetAlertRuleDTO, NotificationDTO, RaisedNotificationDTO, SetBudgetAlertRuleDTO,
+        },
+        forecast_service::Cents,
+    },
+    utils::error::mapping::{ErrorCode, ErrorResponse},
+};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Arc;
+use validator::Validate;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BudgetThreshold {
+    Warning, // The rule's warning percentage of the allocation.
+    Reached, // The whole allocation.
+}
+
+impl BudgetThreshold {
+    fn as_str(self) -> &'static str {
+        match self {
+            BudgetThreshold::Warning => "warning",
+            BudgetThreshold::Reached => "reached",
+        }
+    }
+}
+
+/// Spending of one category over a budget month, as the ledger totals it after a transaction is
+/// inserted or imported. Spending is a positive amount.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CategoryBudget {
+    pub category_id: i32,
+    pub month: NaiveDate, // First day of the budget month.
+    pub allocated: Cents,
+    pub spent: Cents,
+}
+
+/// Highest threshold that `spent` has reached out of `allocated`. Categories without an allocation
+/// have nothing to warn about.
+pub fn budget_threshold(
+    allocated: Cents,
+    spent: Cents,
+    warn_at_percent: i32,
+) -> Option<BudgetThreshold> {
+    if allocated <= 0 {
+        return None;
+    }
+
+    // Compared in i128 so a large allocation cannot overflow the percentage.
+    if spent >= allocated {
+        Some(BudgetThreshold::Reached)
+    } else if spent as i128 * 100 >= allocated as i128 * warn_at_percent as i128 {
+        Some(BudgetThreshold::Warning)
+    } else {
+        None
+    }
+}
+
+/// In-app notifications of each profile, and the budget alert rules that raise them.
+///
+/// Each threshold is raised once per category and budget month, however many transactions cross
+/// it afterwards. Notifications are only stored here; the frontend shows those raised with
+/// `notify_desktop` as desktop notifications as well.
+#[derive(Clone)]
+pub struct NotificationService {
+    repo: Arc<dyn NotificationRepository>,
+}
+
+impl NotificationService {
+    pub fn new(repo: Arc<dyn NotificationRepository>) -> Self {
+        Self { repo }
+    }
+
+    pub async fn get_notifications(
+        &self,
+        profile_id: i32,
+        unread_only: bool,
+    ) -> Result<Vec<NotificationDTO>, ErrorResponse> {
+        let notifications = self.repo.get_notifications(profile_id, unread_only).await?;
+        Ok(notifications
+            .into_iter()
+            .map(NotificationDTO::from)
+            .collect())
+    }
+
+    pub async fn set_read(
+        &self,
+        profile_id: i32,
+        id: i32,
+        read: bool,
+    ) -> Result<NotificationDTO, ErrorResponse> {
+        match self.repo.set_read(profile_id, id, read).await? {
+            Some(notification) => Ok(notification.into()),
+            None => Err(ErrorResponse::keyed(
+                ErrorCode::SearchObjectNotFoundError,
+                Some("id".into()),
+                "notification-not-found",
+            )),
+        }
+    }
+
+    pub async fn mark_all_read(&self, profile_id: i32) -> Result<u64, ErrorResponse> {
+        self.repo.mark_all_read(profile_id).await
+    }
+
+    pub async fn get_alert_rules(
+        &self,
+        profile_id: i32,
+    ) -> Result<Vec<BudgetAlertRuleDTO>, ErrorResponse> {
+        let rules = self.repo.get_alert_rules(profile_id).await?;
+        Ok(rules.into_iter().map(BudgetAlertRuleDTO::from).collect())
+    }
+
+    pub async fn set_alert_rule(
+        &self,
+        profile_id: i32,
+        rule: SetBudgetAlertRuleDTO,
+    ) -> Result<BudgetAlertRuleDTO, ErrorResponse> {
+        rule.validate()?;
+
+        let saved = self
+            .repo
+            .save_alert_rule(BudgetAlertRuleModel {
+                profile_id,
+                category_id: rule.category_id,
+                warn_at_percent: rule.warn_at_percent.unwrap_or(DEFAULT_WARN_AT_PERCENT),
+                notify_desktop: rule.notify_desktop,
+                updated_at: chrono::Utc::now().naive_utc(),
+            })
+            .await?;
+
+        Ok(saved.into())
+    }
+
+    pub async fn delete_alert_rule(
+        &self,
+        profile_id: i32,
+        category_id: i32,
+    ) -> Result<(), ErrorResponse> {
+        if self.repo.delete_alert_rule(profile_id, category_id).await? {
+            Ok(())
+        } else {
+            Err(ErrorResponse::keyed(
+                ErrorCode::SearchObjectNotFoundError,
+                Some("category_id".into()),
+                "budget-alert-rule-not-found",
+            ))
+        }
+    }
+
+    /// Checks the categories a new or imported transaction touched against their alert rules,
+    /// and returns the notifications this raised. Categories without a rule are not watched.
+    pub async fn check_budgets(
+        &self,
+        profile_id: i32,
+        budgets: &[CategoryBudget],
+    ) -> Result<Vec<RaisedNotificationDTO>, ErrorResponse> {
+        let rules = self.repo.get_alert_rules(profile_id).await?;
+        let mut raised = Vec::new();
+
+        for budget in budgets {
+            let Some(rule) = rules
+                .iter()
+                .find(|rule| rule.category_id == budget.category_id)
+            else {
+                continue;
+            };
+            let Some(threshold) =
+                budget_threshold(budget.allocated, budget.spent, rule.warn_at_percent)
+            else {
+                continue;
+            };
+
+            let payload = json!({
+                "categoryId": budget.category_id,
+                "month": budget.month,
+                "threshold": threshold,
+                "warnAtPercent": rule.warn_at_percent,
+                "allocated": budget.allocated,
+                "spent": budget.spent,
+            });
+            let dedup_key = format!(
+                "budget:{}:{}:{}",
+                budget.category_id,
+                budget.month,
+                threshold.as_str()
+            );
+
+            if let Some(notification) = self
+                .repo
+                .insert_notification(
+                    profile_id,
+                    NotificationKind::BudgetThreshold,
+                    payload.to_string(),
+                    Some(dedup_key),
+                )
+                .await?
+            {
+                raised.push(RaisedNotificationDTO {
+                    notification: notification.into(),
+                    notify_desktop: rule.notify_desktop,
+                });
+            }
+        }
+
+        Ok(raised)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repositories::memory::notification_repository::InMemoryNotificationRepository;
+
+    fn service() -> NotificationService {
+        NotificationService::new(Arc::new(InMemoryNotificationRepository::new()))
+    }
+
+    fn rule(category_id: i32, warn_at_percent: Option<i32>) -> SetBudgetAlertRuleDTO {
+        SetBudgetAlertRuleDTO {
+            category_id,
+            warn_at_percent,
+            notify_desktop: category_id == 1,
+        }
+    }
+
+    fn spent(category_id: i32, spent: Cents) -> CategoryBudget {
+        CategoryBudget {
+            category_id,
+            month: "2025-03-01".parse().unwrap(),
+            allocated: 50_000,
+            spent,
+        }
+    }
+
+    #[test]
+    fn thresholds_are_reached_at_the_warning_and_the_allocation() {
+        assert_eq!(budget_threshold(50_000, 39_999, 80), None);
+        assert_eq!(
+            budget_threshold(50_000, 40_000, 80),
+            Some(BudgetThreshold::Warning)
+        );
+        assert_eq!(
+            budget_threshold(50_000, 49_999, 80),
+            Some(BudgetThreshold::Warning)
+        );
+        assert_eq!(
+            budget_threshold(50_000, 50_000, 80),
+            Some(BudgetThreshold::Reached)
+        );
+        assert_eq!(
+            budget_threshold(50_000, 90_000, 80),
+            Some(BudgetThreshold::Reached)
+        );
+        assert_eq!(
+            budget_threshold(Cents::MAX, Cents::MAX - 1, 99),
+            Some(BudgetThreshold::Warning)
+        );
+        assert_eq!(budget_threshold(0, 1_000, 80), None);
+    }
+
+    #[tokio::test]
+    async fn each_threshold_is_raised_once_per_month() {
+        let service = service();
+        service.set_alert_rule(7, rule(1, None)).await.unwrap();
+        service.set_alert_rule(7, rule(2, Some(50))).await.unwrap();
+
+        let raised = service
+            .check_budgets(7, &[spent(1, 41_000), spent(2, 20_000), spent(3, 90_000)])
+            .await
+            .unwrap();
+        assert_eq!(raised.len(), 1);
+        assert!(raised[0].notify_desktop);
+        assert_eq!(raised[0].notification.payload["threshold"], "warning");
+        assert_eq!(raised[0].notification.payload["categoryId"], 1);
+
+        // Later transactions in the same month do not repeat the warning.
+        let raised = service
+            .check_budgets(7, &[spent(1, 45_000), spent(2, 26_000)])
+            .await
+            .unwrap();
+        assert_eq!(raised.len(), 1);
+        assert!(!raised[0].notify_desktop);
+
+        let raised = service.check_budgets(7, &[spent(1, 52_000)]).await.unwrap();
+        assert_eq!(raised[0].notification.payload["threshold"], "reached");
+
+        let next_month = CategoryBudget {
+            month: "2025-04-01".parse().unwrap(),
+            ..spent(1, 41_000)
+        };
+        assert_eq!(
+            service.check_budgets(7, &[next_month]).await.unwrap().len(),
+            1
+        );
+
+        // Rules belong to their profile.
+        assert!(service
+            .check_budgets(8, &[spent(1, 52_000)])
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn notifications_can_be_read_and_unread() {
+        let service = service();
+        service.set_alert_rule(7, rule(1, None)).await.unwrap();
+        service.check_budgets(7, &[spent(1, 41_000)]).await.unwrap();
+        service.check_budgets(7, &[spent(1, 60_000)]).await.unwrap();
+
+        let notifications = service.get_notifications(7, true).await.unwrap();
+        assert_eq!(notifications.len(), 2);
+        assert_eq!(notifications[0].payload["threshold"], "reached");
+
+        let read = service
+            .set_read(7, notifications[0].id, true)
+            .await
+            .unwrap();
+        assert!(read.read_at.is_some());
+        assert_eq!(service.get_notifications(7, true).await.unwrap().len(), 1);
+
+        service.set_read(7, read.id, false).await.unwrap();
+        assert_eq!(service.mark_all_read(7).await.unwrap(), 2);
+        assert!(service.get_notifications(7, true).await.unwrap().is_empty());
+        assert_eq!(service.get_notifications(7, false).await.unwrap().len(), 2);
+
+        let err = service.set_read(8, read.id, true).await.unwrap_err();
+        assert!(matches!(
+            err.code_name,
+            ErrorCode::SearchObjectNotFoundError
+        ));
+    }
+
+    #[tokio::test]
+    async fn alert_rules_are_validated_and_replaced() {
+        let service = service();
+
+        let err = service
+            .set_alert_rule(7, rule(1, Some(100)))
+            .await
+            .unwrap_err();
+        assert!(matches!(err.code_name, ErrorCode::UserInputValidationError));
+        assert_eq!(err.field.as_deref(), Some("warn_at_percent"));
+        assert_eq!(
+            err.message,
+            "Warning must come between 1% and 99% of the allocation"
+        );
+
+        service.set_alert_rule(7, rule(1, None)).await.unwrap();
+        service.set_alert_rule(7, rule(1, Some(90))).await.unwrap();
+        let rules = service.get_alert_rules(7).await.unwrap();
+        assert_eq!(rules.len(), 1);
+        assert_eq!(rules[0].warn_at_percent, 90);
+
+        service.delete_alert_rule(7, 1).await.unwrap();
+        let err = service.delete_alert_rule(7, 1).await.unwrap_err();
+        assert!(matches!(
+            err.code_name,
+            ErrorCode::SearchObjectNotFoundError
+        ));
+    }
+}
@@ -0,0 +1,236 @@
+use crate::{
+    models::v1::preferences_model::{PreferencesModel, PREFERENCES_VERSION},
+    repositories::v1::preferences_repository::PreferencesRepository,
+    services::{
+        dto::preferences_dto::{PreferencesDTO, SetPreferencesDTO},
+        settings_service::SettingsService,
+    },
+    utils::error::mapping::ErrorResponse,
+};
+use std::sync::Arc;
+use validator::Validate;
+
+/// Preferences of each profile, stored as versioned JSON so adding one needs no migration.
+///
+/// New profiles start from the app's default locale and currency. Profiles that predate this
+/// store have no row and follow those defaults until they save preferences of their own.
+#[derive(Clone)]
+pub struct PreferencesService {
+    repo: Arc<dyn PreferencesRepository>,
+    settings: SettingsService,
+}
+
+impl PreferencesService {
+    pub fn new(repo: Arc<dyn PreferencesRepository>, settings: SettingsService) -> Self {
+        Self { repo, settings }
+    }
+
+    pub async fn create_defaults(&self, profile_id: i32) -> Result<PreferencesDTO, ErrorResponse> {
+        let preferences = self.defaults();
+        self.save(profile_id, &preferences).await?;
+
+        Ok(preferences.into())
+    }
+
+    pub async fn get_preferences(&self, profile_id: i32) -> Result<PreferencesDTO, ErrorResponse> {
+        Ok(self.load(profile_id).await?.into())
+    }
+
+    pub async fn set_preferences(
+        &self,
+        profile_id: i32,
+        changes: SetPreferencesDTO,
+    ) -> Result<PreferencesDTO, ErrorResponse> {
+        changes.validate()?;
+
+        let mut preferences = self.load(profile_id).await?;
+        changes.apply_to(&mut preferences);
+        self.save(profile_id, &preferences).await?;
+
+        Ok(preferences.into())
+    }
+
+    /// Locale to show messages in: that of the profile, or the app's default one without a
+    /// profile or when its preferences cannot be read.
+    pub async fn locale(&self, profile_id: Option<i32>) -> String {
+        match profile_id {
+            Some(profile_id) => match self.load(profile_id).await {
+                Ok(preferences) => preferences.locale,
+                Err(_) => self.settings.current().default_locale,
+            },
+            None => self.settings.current().default_locale,
+        }
+    }
+
+    fn defaults(&self) -> PreferencesModel {
+        let settings = self.settings.current();
+
+        PreferencesModel {
+            locale: settings.default_locale,
+            base_currency: settings.default_currency,
+            ..PreferencesModel::default()
+        }
+    }
+
+    async fn load(&self, profile_id: i32) -> Result<PreferencesModel, ErrorResponse> {
+        let Some(stored) = self.repo.get_by_profile_id(profile_id).await? else {
+            return Ok(self.defaults());
+        };
+
+        match PreferencesModel::from_stored(stored.version, &stored.preferences, self.defaults()) {
+            Ok(preferences) => Ok(preferences),
+            Err(err) => {
+                log::warn!("Ignoring unreadable preferences of profile {profile_id}: {err}");
+                Ok(self.defaults())
+            }
+        }
+    }
+
+    async fn save(
+        &self,
+        profile_id: i32,
+        preferences: &PreferencesModel,
+    ) -> Result<(), ErrorResponse> {
+        let preferences =
+            serde_json::to_string(preferences).map_err(|_| ErrorResponse::unhandled())?;
+
+        self.repo
+            .save(profile_id, PREFERENCES_VERSION, preferences)
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::v1::preferences_model::{LayoutMode, Theme},
+        repositories::memory::preferences_repository::InMemoryPreferencesRepository,
+        services::dto::settings_dto::SettingsDTO,
+        utils::error::mapping::ErrorCode,
+    };
+
+    fn service() -> (PreferencesService, Arc<InMemoryPreferencesRepository>) {
+        let dir =
+            std::env::temp_dir().join(format!("nomorebeans-preferences-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let settings = SettingsService::load_from(dir.join("settings.toml")).unwrap();
+        let current = SettingsDTO::from(settings.current());
+        settings
+            .update_settings(SettingsDTO {
+                default_locale: "pt-BR".into(),
+                default_currency: "BRL".into(),
+                ..current
+            })
+            .unwrap();
+
+        let repo = Arc::new(InMemoryPreferencesRepository::new());
+        (PreferencesService::new(repo.clone(), settings), repo)
+    }
+
+    #[tokio::test]
+    async fn defaults_follow_app_settings() {
+        let (service, _) = service();
+
+        let created = service.create_defaults(1).await.unwrap();
+
+        assert_eq!(created.locale, "pt-BR");
+        assert_eq!(created.base_currency, "BRL");
+        assert_eq!(created.budget_month_start_day, 1);
+        assert_eq!(service.get_preferences(2).await.unwrap().locale, "pt-BR");
+    }
+
+    #[tokio::test]
+    async fn set_preferences_changes_only_given_fields() {
+        let (service, _) = service();
+        service.create_defaults(1).await.unwrap();
+
+        service
+            .set_preferences(
+                1,
+                SetPreferencesDTO {
+                    theme: Some(Theme::Dark),
+                    ..SetPreferencesDTO::default()
+                },
+            )
+            .await
+            .unwrap();
+        let updated = service
+            .set_preferences(
+                1,
+                SetPreferencesDTO {
+                    layout_mode: Some(LayoutMode::Grid),
+                    ..SetPreferencesDTO::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(updated.theme, Theme::Dark);
+        assert_eq!(updated.layout_mode, LayoutMode::Grid);
+        assert_eq!(updated.base_currency, "BRL");
+    }
+
+    #[tokio::test]
+    async fn locale_follows_the_profile() {
+        let (service, _) = service();
+        service
+            .set_preferences(
+                1,
+                SetPreferencesDTO {
+                    locale: Some("en-US".into()),
+                    ..SetPreferencesDTO::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(service.locale(Some(1)).await, "en-US");
+        assert_eq!(service.locale(Some(2)).await, "pt-BR");
+        assert_eq!(service.locale(None).await, "pt-BR");
+    }
+
+    #[tokio::test]
+    async fn set_preferences_rejects_invalid_values() {
+        let (service, _) = service();
+
+        let err = service
+            .set_preferences(
+                1,
+                SetPreferencesDTO {
+                    budget_month_start_day: Some(31),
+                    ..SetPreferencesDTO::default()
+                },
+            )
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err.code_name, ErrorCode::UserInputValidationError));
+        assert_eq!(err.field.as_deref(), Some("budget_month_start_day"));
+    }
+
+    #[tokio::test]
+    async fn stored_preferences_keep_unknown_keys() {
+        let (service, repo) = service();
+        repo.save(
+            1,
+            PREFERENCES_VERSION + 1,
+            r#"{"theme":"dark","compact":true}"#.into(),
+        )
+        .await
+        .unwrap();
+
+        let loaded = service.get_preferences(1).await.unwrap();
+        assert_eq!(loaded.theme, Theme::Dark);
+        assert_eq!(loaded.locale, "pt-BR");
+
+        service
+            .set_preferences(1, SetPreferencesDTO::default())
+            .await
+            .unwrap();
+        let stored = repo.get_by_profile_id(1).await.unwrap().unwrap();
+        assert!(stored.preferences.contains(r#""compact":true"#));
+    }
+}
@@ -0,0 +1,465 @@
+use crate::{
+    models::v1::{
+        notification_model::BudgetAlertRuleModel, preferences_model::ProfilePreferencesModel,
+        profile_model::ProfileModel,
+    },
+    repositories::v1::{
+        notification_repository::NotificationRepository,
+        preferences_repository::PreferencesRepository,
+        profile_archive_repository::ProfileArchiveRepository,
+        profile_repository::ProfileRepository,
+    },
+    services::{
+        dto::{
+            notification_dto::SetBudgetAlertRuleDTO,
+            profile_archive_dto::{
+                ArchivedBudgetAlertRuleDTO, ArchivedPreferencesDTO, ArchivedProfileDTO,
+                ProfileArchiveDTO,
+            },
+            profile_dto::{CreateProfileDTO, GetProfileDTO, ProfileSecretKind},
+        },
+        profile_service::{discard_unsaved_picture, store_profile_picture, ProfileService},
+    },
+    utils::{
+        crypto::encryption,
+        error::mapping::{ErrorCode, ErrorResponse},
+        fs::archive::{self, PROFILE_ARCHIVE},
+    },
+};
+use std::{collections::BTreeMap, path::PathBuf, sync::Arc};
+use validator::Validate;
+
+const PROFILE_FILE: &str = "profile.json";
+const AVATAR_FILE: &str = "avatars/avatar.webp";
+
+const MAX_USERNAME_LENGTH: usize = 16;
+const MAX_USERNAME_SUFFIX: u32 = 999;
+
+/// Moves a profile between installs as a single archive; see [`archive`] for the format.
+///
+/// Only the full-size avatar travels. On import it goes through the same decoding as an upload,
+/// which also rebuilds the smaller sizes. Archives are encrypted when exported with a passphrase.
+#[derive(Clone)]
+pub struct ProfileArchiveService {
+    profiles: ProfileService,
+    profile_repo: Arc<dyn ProfileRepository>,
+    preferences_repo: Arc<dyn PreferencesRepository>,
+    notification_repo: Arc<dyn NotificationRepository>,
+    archive_repo: Arc<dyn ProfileArchiveRepository>,
+}
+
+impl ProfileArchiveService {
+    pub fn new(
+        profiles: ProfileService,
+        profile_repo: Arc<dyn ProfileRepository>,
+        preferences_repo: Arc<dyn PreferencesRepository>,
+        notification_repo: Arc<dyn NotificationRepository>,
+        archive_repo: Arc<dyn ProfileArchiveRepository>,
+    ) -> Self {
+        Self {
+            profiles,
+            profile_repo,
+            preferences_repo,
+            notification_repo,
+            archive_repo,
+        }
+    }
+
+    /// The same service, auditing imports as made by `actor`.
+    pub fn acting_as(&self, actor: Option<i32>) -> Self {
+        Self {
+            profiles: self.profiles.acting_as(actor),
+            ..self.clone()
+        }
+    }
+
+    pub async fn export_profile(
+        &self,
+        id: i32,
+        path: PathBuf,
+        passphrase: Option<String>,
+    ) -> Result<(), ErrorResponse> {
+        if let Some(passphrase) = &passphrase {
+            encryption::check_passphrase(passphrase)?;
+        }
+        // Refuses locked profiles.
+        self.profiles.get_one_by_id(id).await?;
+
+        let profile = self
+            .profile_repo
+            .get_one_by_id(id)
+            .await?
+            .ok_or_else(|| profile_not_found("id"))?;
+        let preferences = self.preferences_repo.get_by_profile_id(id).await?;
+        let budget_alert_rules = self.notification_repo.get_alert_rules(id).await?;
+
+        let mut files = BTreeMap::new();
+        let mut avatar = None;
+        if let Some(url) = &profile.profile_picture_url {
+            match tokio::fs::read(url).await {
+                Ok(bytes) => {
+                    files.insert(AVATAR_FILE.to_string(), bytes);
+                    avatar = Some(AVATAR_FILE.to_string());
+                }
+                Err(err) => log::warn!("Exporting profile {id} without its avatar: {err}"),
+            }
+        }
+
+        let archive = ProfileArchiveDTO {
+            profile: ArchivedProfileDTO {
+                username: profile.username,
+                display_name: profile.display_name,
+                created_at: profile.created_at,
+                updated_at: profile.updated_at,
+                avatar,
+                secret_kind: profile
+                    .secret_kind
+                    .as_deref()
+                    .and_then(ProfileSecretKind::from_column),
+                secret_hash: profile.secret_hash,
+            },
+            preferences: preferences.and_then(|preferences| {
+                match serde_json::from_str(&preferences.preferences) {
+                    Ok(value) => Some(ArchivedPreferencesDTO {
+                        version: preferences.version,
+                        preferences: value,
+                    }),
+                    Err(err) => {
+                        log::warn!(
+                            "Exporting profile {id} without its unreadable preferences: {err}"
+                        );
+                        None
+                    }
+                }
+            }),
+            budget_alert_rules: budget_alert_rules
+                .into_iter()
+                .map(|rule| ArchivedBudgetAlertRuleDTO {
+                    category_id: rule.category_id,
+                    warn_at_percent: rule.warn_at_percent,
+                    notify_desktop: rule.notify_desktop,
+                    updated_at: rule.updated_at,
+                })
+                .collect(),
+        };
+        let archive =
+            serde_json::to_vec_pretty(&archive).map_err(|_| ErrorResponse::unhandled())?;
+        files.insert(PROFILE_FILE.to_string(), archive);
+
+        tokio::task::spawn_blocking(move || {
+            archive::write_archive(&PROFILE_ARCHIVE, &path, &files, passphrase.as_deref())
+        })
+        .await
+        .map_err(|_| ErrorResponse::unhandled())?
+    }
+
+    /// Adds the archived profile as a new one. Its username gets a number appended when it is
+    /// taken here. Nothing is written unless the whole archive checks out.
+    pub async fn import_profile(
+        &self,
+        path: PathBuf,
+        passphrase: Option<String>,
+    ) -> Result<GetProfileDTO, ErrorResponse> {
+        let mut files = tokio::task::spawn_blocking(move || {
+            archive::read_archive(&PROFILE_ARCHIVE, &path, passphrase.as_deref())
+        })
+        .await
+        .map_err(|_| ErrorResponse::unhandled())??;
+
+        let archive: ProfileArchiveDTO = files
+            .get(PROFILE_FILE)
+            .and_then(|bytes| serde_json::from_slice(bytes).ok())
+            .ok_or_else(|| corrupted("archive-no-profile"))?;
+        let imported = archive.profile;
+
+        // Held to the same rules as a profile created here.
+        CreateProfileDTO {
+            username: imported.username.clone(),
+            display_name: imported.display_name.clone(),
+            profile_picture_bytes: None,
+            profile_picture_crop: None,
+        }
+        .validate()?;
+        if imported.secret_hash.is_some() != imported.secret_kind.is_some() {
+            return Err(corrupted("archive-incomplete-lock"));
+        }
+        for rule in &archive.budget_alert_rules {
+            SetBudgetAlertRuleDTO {
+                category_id: rule.category_id,
+                warn_at_percent: Some(rule.warn_at_percent),
+                notify_desktop: rule.notify_desktop,
+            }
+            .validate()?;
+        }
+
+        let username = self.free_username(&imported.username).await?;
+        let avatar = match &imported.avatar {
+            Some(name) => Some(
+                files
+                    .remove(name)
+                    .ok_or_else(|| corrupted("archive-avatar-missing"))?,
+            ),
+            None => None,
+        };
+        let profile_picture_url = store_profile_picture(avatar, None).await?;
+
+        let now = chrono::Utc::now().naive_utc();
+        let profile = ProfileModel {
+            id: 0,
+            created_at: imported.created_at,
+            updated_at: imported.updated_at,
+            deleted_at: None,
+            username,
+            original_username: None,
+            display_name: imported.display_name,
+            profile_picture_url: profile_picture_url.clone(),
+            secret_hash: imported.secret_hash,
+            secret_kind: imported.secret_kind.map(|kind| kind.as_str().to_string()),
+            failed_unlock_attempts: 0,
+            unlock_blocked_until: None,
+        };
+        let preferences = archive
+            .preferences
+            .map(|preferences| ProfilePreferencesModel {
+                profile_id: 0,
+                version: preferences.version,
+                preferences: preferences.preferences.to_string(),
+                updated_at: now,
+            });
+        let budget_alert_rules = archive
+            .budget_alert_rules
+            .into_iter()
+            .map(|rule| BudgetAlertRuleModel {
+                profile_id: 0,
+                category_id: rule.category_id,
+                warn_at_percent: rule.warn_at_percent,
+                notify_desktop: rule.notify_desktop,
+                updated_at: rule.updated_at,
+            })
+            .collect();
+
+        let result = self
+            .archive_repo
+            .import_profile(
+                profile,
+                preferences,
+                budget_alert_rules,
+                self.profiles.actor(),
+            )
+            .await;
+        let profile = discard_unsaved_picture(result, profile_picture_url.as_deref()).await?;
+
+        Ok(self.profiles.to_dto(profile))
+    }
+
+    /// `base` when nobody here uses it, otherwise the first free `base2`, `base3`, ... cut down to
+    /// fit the username length limit.
+    async fn free_username(&self, base: &str) -> Result<String, ErrorResponse> {
+        for number in 1..=MAX_USERNAME_SUFFIX {
+            let candidate = match number {
+                1 => base.to_string(),
+                _ => {
+                    let suffix = number.to_string();
+                    let keep = base.len().min(MAX_USERNAME_LENGTH - suffix.len());
+                    format!("{}{suffix}", &base[..keep])
+                }
+            };
+
+            if self
+                .profile_repo
+                .get_one_by_username(candidate.clone())
+                .await?
+                .is_none()
+            {
+                return Ok(candidate);
+            }
+        }
+
+        Err(ErrorResponse::keyed(
+            ErrorCode::UserInputValidationError,
+            Some("username".into()),
+            "archive-no-free-username",
+        )
+        .with_arg("username", base))
+    }
+}
+
+fn profile_not_found(field: &str) -> ErrorResponse {
+    ErrorResponse::keyed(
+        ErrorCode::SearchObjectNotFoundError,
+        Some(field.into()),
+        "profile-not-found",
+    )
+}
+
+fn corrupted(message_id: &'static str) -> ErrorResponse {
+    ErrorResponse::keyed(ErrorCode::CorruptedFileError, None, message_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        repositories::memory::{
+            notification_repository::InMemoryNotificationRepository,
+            preferences_repository::InMemoryPreferencesRepository,
+            profile_archive_repository::InMemoryProfileArchiveRepository,
+            profile_repository::InMemoryProfileRepository,
+        },
+        services::dto::profile_dto::SetProfileSecretDTO,
+    };
+
+    struct Install {
+        service: ProfileArchiveService,
+        preferences: Arc<InMemoryPreferencesRepository>,
+        notifications: Arc<InMemoryNotificationRepository>,
+    }
+
+    fn install() -> Install {
+        let profile_repo = Arc::new(InMemoryProfileRepository::new());
+        let preferences_repo = Arc::new(InMemoryPreferencesRepository::new());
+        let notification_repo = Arc::new(InMemoryNotificationRepository::new());
+        let archive_repo = Arc::new(InMemoryProfileArchiveRepository::new(
+            profile_repo.clone(),
+            preferences_repo.clone(),
+            notification_repo.clone(),
+        ));
+
+        Install {
+            service: ProfileArchiveService::new(
+                ProfileService::new(profile_repo.clone()),
+                profile_repo,
+                preferences_repo.clone(),
+                notification_repo.clone(),
+                archive_repo,
+            ),
+            preferences: preferences_repo,
+            notifications: notification_repo,
+        }
+    }
+
+    fn archive_path() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("nomorebeans-export-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.join("profile.zip")
+    }
+
+    async fn create_profile(install: &Install, username: &str) -> GetProfileDTO {
+        install
+            .service
+            .profiles
+            .create_profile(CreateProfileDTO {
+                username: username.into(),
+                display_name: Some("Alice".into()),
+                profile_picture_bytes: None,
+                profile_picture_crop: None,
+            })
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn exported_profile_imports_elsewhere() {
+        let source = install();
+        let alice = create_profile(&source, "alice").await;
+        source
+            .service
+            .profiles
+            .set_profile_secret(
+                alice.id,
+                Some(SetProfileSecretDTO {
+                    kind: ProfileSecretKind::Pin,
+                    secret: "1234".into(),
+                }),
+            )
+            .await
+            .unwrap();
+        source
+            .preferences
+            .save(alice.id, 1, r#"{"theme":"dark"}"#.into())
+            .await
+            .unwrap();
+        let rule = BudgetAlertRuleModel {
+            profile_id: alice.id,
+            category_id: 3,
+            warn_at_percent: 90,
+            notify_desktop: true,
+            updated_at: alice.updated_at,
+        };
+        source
+            .notifications
+            .save_alert_rule(rule.clone())
+            .await
+            .unwrap();
+        let path = archive_path();
+
+        source
+            .service
+            .export_profile(alice.id, path.clone(), None)
+            .await
+            .unwrap();
+
+        let target = install();
+        create_profile(&target, "bob").await;
+        let imported = target.service.import_profile(path, None).await.unwrap();
+
+        assert_eq!(imported.username, "alice");
+        assert_eq!(imported.display_name.as_deref(), Some("Alice"));
+        assert!(imported.locked);
+        let preferences = target
+            .preferences
+            .get_by_profile_id(imported.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(preferences.preferences, r#"{"theme":"dark"}"#);
+        let rules = target
+            .notifications
+            .get_alert_rules(imported.id)
+            .await
+            .unwrap();
+        assert_eq!(
+            rules,
+            [BudgetAlertRuleModel {
+                profile_id: imported.id,
+                ..rule
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn import_renames_taken_username() {
+        let install = install();
+        let alice = create_profile(&install, "alice").await;
+        let path = archive_path();
+        install
+            .service
+            .export_profile(alice.id, path.clone(), None)
+            .await
+            .unwrap();
+
+        let first = install
+            .service
+            .import_profile(path.clone(), None)
+            .await
+            .unwrap();
+        let second = install.service.import_profile(path, None).await.unwrap();
+
+        assert_eq!(first.username, "alice2");
+        assert_eq!(second.username, "alice3");
+    }
+
+    #[tokio::test]
+    async fn import_refuses_other_files() {
+        let path = archive_path();
+        std::fs::write(&path, "not an archive").unwrap();
+
+        let err = install()
+            .service
+            .import_profile(path, None)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err.code_name, ErrorCode::CorruptedFileError));
+    }
+}
@@ -1,19 +1,68 @@
 use crate::{
-    models::v1::profile_model::ProfileModel,
-    repositories,
-    services::dto::profile_dto::{CreateProfileDTO, GetProfileDTO, UpdateProfileDTO},
-    utils::error::mapping::{ErrorCode, ErrorResponse},
+    models::{
+        patch::Patch,
+        v1::{audit_model::AuditOperation, profile_model::ProfileModel},
+    },
+    repositories::v1::profile_repository::ProfileRepository,
+    services::{
+        dto::profile_dto::{
+            CreateProfileDTO, DeletedProfileDTO, GetProfileDTO, ProfilePictureCropDTO,
+            RestoreProfileDTO, SetProfileSecretDTO, UnlockProfileDTO, UpdateProfileDTO,
+        },
+        undo_service::{UndoHistory, UndoableChange},
+    },
+    utils::{
+        crypto::secret,
+        error::mapping::{ErrorCode, ErrorResponse},
+        fs::profile_picture::{self, CropRect},
+    },
+};
+use chrono::{Duration, NaiveDateTime, Utc};
+use std::{
+    collections::HashSet,
+    path::PathBuf,
+    sync::{Arc, Mutex},
 };
 use validator::Validate;
 
+const FREE_UNLOCK_ATTEMPTS: i32 = 5;
+const BASE_UNLOCK_LOCKOUT_SECS: i64 = 30;
+const MAX_UNLOCK_LOCKOUT_SECS: i64 = 15 * 60;
+
 #[derive(Clone)]
 pub struct ProfileService {
-    repo: repositories::v1::profile_repository::ProfileRepository,
+    repo: Arc<dyn ProfileRepository>,
+    unlocked: Arc<Mutex<HashSet<i32>>>, // Profiles with a secret that were unlocked since startup.
+    actor: Option<i32>,                 // Who changes are audited as; None for the app itself.
+    history: UndoHistory,
 }
 
 impl ProfileService {
-    pub fn new(repo: repositories::v1::profile_repository::ProfileRepository) -> Self {
-        Self { repo }
+    pub fn new(repo: Arc<dyn ProfileRepository>) -> Self {
+        Self {
+            repo,
+            unlocked: Arc::default(),
+            actor: None,
+            history: UndoHistory::default(),
+        }
+    }
+
+    /// The same service, auditing the changes it makes as made by `actor`.
+    pub fn acting_as(&self, actor: Option<i32>) -> Self {
+        Self {
+            actor,
+            ..self.clone()
+        }
+    }
+
+    pub fn actor(&self) -> Option<i32> {
+        self.actor
+    }
+
+    /// Where edits and moves to and from the trash are recorded for undo. Creations, purges and
+    /// secret changes are not.
+    pub fn history(&self) -> UndoHistory {
+        self.history.clone()
     }
 
     pub async fn create_profile(
@@ -22,66 +71,208 @@ impl ProfileService {
     ) -> Result<GetProfileDTO, ErrorResponse> {
         profile.validate()?;
 
-        let profile = self
+        let profile_picture_url =
+            store_profile_picture(profile.profile_picture_bytes, profile.profile_picture_crop)
+                .await?;
+        let display_name = profile
+            .display_name
+            .map(|display_name| display_name.trim().to_string());
+
+        let result = self
             .repo
             .create_profile(
                 profile.username,
-                profile.display_name,
-                profile.profile_picture_bytes,
+                display_name,
+                profile_picture_url.clone(),
+                self.actor,
             )
-            .await?;
-
-        let dto = GetProfileDTO::try_from(profile).map_err(|_| ErrorResponse::unhandled())?; // TODO: improve error handling
+            .await;
+        let profile = discard_unsaved_picture(result, profile_picture_url.as_deref()).await?;
 
-        Ok(dto)
+        Ok(self.to_dto(profile))
     }
 
+    /// Lists every profile, locked ones included, so the picker can offer to unlock them.
     pub async fn get_all(&self) -> Result<Vec<GetProfileDTO>, ErrorResponse> {
         let users: Vec<ProfileModel> = self.repo.get_all().await?;
-        let dtos: Result<Vec<_>, _> = users.into_iter().map(GetProfileDTO::try_from).collect();
 
-        dtos.map_err(|_| ErrorResponse::unhandled()) // TODO: improve error handling
+        Ok(users.into_iter().map(|user| self.to_dto(user)).collect())
     }
 
     pub async fn get_one_by_id(&self, id: i32) -> Result<GetProfileDTO, ErrorResponse> {
-        let profile: Option<ProfileModel> = self.repo.get_one_by_id(id).await?;
+        let profile = self.unlocked_profile(id).await?;
 
-        if let None = profile {
-            return Err(ErrorResponse::new(
-                ErrorCode::SearchObjectNotFoundError,
-                Some("id".into()),
-                "Profile not found",
-            ));
+        Ok(self.to_dto(profile))
+    }
+
+    pub async fn get_one_by_username(
+        &self,
+        username: impl Into<String>,
+    ) -> Result<GetProfileDTO, ErrorResponse> {
+        let profile = self
+            .repo
+            .get_one_by_username(username.into())
+            .await?
+            .ok_or_else(|| profile_not_found("username"))?;
+
+        self.ensure_unlocked(&profile)?;
+        Ok(self.to_dto(profile))
+    }
+
+    pub async fn delete_profile(&self, id: i32) -> Result<(), ErrorResponse> {
+        let before = self.unlocked_profile(id).await?;
+
+        if !self.repo.delete_profile(id, self.actor).await? {
+            return Err(profile_not_found("id"));
+        }
+
+        self.lock_profile(id);
+        if let Some(deleted) = self.repo.get_deleted_by_id(id).await? {
+            self.history.record(
+                AuditOperation::Delete,
+                UndoableChange::Profile {
+                    expected: deleted,
+                    revert_to: before,
+                },
+            );
         }
+        Ok(())
+    }
+
+    /// Lists the trash, newest first. `retention_days` only feeds `purge_at`; see
+    /// [`ProfileService::purge_expired`].
+    pub async fn get_deleted_profiles(
+        &self,
+        retention_days: u32,
+    ) -> Result<Vec<DeletedProfileDTO>, ErrorResponse> {
+        let retention = retention(retention_days);
+        let profiles = self.repo.get_deleted().await?;
 
-        let dto =
-            GetProfileDTO::try_from(profile.unwrap()).map_err(|_| ErrorResponse::unhandled())?; // TODO: improve error handling
+        Ok(profiles
+            .into_iter()
+            .map(|profile| {
+                let purge_at = retention
+                    .zip(profile.deleted_at)
+                    .map(|(retention, deleted_at)| deleted_at + retention);
 
-        Ok(dto)
+                DeletedProfileDTO::new(profile, purge_at)
+            })
+            .collect())
     }
 
-    pub async fn get_one_by_username(
+    /// Takes a profile out of the trash. Its old username may have been taken since, in which
+    /// case the caller has to pick another one.
+    pub async fn restore_profile(
         &self,
-        username: impl Into<String>,
+        id: i32,
+        restore: RestoreProfileDTO,
     ) -> Result<GetProfileDTO, ErrorResponse> {
-        let profile: Option<ProfileModel> = self.repo.get_one_by_username(username.into()).await?;
+        restore.validate()?;
+
+        let profile = self
+            .repo
+            .get_deleted_by_id(id)
+            .await?
+            .ok_or_else(|| profile_not_found("id"))?;
 
-        if let None = profile {
-            return Err(ErrorResponse::new(
-                ErrorCode::SearchObjectNotFoundError,
+        let username = restore
+            .username
+            .or(profile.original_username.clone())
+            .ok_or_else(|| {
+                ErrorResponse::keyed(
+                    ErrorCode::UserInputValidationError,
+                    Some("username".into()),
+                    "profile-restore-needs-username",
+                )
+            })?;
+
+        if self
+            .repo
+            .get_one_by_username(username.clone())
+            .await?
+            .is_some()
+        {
+            return Err(ErrorResponse::keyed(
+                ErrorCode::UserInputValidationError,
                 Some("username".into()),
-                "Profile not found",
-            ));
+                "profile-restore-username-taken",
+            )
+            .with_arg("username", username));
         }
 
-        let dto =
-            GetProfileDTO::try_from(profile.unwrap()).map_err(|_| ErrorResponse::unhandled())?; // TODO: improve error handling
+        let restored = self
+            .repo
+            .restore_profile(id, username, self.actor)
+            .await?
+            .ok_or_else(|| profile_not_found("id"))?;
 
-        Ok(dto)
+        self.history.record(
+            AuditOperation::Restore,
+            UndoableChange::Profile {
+                expected: restored.clone(),
+                revert_to: profile,
+            },
+        );
+        Ok(self.to_dto(restored))
     }
 
-    pub async fn delete_profile(&self, id: i32) -> Result<(), ErrorResponse> {
-        self.repo.delete_profile(id).await
+    /// Erases a trashed profile for good, along with everything it owns and its avatar files.
+    pub async fn purge_profile(&self, id: i32) -> Result<(), ErrorResponse> {
+        let profile = self
+            .repo
+            .get_deleted_by_id(id)
+            .await?
+            .ok_or_else(|| profile_not_found("id"))?;
+
+        if !self.repo.purge_profile(id, self.actor).await? {
+            return Err(profile_not_found("id"));
+        }
+
+        if let Some(url) = profile.profile_picture_url {
+            let path = PathBuf::from(&url);
+            let removed =
+                tokio::task::spawn_blocking(move || profile_picture::remove_profile_picture(&path))
+                    .await;
+
+            // The orphan sweep gets whatever is left next time.
+            if !matches!(removed, Ok(Ok(()))) {
+                log::warn!("Could not remove avatar {url} of purged profile {id}");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Purges profiles that have been in the trash longer than `retention_days` and returns how
+    /// many went. `0` keeps them forever.
+    pub async fn purge_expired(&self, retention_days: u32) -> Result<usize, ErrorResponse> {
+        let Some(retention) = retention(retention_days) else {
+            return Ok(0);
+        };
+
+        self.purge_deleted_before(Utc::now().naive_utc() - retention)
+            .await
+    }
+
+    async fn purge_deleted_before(&self, cutoff: NaiveDateTime) -> Result<usize, ErrorResponse> {
+        let expired: Vec<i32> = self
+            .repo
+            .get_deleted()
+            .await?
+            .into_iter()
+            .filter(|profile| {
+                profile
+                    .deleted_at
+                    .is_some_and(|deleted_at| deleted_at <= cutoff)
+            })
+            .map(|profile| profile.id)
+            .collect();
+
+        for id in &expired {
+            self.purge_profile(*id).await?;
+        }
+
+        Ok(expired.len())
     }
 
     pub async fn update_profile(
@@ -89,18 +280,841 @@ impl ProfileService {
         id: i32,
         profile: UpdateProfileDTO,
     ) -> Result<GetProfileDTO, ErrorResponse> {
-        let profile = self
+        profile.validate()?;
+        let current = self.unlocked_profile(id).await?;
+
+        if profile.is_empty() {
+            return Ok(self.to_dto(current));
+        }
+
+        let profile_picture_url = match profile.profile_picture_bytes {
+            Patch::Value(bytes) => store_profile_picture(Some(bytes), profile.profile_picture_crop)
+                .await?
+                .map_or(Patch::Null, Patch::Value),
+            Patch::Null => Patch::Null,
+            Patch::Missing => Patch::Missing,
+        };
+        let picture_changed = !profile_picture_url.is_missing();
+        let stored_picture_url = profile_picture_url.value().cloned();
+        let display_name = profile
+            .display_name
+            .map(|display_name| display_name.trim().to_string());
+
+        let result = self
             .repo
             .update_profile(
                 id,
                 profile.username,
-                profile.display_name,
-                profile.profile_picture_bytes,
+                display_name,
+                profile_picture_url,
+                self.actor,
             )
+            .await
+            .and_then(|profile| profile.ok_or_else(|| profile_not_found("id")));
+        let profile = discard_unsaved_picture(result, stored_picture_url.as_deref()).await?;
+        self.history.record(
+            AuditOperation::Update,
+            UndoableChange::Profile {
+                expected: profile.clone(),
+                revert_to: current,
+            },
+        );
+
+        // The previous picture just lost its last reference.
+        if picture_changed {
+            if let Err(err) = self.collect_orphaned_avatars().await {
+                log::warn!("Could not clean up old avatars: {}", err.message);
+            }
+        }
+
+        Ok(self.to_dto(profile))
+    }
+
+    /// Sets, replaces or (with `None`) removes the profile's PIN or passphrase. A profile that
+    /// already has one must be unlocked first.
+    pub async fn set_profile_secret(
+        &self,
+        id: i32,
+        secret: Option<SetProfileSecretDTO>,
+    ) -> Result<GetProfileDTO, ErrorResponse> {
+        self.unlocked_profile(id).await?;
+
+        let (secret_hash, secret_kind) = match secret {
+            Some(secret) => {
+                secret.validate()?;
+                secret.validate_secret()?;
+
+                let hash = hash_secret(secret.secret).await?;
+                (Some(hash), Some(secret.kind.as_str().to_string()))
+            }
+            None => (None, None),
+        };
+
+        if !self
+            .repo
+            .set_secret(id, secret_hash, secret_kind, self.actor)
+            .await?
+        {
+            return Err(profile_not_found("id"));
+        }
+
+        // Whoever just set the secret knows it; do not lock them out of their own profile.
+        self.mark_unlocked(id);
+        self.get_one_by_id(id).await
+    }
+
+    /// Checks the secret and unlocks the profile until [`ProfileService::lock_profile`]. After
+    /// [`FREE_UNLOCK_ATTEMPTS`] failures, each further failure doubles a waiting period during
+    /// which every attempt is refused, right or wrong.
+    pub async fn unlock_profile(
+        &self,
+        id: i32,
+        unlock: UnlockProfileDTO,
+    ) -> Result<GetProfileDTO, ErrorResponse> {
+        unlock.validate()?;
+
+        let profile = self
+            .repo
+            .get_one_by_id(id)
+            .await?
+            .ok_or_else(|| profile_not_found("id"))?;
+
+        let Some(secret_hash) = profile.secret_hash.clone() else {
+            return Ok(self.to_dto(profile));
+        };
+
+        let now = Utc::now().naive_utc();
+        if let Some(blocked_until) = profile.unlock_blocked_until.filter(|until| *until > now) {
+            return Err(too_many_attempts(blocked_until - now));
+        }
+
+        if verify_secret(unlock.secret, secret_hash).await? {
+            if profile.failed_unlock_attempts > 0 {
+                self.repo.set_unlock_attempts(id, 0, None).await?;
+            }
+
+            self.mark_unlocked(id);
+            return Ok(self.to_dto(profile));
+        }
+
+        let blocked_until = self
+            .repo
+            .record_failed_unlock(id, now, unlock_lockout)
             .await?;
 
-        let dto = GetProfileDTO::try_from(profile).map_err(|_| ErrorResponse::unhandled())?; // TODO: improve error handling
+        Err(match blocked_until {
+            Some(blocked_until) => too_many_attempts(blocked_until - now),
+            None => ErrorResponse::keyed(
+                ErrorCode::InsufficientPrivilegesError,
+                Some("secret".into()),
+                "profile-wrong-secret",
+            ),
+        })
+    }
+
+    /// Deletes avatar files that no profile, trashed ones included, points at and returns how
+    /// many went. Avatars that undo or redo could bring back are kept.
+    pub async fn collect_orphaned_avatars(&self) -> Result<usize, ErrorResponse> {
+        let referenced: HashSet<PathBuf> = self
+            .repo
+            .get_profile_picture_urls()
+            .await?
+            .into_iter()
+            .chain(self.history.profile_picture_urls())
+            .map(PathBuf::from)
+            .collect();
+
+        let removed = tokio::task::spawn_blocking(move || {
+            profile_picture::remove_unreferenced_profile_pictures(&referenced)
+        })
+        .await
+        .map_err(|_| ErrorResponse::unhandled())??;
+
+        Ok(removed)
+    }
+
+    /// Writes `revert_to` over the profile, provided it is still `expected`. Active profiles must be
+    /// unlocked, as for any other change; trashed ones can be brought back as from the trash.
+    pub(crate) async fn revert_profile(
+        &self,
+        expected: &ProfileModel,
+        revert_to: ProfileModel,
+    ) -> Result<ProfileModel, ErrorResponse> {
+        let id = expected.id;
+        let current = match self.repo.get_one_by_id(id).await? {
+            Some(profile) => {
+                self.ensure_unlocked(&profile)?;
+                Some(profile)
+            }
+            None => self.repo.get_deleted_by_id(id).await?,
+        };
+        if current.is_none_or(|current| current.updated_at != expected.updated_at) {
+            return Err(stale_profile());
+        }
+
+        let reverted = self
+            .repo
+            .revert_profile(revert_to, expected.updated_at, self.actor)
+            .await?
+            .ok_or_else(stale_profile)?;
+
+        if reverted.deleted_at.is_some() {
+            self.lock_profile(id);
+        }
+        Ok(reverted)
+    }
+
+    /// Avatar file for the avatar protocol, preferring the `size` variant when one exists. Not
+    /// profile-scoped, since the picker shows avatars of locked profiles too.
+    pub async fn avatar_path(
+        &self,
+        id: i32,
+        size: Option<u32>,
+    ) -> Result<Option<PathBuf>, ErrorResponse> {
+        let profile = match self.repo.get_one_by_id(id).await? {
+            Some(profile) => profile,
+            None => match self.repo.get_deleted_by_id(id).await? {
+                Some(profile) => profile,
+                None => return Ok(None),
+            },
+        };
+        let Some(path) = profile.profile_picture_url.map(PathBuf::from) else {
+            return Ok(None);
+        };
+
+        if let Some(size) = size {
+            let variant = profile_picture::variant_path(&path, size);
+            if tokio::fs::try_exists(&variant).await.unwrap_or(false) {
+                return Ok(Some(variant));
+            }
+        }
+
+        Ok(Some(path))
+    }
+
+    pub fn lock_profile(&self, id: i32) {
+        self.unlocked
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .remove(&id);
+    }
+
+    fn mark_unlocked(&self, id: i32) {
+        self.unlocked
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .insert(id);
+    }
+
+    fn is_locked(&self, profile: &ProfileModel) -> bool {
+        profile.secret_hash.is_some()
+            && !self
+                .unlocked
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .contains(&profile.id)
+    }
+
+    fn ensure_unlocked(&self, profile: &ProfileModel) -> Result<(), ErrorResponse> {
+        if self.is_locked(profile) {
+            return Err(ErrorResponse::keyed(
+                ErrorCode::InsufficientPrivilegesError,
+                Some("id".into()),
+                "profile-locked",
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Fetches a profile for a profile-scoped operation, refusing locked ones.
+    async fn unlocked_profile(&self, id: i32) -> Result<ProfileModel, ErrorResponse> {
+        let profile = self
+            .repo
+            .get_one_by_id(id)
+            .await?
+            .ok_or_else(|| profile_not_found("id"))?;
+
+        self.ensure_unlocked(&profile)?;
+        Ok(profile)
+    }
+
+    pub(crate) fn to_dto(&self, profile: ProfileModel) -> GetProfileDTO {
+        let locked = self.is_locked(&profile);
+        let mut dto = GetProfileDTO::from(profile);
+        dto.locked = locked;
+
+        dto
+    }
+}
+
+fn profile_not_found(field: &str) -> ErrorResponse {
+    ErrorResponse::keyed(
+        ErrorCode::SearchObjectNotFoundError,
+        Some(field.into()),
+        "profile-not-found",
+    )
+}
+
+fn stale_profile() -> ErrorResponse {
+    ErrorResponse::keyed(ErrorCode::StaleDataError, None, "profile-stale")
+}
+
+fn retention(retention_days: u32) -> Option<Duration> {
+    (retention_days > 0).then(|| Duration::days(i64::from(retention_days)))
+}
+
+fn too_many_attempts(wait: Duration) -> ErrorResponse {
+    let seconds = wait.num_seconds().max(1);
+
+    ErrorResponse::keyed(
+        ErrorCode::TooManyAttemptsError,
+        Some("secret".into()),
+        "profile-too-many-attempts",
+    )
+    .with_arg("seconds", seconds)
+}
+
+/// `None` while the attempt is still within the free ones.
+fn unlock_lockout(failed_unlock_attempts: i32) -> Option<Duration> {
+    let over = failed_unlock_attempts - FREE_UNLOCK_ATTEMPTS;
+    if over < 0 {
+        return None;
+    }
+
+    let seconds = BASE_UNLOCK_LOCKOUT_SECS
+        .saturating_mul(1_i64 << over.min(16))
+        .min(MAX_UNLOCK_LOCKOUT_SECS);
+
+    Some(Duration::seconds(seconds))
+}
+
+// Argon2 takes tens of milliseconds by design, so keep it off the async workers.
+async fn hash_secret(value: String) -> Result<String, ErrorResponse> {
+    tokio::task::spawn_blocking(move || secret::hash_secret(&value))
+        .await
+        .map_err(|_| ErrorResponse::unhandled())?
+}
+
+async fn verify_secret(value: String, hash: String) -> Result<bool, ErrorResponse> {
+    tokio::task::spawn_blocking(move || secret::verify_secret(&value, &hash))
+        .await
+        .map_err(|_| ErrorResponse::unhandled())?
+}
+
+/// Removes a freshly stored picture when the row that was meant to reference it was not written.
+pub(crate) async fn discard_unsaved_picture<T>(
+    result: Result<T, ErrorResponse>,
+    profile_picture_url: Option<&str>,
+) -> Result<T, ErrorResponse> {
+    if let (Err(_), Some(url)) = (&result, profile_picture_url) {
+        let path = PathBuf::from(url);
+        let removed =
+            tokio::task::spawn_blocking(move || profile_picture::remove_profile_picture(&path))
+                .await;
+
+        if !matches!(removed, Ok(Ok(()))) {
+            log::warn!("Could not remove unsaved avatar {url}");
+        }
+    }
+
+    result
+}
+
+// Decoding and resizing are CPU-bound, so they run on the blocking pool as well.
+pub(crate) async fn store_profile_picture(
+    bytes: Option<Vec<u8>>,
+    crop: Option<ProfilePictureCropDTO>,
+) -> Result<Option<String>, ErrorResponse> {
+    let Some(bytes) = bytes else {
+        return Ok(None);
+    };
+
+    let path = tokio::task::spawn_blocking(move || {
+        profile_picture::save_profile_picture(&bytes, crop.map(CropRect::from))
+    })
+    .await
+    .map_err(|_| ErrorResponse::unhandled())??;
+
+    Ok(Some(path.to_string_lossy().to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        repositories::memory::profile_repository::InMemoryProfileRepository,
+        services::dto::profile_dto::ProfileSecretKind,
+    };
+
+    fn service() -> ProfileService {
+        ProfileService::new(Arc::new(InMemoryProfileRepository::new()))
+    }
+
+    fn create_dto(username: &str) -> CreateProfileDTO {
+        CreateProfileDTO {
+            username: username.into(),
+            display_name: None,
+            profile_picture_bytes: None,
+            profile_picture_crop: None,
+        }
+    }
+
+    fn update_dto() -> UpdateProfileDTO {
+        UpdateProfileDTO::default()
+    }
+
+    #[tokio::test]
+    async fn create_profile_returns_created_profile() {
+        let service = service();
+
+        let created = service
+            .create_profile(CreateProfileDTO {
+                display_name: Some("  Alice  ".into()),
+                ..create_dto("alice")
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(created.username, "alice");
+        assert_eq!(created.display_name.as_deref(), Some("Alice"));
+        assert!(created.avatar.is_none());
+
+        let fetched = service.get_one_by_id(created.id).await.unwrap();
+        assert_eq!(fetched.username, "alice");
+    }
+
+    #[tokio::test]
+    async fn create_profile_rejects_invalid_username() {
+        let err = service()
+            .create_profile(create_dto("Al"))
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err.code_name, ErrorCode::UserInputValidationError));
+        assert_eq!(err.field.as_deref(), Some("username"));
+    }
+
+    #[tokio::test]
+    async fn create_profile_rejects_taken_username() {
+        let service = service();
+        service.create_profile(create_dto("alice")).await.unwrap();
+
+        let err = service
+            .create_profile(create_dto("alice"))
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err.code_name, ErrorCode::UserInputValidationError));
+        assert_eq!(err.field.as_deref(), Some("username"));
+    }
+
+    #[tokio::test]
+    async fn get_profile_not_found() {
+        let service = service();
+
+        let err = service.get_one_by_id(42).await.unwrap_err();
+        assert!(matches!(
+            err.code_name,
+            ErrorCode::SearchObjectNotFoundError
+        ));
+        assert_eq!(err.field.as_deref(), Some("id"));
+
+        let err = service.get_one_by_username("nobody").await.unwrap_err();
+        assert!(matches!(
+            err.code_name,
+            ErrorCode::SearchObjectNotFoundError
+        ));
+        assert_eq!(err.field.as_deref(), Some("username"));
+    }
+
+    #[tokio::test]
+    async fn update_profile_changes_only_given_fields() {
+        let service = service();
+        let created = service
+            .create_profile(CreateProfileDTO {
+                display_name: Some("Alice".into()),
+                ..create_dto("alice")
+            })
+            .await
+            .unwrap();
+
+        let updated = service
+            .update_profile(
+                created.id,
+                UpdateProfileDTO {
+                    username: Some("alice2".into()),
+                    ..update_dto()
+                },
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(updated.username, "alice2");
+        assert_eq!(updated.display_name.as_deref(), Some("Alice"));
+    }
+
+    #[tokio::test]
+    async fn update_profile_clears_null_fields() {
+        let service = service();
+        let created = service
+            .create_profile(CreateProfileDTO {
+                display_name: Some("Alice".into()),
+                ..create_dto("alice")
+            })
+            .await
+            .unwrap();
+
+        let updated = service
+            .update_profile(
+                created.id,
+                UpdateProfileDTO {
+                    display_name: Patch::Null,
+                    ..update_dto()
+                },
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(updated.display_name, None);
+        assert_eq!(updated.username, "alice");
+        assert!(updated.updated_at >= created.updated_at);
+    }
+
+    #[tokio::test]
+    async fn update_profile_validates_fields() {
+        let service = service();
+        let created = service.create_profile(create_dto("alice")).await.unwrap();
+
+        let err = service
+            .update_profile(
+                created.id,
+                UpdateProfileDTO {
+                    display_name: Patch::Value("x".repeat(33)),
+                    ..update_dto()
+                },
+            )
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err.code_name, ErrorCode::UserInputValidationError));
+        assert_eq!(err.field.as_deref(), Some("display_name"));
+    }
+
+    #[tokio::test]
+    async fn update_profile_rejects_taken_username() {
+        let service = service();
+        service.create_profile(create_dto("alice")).await.unwrap();
+        let bob = service.create_profile(create_dto("bob")).await.unwrap();
+
+        let err = service
+            .update_profile(
+                bob.id,
+                UpdateProfileDTO {
+                    username: Some("alice".into()),
+                    ..update_dto()
+                },
+            )
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err.code_name, ErrorCode::UserInputValidationError));
+    }
+
+    #[tokio::test]
+    async fn update_profile_not_found() {
+        let err = service()
+            .update_profile(42, update_dto())
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            err.code_name,
+            ErrorCode::SearchObjectNotFoundError
+        ));
+    }
+
+    #[tokio::test]
+    async fn delete_profile_hides_profile_and_frees_username() {
+        let service = service();
+        let created = service.create_profile(create_dto("alice")).await.unwrap();
+
+        service.delete_profile(created.id).await.unwrap();
+
+        let err = service.get_one_by_id(created.id).await.unwrap_err();
+        assert!(matches!(
+            err.code_name,
+            ErrorCode::SearchObjectNotFoundError
+        ));
+        assert!(service.get_all().await.unwrap().is_empty());
+
+        service.create_profile(create_dto("alice")).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn delete_profile_not_found() {
+        let service = service();
+        let created = service.create_profile(create_dto("alice")).await.unwrap();
+        service.delete_profile(created.id).await.unwrap();
+
+        let err = service.delete_profile(created.id).await.unwrap_err();
+        assert!(matches!(
+            err.code_name,
+            ErrorCode::SearchObjectNotFoundError
+        ));
+
+        let err = service.delete_profile(42).await.unwrap_err();
+        assert!(matches!(
+            err.code_name,
+            ErrorCode::SearchObjectNotFoundError
+        ));
+    }
+
+    #[tokio::test]
+    async fn restore_profile_brings_back_original_username() {
+        let service = service();
+        let created = service.create_profile(create_dto("alice")).await.unwrap();
+        service.delete_profile(created.id).await.unwrap();
+
+        let deleted = service.get_deleted_profiles(30).await.unwrap();
+        assert_eq!(deleted.len(), 1);
+        assert_eq!(deleted[0].username.as_deref(), Some("alice"));
+        assert!(deleted[0].purge_at.is_some());
+
+        let restored = service
+            .restore_profile(created.id, RestoreProfileDTO::default())
+            .await
+            .unwrap();
+        assert_eq!(restored.username, "alice");
+        assert!(service.get_deleted_profiles(30).await.unwrap().is_empty());
+        service.get_one_by_username("alice").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn restore_profile_rejects_taken_username() {
+        let service = service();
+        let created = service.create_profile(create_dto("alice")).await.unwrap();
+        service.delete_profile(created.id).await.unwrap();
+        service.create_profile(create_dto("alice")).await.unwrap();
+
+        let err = service
+            .restore_profile(created.id, RestoreProfileDTO::default())
+            .await
+            .unwrap_err();
+        assert!(matches!(err.code_name, ErrorCode::UserInputValidationError));
+        assert_eq!(err.field.as_deref(), Some("username"));
+
+        let restored = service
+            .restore_profile(
+                created.id,
+                RestoreProfileDTO {
+                    username: Some("alice2".into()),
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(restored.username, "alice2");
+    }
+
+    #[tokio::test]
+    async fn purge_profile_only_erases_trashed_profiles() {
+        let service = service();
+        let created = service.create_profile(create_dto("alice")).await.unwrap();
+
+        let err = service.purge_profile(created.id).await.unwrap_err();
+        assert!(matches!(
+            err.code_name,
+            ErrorCode::SearchObjectNotFoundError
+        ));
+
+        service.delete_profile(created.id).await.unwrap();
+        service.purge_profile(created.id).await.unwrap();
+
+        assert!(service.get_deleted_profiles(0).await.unwrap().is_empty());
+        let err = service
+            .restore_profile(created.id, RestoreProfileDTO::default())
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.code_name,
+            ErrorCode::SearchObjectNotFoundError
+        ));
+    }
+
+    #[tokio::test]
+    async fn purge_expired_respects_retention() {
+        let service = service();
+        let created = service.create_profile(create_dto("alice")).await.unwrap();
+        service.delete_profile(created.id).await.unwrap();
+
+        assert_eq!(service.purge_expired(0).await.unwrap(), 0);
+        assert_eq!(service.purge_expired(30).await.unwrap(), 0);
+
+        let later = Utc::now().naive_utc() + Duration::seconds(1);
+        assert_eq!(service.purge_deleted_before(later).await.unwrap(), 1);
+        assert!(service.get_deleted_profiles(30).await.unwrap().is_empty());
+    }
+
+    fn pin(secret: &str) -> SetProfileSecretDTO {
+        SetProfileSecretDTO {
+            kind: ProfileSecretKind::Pin,
+            secret: secret.into(),
+        }
+    }
+
+    fn unlock_dto(secret: &str) -> UnlockProfileDTO {
+        UnlockProfileDTO {
+            secret: secret.into(),
+        }
+    }
+
+    #[tokio::test]
+    async fn locked_profile_rejects_scoped_operations() {
+        let service = service();
+        let created = service.create_profile(create_dto("alice")).await.unwrap();
+        service
+            .set_profile_secret(created.id, Some(pin("1234")))
+            .await
+            .unwrap();
+        service.lock_profile(created.id);
+
+        let err = service.get_one_by_id(created.id).await.unwrap_err();
+        assert!(matches!(
+            err.code_name,
+            ErrorCode::InsufficientPrivilegesError
+        ));
+        let err = service
+            .update_profile(created.id, update_dto())
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.code_name,
+            ErrorCode::InsufficientPrivilegesError
+        ));
+        let err = service.delete_profile(created.id).await.unwrap_err();
+        assert!(matches!(
+            err.code_name,
+            ErrorCode::InsufficientPrivilegesError
+        ));
+
+        let listed = service.get_all().await.unwrap();
+        assert!(listed[0].locked);
+        assert_eq!(listed[0].secret_kind, Some(ProfileSecretKind::Pin));
+    }
+
+    #[tokio::test]
+    async fn unlock_profile_checks_secret() {
+        let service = service();
+        let created = service.create_profile(create_dto("alice")).await.unwrap();
+        service
+            .set_profile_secret(created.id, Some(pin("1234")))
+            .await
+            .unwrap();
+        service.lock_profile(created.id);
+
+        let err = service
+            .unlock_profile(created.id, unlock_dto("4321"))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.code_name,
+            ErrorCode::InsufficientPrivilegesError
+        ));
+
+        let unlocked = service
+            .unlock_profile(created.id, unlock_dto("1234"))
+            .await
+            .unwrap();
+        assert!(!unlocked.locked);
+        service
+            .update_profile(created.id, update_dto())
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn unlock_profile_rate_limits_failures() {
+        let service = service();
+        let created = service.create_profile(create_dto("alice")).await.unwrap();
+        service
+            .set_profile_secret(created.id, Some(pin("1234")))
+            .await
+            .unwrap();
+        service.lock_profile(created.id);
+
+        for _ in 0..FREE_UNLOCK_ATTEMPTS - 1 {
+            let err = service
+                .unlock_profile(created.id, unlock_dto("0000"))
+                .await
+                .unwrap_err();
+            assert!(matches!(
+                err.code_name,
+                ErrorCode::InsufficientPrivilegesError
+            ));
+        }
+
+        let err = service
+            .unlock_profile(created.id, unlock_dto("0000"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err.code_name, ErrorCode::TooManyAttemptsError));
+
+        let err = service
+            .unlock_profile(created.id, unlock_dto("1234"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err.code_name, ErrorCode::TooManyAttemptsError));
+    }
+
+    #[tokio::test]
+    async fn unlock_profile_counts_concurrent_failures() {
+        let service = service();
+        let created = service.create_profile(create_dto("alice")).await.unwrap();
+        service
+            .set_profile_secret(created.id, Some(pin("1234")))
+            .await
+            .unwrap();
+        service.lock_profile(created.id);
+
+        let mut attempts = tokio::task::JoinSet::new();
+        for _ in 0..FREE_UNLOCK_ATTEMPTS * 2 {
+            let service = service.clone();
+            attempts.spawn(async move {
+                service
+                    .unlock_profile(created.id, unlock_dto("0000"))
+                    .await
+                    .unwrap_err()
+            });
+        }
+
+        let mut wrong_secret = 0;
+        while let Some(err) = attempts.join_next().await {
+            match err.unwrap().code_name {
+                ErrorCode::InsufficientPrivilegesError => wrong_secret += 1,
+                ErrorCode::TooManyAttemptsError => {}
+                other => panic!("unexpected {other:?}"),
+            }
+        }
+        assert_eq!(wrong_secret, FREE_UNLOCK_ATTEMPTS - 1);
+
+        let err = service
+            .unlock_profile(created.id, unlock_dto("1234"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err.code_name, ErrorCode::TooManyAttemptsError));
+    }
+
+    #[tokio::test]
+    async fn set_profile_secret_validates_format() {
+        let service = service();
+        let created = service.create_profile(create_dto("alice")).await.unwrap();
+
+        let err = service
+            .set_profile_secret(created.id, Some(pin("12ab")))
+            .await
+            .unwrap_err();
 
-        Ok(dto)
+        assert!(matches!(err.code_name, ErrorCode::UserInputValidationError));
+        assert_eq!(err.field.as_deref(), Some("secret"));
     }
 }
@@ -0,0 +1,570 @@
+use crate::{
+    repositories::v1::report_repository::ReportRepository, services::forecast_service::Cents,
+    utils::error::mapping::ErrorResponse,
+};
+use chrono::{Datelike, Days, Months, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoricalTransaction {
+    pub id: i32,
+    pub account_id: i32,
+    pub category_id: Option<i32>,
+    pub payee: String,
+    pub amount: Cents,
+    pub date: NaiveDate,
+    pub tag_ids: Vec<i32>,
+}
+
+/// Which transactions a report covers, out of the history of the active profile. Empty lists do
+/// not restrict anything.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportFilterDTO {
+    #[serde(default)]
+    pub account_ids: Vec<i32>,
+    #[serde(default)]
+    pub tag_ids: Vec<i32>, // A transaction matches when it carries any of them.
+    pub from: Option<NaiveDate>,
+    pub until: Option<NaiveDate>, // Exclusive.
+}
+
+impl ReportFilterDTO {
+    fn matches(&self, transaction: &HistoricalTransaction) -> bool {
+        (self.account_ids.is_empty() || self.account_ids.contains(&transaction.account_id))
+            && (self.tag_ids.is_empty()
+                || transaction
+                    .tag_ids
+                    .iter()
+                    .any(|tag_id| self.tag_ids.contains(tag_id)))
+            && self.from.is_none_or(|from| transaction.date >= from)
+            && self.until.is_none_or(|until| transaction.date < until)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ReportInterval {
+    Week,
+    Month,
+    Year,
+}
+
+impl ReportInterval {
+    /// First day of the period containing `date`. Weeks start on Monday.
+    fn start_of(self, date: NaiveDate) -> NaiveDate {
+        match self {
+            ReportInterval::Week => date - Days::new(date.weekday().num_days_from_monday() as u64),
+            ReportInterval::Month => date.with_day(1).unwrap_or(date),
+            ReportInterval::Year => date.with_ordinal(1).unwrap_or(date),
+        }
+    }
+
+    fn next(self, start: NaiveDate) -> Option<NaiveDate> {
+        match self {
+            ReportInterval::Week => start.checked_add_days(Days::new(7)),
+            ReportInterval::Month => start.checked_add_months(Months::new(1)),
+            ReportInterval::Year => start.checked_add_months(Months::new(12)),
+        }
+    }
+
+    /// Every period from the one containing `first` to the one containing `last`, so series have
+    /// a point for periods without transactions.
+    fn periods(self, first: NaiveDate, last: NaiveDate) -> Vec<NaiveDate> {
+        let last = self.start_of(last);
+
+        std::iter::successors(Some(self.start_of(first)), |start| self.next(*start))
+            .take_while(|start| *start <= last)
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CategorySpending {
+    pub category_id: Option<i32>, // None collects uncategorised spending.
+    pub total: Cents,
+    pub share_percent: f64,
+    pub transactions: usize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CategorySpendingReport {
+    pub total: Cents,
+    pub categories: Vec<CategorySpending>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MonthlyCashFlow {
+    pub month: NaiveDate,
+    pub income: Cents,
+    pub expense: Cents,
+    pub net: Cents,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IncomeExpenseReport {
+    pub income: Cents,
+    pub expense: Cents,
+    pub net: Cents,
+    pub months: Vec<MonthlyCashFlow>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PayeeSpending {
+    pub payee: String, // Spelling of the latest transaction.
+    pub total: Cents,
+    pub transactions: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CategorySeries {
+    pub category_id: Option<i32>,
+    pub total: Cents,
+    pub totals: Vec<Cents>, // One per entry of `periods`.
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CategoryTrendReport {
+    pub periods: Vec<NaiveDate>,
+    pub series: Vec<CategorySeries>,
+}
+
+/// Reports over the history of one profile. The aggregation itself is left to the repository.
+pub struct ReportService {
+    repo: Arc<dyn ReportRepository>,
+}
+
+impl ReportService {
+    pub fn new(repo: Arc<dyn ReportRepository>) -> Self {
+        Self { repo }
+    }
+
+    pub async fn spending_by_category(
+        &self,
+        profile_id: i32,
+        filter: &ReportFilterDTO,
+    ) -> Result<CategorySpendingReport, ErrorResponse> {
+        self.repo.spending_by_category(profile_id, filter).await
+    }
+
+    pub async fn income_vs_expense(
+        &self,
+        profile_id: i32,
+        filter: &ReportFilterDTO,
+    ) -> Result<IncomeExpenseReport, ErrorResponse> {
+        self.repo.income_vs_expense(profile_id, filter).await
+    }
+
+    pub async fn top_payees(
+        &self,
+        profile_id: i32,
+        filter: &ReportFilterDTO,
+        limit: usize,
+    ) -> Result<Vec<PayeeSpending>, ErrorResponse> {
+        self.repo.top_payees(profile_id, filter, limit).await
+    }
+
+    pub async fn category_trends(
+        &self,
+        profile_id: i32,
+        filter: &ReportFilterDTO,
+        interval: ReportInterval,
+    ) -> Result<CategoryTrendReport, ErrorResponse> {
+        self.repo
+            .category_trends(profile_id, filter, interval)
+            .await
+    }
+}
+
+/// Folds case and runs of whitespace, so the spellings a bank uses for one payee group together.
+pub fn normalize_payee(payee: &str) -> String {
+    payee
+        .split_whitespace()
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// Reports show spending as positive amounts; income is left out.
+fn spending<'a>(
+    history: &'a [HistoricalTransaction],
+    filter: &'a ReportFilterDTO,
+) -> impl Iterator<Item = &'a HistoricalTransaction> {
+    history
+        .iter()
+        .filter(move |transaction| transaction.amount < 0 && filter.matches(transaction))
+}
+
+/// Spending per category, largest first, with each category's share of the total.
+pub fn spending_by_category(
+    history: &[HistoricalTransaction],
+    filter: &ReportFilterDTO,
+) -> CategorySpendingReport {
+    let mut by_category: HashMap<Option<i32>, (Cents, usize)> = HashMap::new();
+
+    for transaction in spending(history, filter) {
+        let (total, count) = by_category.entry(transaction.category_id).or_default();
+        *total -= transaction.amount;
+        *count += 1;
+    }
+
+    let total: Cents = by_category.values().map(|(spent, _)| spent).sum();
+
+    let mut categories: Vec<CategorySpending> = by_category
+        .into_iter()
+        .map(|(category_id, (spent, transactions))| CategorySpending {
+            category_id,
+            total: spent,
+            share_percent: spent as f64 / total as f64 * 100.0,
+            transactions,
+        })
+        .collect();
+
+    categories.sort_by(|a, b| {
+        b.total
+            .cmp(&a.total)
+            .then_with(|| a.category_id.cmp(&b.category_id))
+    });
+
+    CategorySpendingReport { total, categories }
+}
+
+/// Income and spending per calendar month, from the first month with a matching transaction to the
+/// last, including the quiet months in between.
+pub fn income_vs_expense(
+    history: &[HistoricalTransaction],
+    filter: &ReportFilterDTO,
+) -> IncomeExpenseReport {
+    let mut by_month: BTreeMap<NaiveDate, (Cents, Cents)> = BTreeMap::new();
+
+    for transaction in history.iter().filter(|t| filter.matches(t)) {
+        let (income, expense) = by_month
+            .entry(ReportInterval::Month.start_of(transaction.date))
+            .or_default();
+
+        if transaction.amount >= 0 {
+            *income += transaction.amount;
+        } else {
+            *expense -= transaction.amount;
+        }
+    }
+
+    let (Some(first), Some(last)) = (
+        by_month.keys().next().copied(),
+        by_month.keys().next_back().copied(),
+    ) else {
+        return IncomeExpenseReport::default();
+    };
+
+    let months: Vec<MonthlyCashFlow> = ReportInterval::Month
+        .periods(first, last)
+        .into_iter()
+        .map(|month| {
+            let (income, expense) = by_month.get(&month).copied().unwrap_or_default();
+
+            MonthlyCashFlow {
+                month,
+                income,
+                expense,
+                net: income - expense,
+            }
+        })
+        .collect();
+
+    let income = months.iter().map(|month| month.income).sum();
+    let expense = months.iter().map(|month| month.expense).sum();
+
+    IncomeExpenseReport {
+        income,
+        expense,
+        net: income - expense,
+        months,
+    }
+}
+
+/// The `limit` payees the profile spent the most with. Spellings that differ only in case or
+/// spacing count as one payee.
+pub fn top_payees(
+    history: &[HistoricalTransaction],
+    filter: &ReportFilterDTO,
+    limit: usize,
+) -> Vec<PayeeSpending> {
+    let mut by_payee: HashMap<String, (&HistoricalTransaction, Cents, usize)> = HashMap::new();
+
+    for transaction in spending(history, filter) {
+        let (latest, total, count) = by_payee
+            .entry(normalize_payee(&transaction.payee))
+            .or_insert((transaction, 0, 0));
+
+        if transaction.date >= latest.date {
+            *latest = transaction;
+        }
+        *total -= transaction.amount;
+        *count += 1;
+    }
+
+    let mut payees: Vec<PayeeSpending> = by_payee
+        .into_values()
+        .map(|(latest, total, transactions)| PayeeSpending {
+            payee: latest.payee.trim().to_string(),
+            total,
+            transactions,
+        })
+        .collect();
+
+    payees.sort_by(|a, b| b.total.cmp(&a.total).then_with(|| a.payee.cmp(&b.payee)));
+    payees.truncate(limit);
+    payees
+}
+
+/// Spending per category and period. Every series has one total per entry of `periods`, so the
+/// series can be charted against the same axis.
+pub fn category_trends(
+    history: &[HistoricalTransaction],
+    filter: &ReportFilterDTO,
+    interval: ReportInterval,
+) -> CategoryTrendReport {
+    let transactions: Vec<&HistoricalTransaction> = spending(history, filter).collect();
+
+    let (Some(first), Some(last)) = (
+        transactions.iter().map(|t| t.date).min(),
+        transactions.iter().map(|t| t.date).max(),
+    ) else {
+        return CategoryTrendReport::default();
+    };
+
+    let periods = interval.periods(first, last);
+    let mut by_category: BTreeMap<Option<i32>, Vec<Cents>> = BTreeMap::new();
+
+    for transaction in transactions {
+        let start = interval.start_of(transaction.date);
+        let Ok(index) = periods.binary_search(&start) else {
+            continue;
+        };
+
+        by_category
+            .entry(transaction.category_id)
+            .or_insert_with(|| vec![0; periods.len()])[index] -= transaction.amount;
+    }
+
+    let mut series: Vec<CategorySeries> = by_category
+        .into_iter()
+        .map(|(category_id, totals)| CategorySeries {
+            category_id,
+            total: totals.iter().sum(),
+            totals,
+        })
+        .collect();
+
+    series.sort_by_key(|series| Reverse(series.total));
+
+    CategoryTrendReport { periods, series }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repositories::memory::report_repository::InMemoryReportRepository;
+
+    fn date(value: &str) -> NaiveDate {
+        value.parse().unwrap()
+    }
+
+    fn transaction(
+        account_id: i32,
+        category_id: Option<i32>,
+        payee: &str,
+        on: &str,
+        amount: Cents,
+    ) -> HistoricalTransaction {
+        HistoricalTransaction {
+            id: 0,
+            account_id,
+            category_id,
+            payee: payee.into(),
+            amount,
+            date: date(on),
+            tag_ids: Vec::new(),
+        }
+    }
+
+    fn history() -> Vec<HistoricalTransaction> {
+        vec![
+            transaction(1, None, "Employer", "2025-01-01", 300_000),
+            transaction(1, Some(1), "Market", "2025-01-03", -12_000),
+            transaction(1, Some(2), "Power Co", "2025-01-10", -8_000),
+            transaction(2, Some(1), " market ", "2025-03-14", -6_000),
+            transaction(1, None, "Kiosk", "2025-03-20", -2_000),
+            transaction(1, None, "Employer", "2025-03-01", 300_000),
+        ]
+    }
+
+    #[test]
+    fn spending_is_split_by_category() {
+        let report = spending_by_category(&history(), &ReportFilterDTO::default());
+
+        assert_eq!(report.total, 28_000);
+        let totals: Vec<_> = report
+            .categories
+            .iter()
+            .map(|category| (category.category_id, category.total, category.transactions))
+            .collect();
+        assert_eq!(
+            totals,
+            [(Some(1), 18_000, 2), (Some(2), 8_000, 1), (None, 2_000, 1)]
+        );
+        let shares: f64 = report.categories.iter().map(|c| c.share_percent).sum();
+        assert!((shares - 100.0).abs() < 1e-9);
+
+        let empty = spending_by_category(&[], &ReportFilterDTO::default());
+        assert_eq!(empty, CategorySpendingReport::default());
+    }
+
+    #[test]
+    fn income_and_expense_cover_every_month() {
+        let report = income_vs_expense(&history(), &ReportFilterDTO::default());
+
+        let months: Vec<_> = report
+            .months
+            .iter()
+            .map(|month| (month.month, month.income, month.expense, month.net))
+            .collect();
+        assert_eq!(
+            months,
+            [
+                (date("2025-01-01"), 300_000, 20_000, 280_000),
+                (date("2025-02-01"), 0, 0, 0),
+                (date("2025-03-01"), 300_000, 8_000, 292_000),
+            ]
+        );
+        assert_eq!(report.income, 600_000);
+        assert_eq!(report.expense, 28_000);
+        assert_eq!(report.net, 572_000);
+    }
+
+    #[test]
+    fn top_payees_merge_spellings() {
+        let payees = top_payees(&history(), &ReportFilterDTO::default(), 2);
+
+        assert_eq!(
+            payees,
+            [
+                PayeeSpending {
+                    payee: "market".into(),
+                    total: 18_000,
+                    transactions: 2,
+                },
+                PayeeSpending {
+                    payee: "Power Co".into(),
+                    total: 8_000,
+                    transactions: 1,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn category_trends_share_one_axis() {
+        let report = category_trends(
+            &history(),
+            &ReportFilterDTO::default(),
+            ReportInterval::Month,
+        );
+
+        assert_eq!(
+            report.periods,
+            [date("2025-01-01"), date("2025-02-01"), date("2025-03-01")]
+        );
+        let series: Vec<_> = report
+            .series
+            .iter()
+            .map(|series| (series.category_id, series.totals.clone()))
+            .collect();
+        assert_eq!(
+            series,
+            [
+                (Some(1), vec![12_000, 0, 6_000]),
+                (Some(2), vec![8_000, 0, 0]),
+                (None, vec![0, 0, 2_000]),
+            ]
+        );
+
+        // 2025-01-10 is a Friday; its week starts on Monday the 6th.
+        let weekly = category_trends(
+            &history()[..3],
+            &ReportFilterDTO::default(),
+            ReportInterval::Week,
+        );
+        assert_eq!(weekly.periods, [date("2024-12-30"), date("2025-01-06")]);
+    }
+
+    #[test]
+    fn filters_narrow_every_report() {
+        let mut history = history();
+        history[2].tag_ids = vec![5];
+        history[4].tag_ids = vec![6, 7];
+
+        let by_account = ReportFilterDTO {
+            account_ids: vec![2],
+            ..Default::default()
+        };
+        assert_eq!(spending_by_category(&history, &by_account).total, 6_000);
+
+        let by_tag = ReportFilterDTO {
+            tag_ids: vec![5, 7],
+            ..Default::default()
+        };
+        assert_eq!(top_payees(&history, &by_tag, 10).len(), 2);
+
+        // `until` is exclusive.
+        let by_date = ReportFilterDTO {
+            from: Some(date("2025-01-03")),
+            until: Some(date("2025-03-14")),
+            ..Default::default()
+        };
+        let report = income_vs_expense(&history, &by_date);
+        assert_eq!(report.income, 300_000);
+        assert_eq!(report.expense, 20_000);
+    }
+
+    #[tokio::test]
+    async fn service_reports_only_the_given_profile() {
+        let repo = Arc::new(InMemoryReportRepository::new());
+        for transaction in history() {
+            repo.insert(1, transaction);
+        }
+        repo.insert(2, transaction(3, Some(1), "Market", "2025-02-01", -50_000));
+        let service = ReportService::new(repo);
+        let filter = ReportFilterDTO::default();
+
+        let spending = service.spending_by_category(1, &filter).await.unwrap();
+        assert_eq!(spending.total, 28_000);
+
+        let cash_flow = service.income_vs_expense(2, &filter).await.unwrap();
+        assert_eq!((cash_flow.income, cash_flow.expense), (0, 50_000));
+        assert_eq!(cash_flow.months.len(), 1);
+
+        let payees = service.top_payees(1, &filter, 1).await.unwrap();
+        assert_eq!(payees[0].payee, "market");
+        assert_eq!(payees[0].total, 18_000);
+
+        let trends = service
+            .category_trends(3, &filter, ReportInterval::Month)
+            .await
+            .unwrap();
+        assert_eq!(trends, CategoryTrendReport::default());
+    }
+}
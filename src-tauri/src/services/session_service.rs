@@ -0,0 +1,318 @@
+use crate::{
+    services::{
+        dto::{
+            profile_dto::{GetProfileDTO, UnlockProfileDTO},
+            session_dto::SessionDTO,
+        },
+        profile_service::ProfileService,
+        settings_service::SettingsService,
+    },
+    utils::error::mapping::{ErrorCode, ErrorResponse},
+};
+use std::{
+    sync::{Arc, Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+use tokio::sync::watch;
+
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(15);
+
+struct Session {
+    profile_id: i32,
+    unlocked_at: chrono::NaiveDateTime,
+    last_activity: Instant,
+    locked: bool,
+}
+
+/// Tracks which profile the window is acting as.
+///
+/// Profile-scoped commands ask [`SessionService::active_profile_id`] for the profile instead of
+/// taking an id from the frontend. Each such call counts as activity; once the session has been
+/// idle for `session_idle_minutes` it locks and has to be selected again.
+#[derive(Clone)]
+pub struct SessionService {
+    profiles: ProfileService,
+    settings: SettingsService,
+    session: Arc<Mutex<Option<Session>>>,
+    status: Arc<watch::Sender<SessionDTO>>,
+}
+
+impl SessionService {
+    pub fn new(profiles: ProfileService, settings: SettingsService) -> Self {
+        let (status, _) = watch::channel(SessionDTO {
+            profile_id: None,
+            locked: false,
+            unlocked_at: None,
+        });
+
+        Self {
+            profiles,
+            settings,
+            session: Arc::default(),
+            status: Arc::new(status),
+        }
+    }
+
+    pub fn status(&self) -> SessionDTO {
+        self.status.borrow().clone()
+    }
+
+    pub fn subscribe(&self) -> watch::Receiver<SessionDTO> {
+        self.status.subscribe()
+    }
+
+    /// Makes `id` the active profile. Profiles with a secret need it here, even if they were
+    /// active before the session locked.
+    pub async fn select_profile(
+        &self,
+        id: i32,
+        secret: Option<String>,
+    ) -> Result<GetProfileDTO, ErrorResponse> {
+        let profile = match secret {
+            Some(secret) => {
+                self.profiles
+                    .unlock_profile(id, UnlockProfileDTO { secret })
+                    .await?
+            }
+            None => self.profiles.get_one_by_id(id).await?,
+        };
+
+        let previous = self.lock_session().replace(Session {
+            profile_id: id,
+            unlocked_at: chrono::Utc::now().naive_utc(),
+            last_activity: Instant::now(),
+            locked: false,
+        });
+
+        if let Some(previous) = previous.filter(|previous| previous.profile_id != id) {
+            self.profiles.lock_profile(previous.profile_id);
+        }
+
+        self.publish();
+        Ok(profile)
+    }
+
+    pub fn leave_profile(&self) {
+        let previous = self.lock_session().take();
+
+        if let Some(previous) = previous {
+            self.profiles.lock_profile(previous.profile_id);
+        }
+
+        self.publish();
+    }
+
+    /// The selected profile, provided the session is not locked. Counts as activity.
+    pub fn active_profile_id(&self) -> Result<i32, ErrorResponse> {
+        let mut session = self.lock_session();
+
+        match session.as_mut() {
+            None => Err(ErrorResponse::keyed(
+                ErrorCode::InsufficientPrivilegesError,
+                None,
+                "session-no-profile",
+            )),
+            Some(session) if session.locked => Err(ErrorResponse::keyed(
+                ErrorCode::InsufficientPrivilegesError,
+                None,
+                "session-locked",
+            )),
+            Some(session) => {
+                session.last_activity = Instant::now();
+                Ok(session.profile_id)
+            }
+        }
+    }
+
+    /// Locks the session if nothing used it for the configured idle time. Returns whether it did.
+    pub fn lock_if_idle(&self, now: Instant) -> bool {
+        let idle_minutes = self.settings.current().session_idle_minutes;
+        if idle_minutes == 0 {
+            return false;
+        }
+
+        let timeout = Duration::from_secs(u64::from(idle_minutes) * 60);
+        let profile_id = {
+            let mut session = self.lock_session();
+            let Some(session) = session.as_mut() else {
+                return false;
+            };
+
+            if session.locked || now.saturating_duration_since(session.last_activity) < timeout {
+                return false;
+            }
+
+            session.locked = true;
+            session.profile_id
+        };
+
+        self.profiles.lock_profile(profile_id);
+        self.publish();
+        true
+    }
+
+    pub async fn supervise_idle(self) {
+        loop {
+            tokio::time::sleep(IDLE_CHECK_INTERVAL).await;
+
+            if self.lock_if_idle(Instant::now()) {
+                log::info!("Locked the session after inactivity");
+            }
+        }
+    }
+
+    fn lock_session(&self) -> MutexGuard<'_, Option<Session>> {
+        self.session
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn publish(&self) {
+        let status = match self.lock_session().as_ref() {
+            Some(session) => SessionDTO {
+                profile_id: Some(session.profile_id),
+                locked: session.locked,
+                unlocked_at: Some(session.unlocked_at),
+            },
+            None => SessionDTO {
+                profile_id: None,
+                locked: false,
+                unlocked_at: None,
+            },
+        };
+
+        self.status.send_if_modified(|current| {
+            let changed = *current != status;
+            *current = status;
+            changed
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        repositories::memory::profile_repository::InMemoryProfileRepository,
+        services::dto::profile_dto::{CreateProfileDTO, ProfileSecretKind, SetProfileSecretDTO},
+    };
+
+    fn service() -> SessionService {
+        let dir =
+            std::env::temp_dir().join(format!("nomorebeans-session-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let settings = SettingsService::load_from(dir.join("settings.toml")).unwrap();
+        let profiles = ProfileService::new(Arc::new(InMemoryProfileRepository::new()));
+
+        SessionService::new(profiles, settings)
+    }
+
+    async fn create_profile(service: &SessionService, username: &str, pin: Option<&str>) -> i32 {
+        let profile = service
+            .profiles
+            .create_profile(CreateProfileDTO {
+                username: username.into(),
+                display_name: None,
+                profile_picture_bytes: None,
+                profile_picture_crop: None,
+            })
+            .await
+            .unwrap();
+
+        if let Some(pin) = pin {
+            let secret = SetProfileSecretDTO {
+                kind: ProfileSecretKind::Pin,
+                secret: pin.into(),
+            };
+            service
+                .profiles
+                .set_profile_secret(profile.id, Some(secret))
+                .await
+                .unwrap();
+            service.profiles.lock_profile(profile.id);
+        }
+
+        profile.id
+    }
+
+    #[tokio::test]
+    async fn active_profile_requires_selection() {
+        let service = service();
+        let id = create_profile(&service, "alice", None).await;
+
+        let err = service.active_profile_id().unwrap_err();
+        assert!(matches!(
+            err.code_name,
+            ErrorCode::InsufficientPrivilegesError
+        ));
+
+        service.select_profile(id, None).await.unwrap();
+        assert_eq!(service.active_profile_id().unwrap(), id);
+        assert_eq!(service.status().profile_id, Some(id));
+
+        service.leave_profile();
+        assert!(service.active_profile_id().is_err());
+        assert_eq!(service.status().profile_id, None);
+    }
+
+    #[tokio::test]
+    async fn select_profile_requires_secret_of_locked_profile() {
+        let service = service();
+        let id = create_profile(&service, "alice", Some("1234")).await;
+
+        let err = service.select_profile(id, None).await.unwrap_err();
+        assert!(matches!(
+            err.code_name,
+            ErrorCode::InsufficientPrivilegesError
+        ));
+
+        service
+            .select_profile(id, Some("1234".into()))
+            .await
+            .unwrap();
+        assert_eq!(service.active_profile_id().unwrap(), id);
+    }
+
+    #[tokio::test]
+    async fn switching_profiles_locks_the_previous_one() {
+        let service = service();
+        let alice = create_profile(&service, "alice", Some("1234")).await;
+        let bob = create_profile(&service, "bob", None).await;
+
+        service
+            .select_profile(alice, Some("1234".into()))
+            .await
+            .unwrap();
+        service.select_profile(bob, None).await.unwrap();
+
+        let err = service.select_profile(alice, None).await.unwrap_err();
+        assert!(matches!(
+            err.code_name,
+            ErrorCode::InsufficientPrivilegesError
+        ));
+    }
+
+    #[tokio::test]
+    async fn idle_session_locks() {
+        let service = service();
+        let id = create_profile(&service, "alice", Some("1234")).await;
+        service
+            .select_profile(id, Some("1234".into()))
+            .await
+            .unwrap();
+
+        assert!(!service.lock_if_idle(Instant::now()));
+
+        let later = Instant::now() + Duration::from_secs(60 * 60);
+        assert!(service.lock_if_idle(later));
+        assert!(service.status().locked);
+
+        let err = service.active_profile_id().unwrap_err();
+        assert!(matches!(
+            err.code_name,
+            ErrorCode::InsufficientPrivilegesError
+        ));
+        assert!(service.select_profile(id, None).await.is_err());
+    }
+}
@@ -0,0 +1,429 @@
+use crate::{
+    models::v1::settings_model::{LogLevel, SettingsModel, MAX_BACKUPS_KEPT},
+    services::dto::settings_dto::{GetSettingsDTO, SettingsDTO},
+    utils::{
+        db::pool::DbBackend,
+        error::mapping::{ErrorCode, ErrorResponse},
+        fs::settings,
+    },
+};
+use std::{
+    env,
+    path::PathBuf,
+    sync::{Arc, RwLock},
+};
+use validator::Validate;
+
+const ENV_PREFIX: &str = "NOMOREBEANS_";
+
+/// Reads and writes `settings.toml`.
+///
+/// Environment variables prefixed with `NOMOREBEANS_` (and the older `DATABASE_URL`) take
+/// precedence over the file so development setups keep working, but they are never written back.
+/// Database and data directory changes only apply after a restart; everything else applies at once.
+#[derive(Clone)]
+pub struct SettingsService {
+    path: PathBuf,
+    stored: Arc<RwLock<SettingsModel>>,
+    running: Arc<SettingsModel>,
+}
+
+impl SettingsService {
+    pub fn load() -> Result<Self, ErrorResponse> {
+        Self::load_from(settings::settings_path()?)
+    }
+
+    /// Creates the file with defaults when it does not exist, so users have something to edit.
+    pub fn load_from(path: PathBuf) -> Result<Self, ErrorResponse> {
+        let stored = match settings::read_settings_file(&path)? {
+            Some(contents) => {
+                let stored: SettingsModel = toml::from_str(&contents).map_err(|err| {
+                    ErrorResponse::keyed(ErrorCode::ExpectedError, None, "settings-file-unreadable")
+                        .with_arg("path", path.display().to_string())
+                        .with_arg("reason", err.message())
+                })?;
+
+                // The field errors keep their own messages; the summary names the file.
+                let dto = SettingsDTO::from(stored.clone());
+                dto.validate()
+                    .map_err(ErrorResponse::from)
+                    .and_then(|_| dto.validate_database_url())
+                    .map_err(|err| ErrorResponse {
+                        errors: err.errors,
+                        ..ErrorResponse::keyed(
+                            err.code_name,
+                            err.field.clone(),
+                            "settings-file-invalid",
+                        )
+                        .with_arg("path", path.display().to_string())
+                        .with_arg("field", err.field.unwrap_or_default())
+                    })?;
+
+                stored
+            }
+            None => {
+                let stored = SettingsModel::default();
+                write_settings(&path, &stored)?;
+                stored
+            }
+        };
+
+        let mut running = stored.clone();
+        apply_env_overrides(&mut running, |key| env::var(key).ok());
+
+        Ok(Self {
+            path,
+            stored: Arc::new(RwLock::new(stored)),
+            running: Arc::new(running),
+        })
+    }
+
+    /// Settings the app was started with, environment overrides included.
+    pub fn running(&self) -> &SettingsModel {
+        &self.running
+    }
+
+    /// Settings as they are now, environment overrides included.
+    pub fn current(&self) -> SettingsModel {
+        let mut current = self.stored();
+        apply_env_overrides(&mut current, |key| env::var(key).ok());
+        current
+    }
+
+    pub fn get_settings(&self) -> GetSettingsDTO {
+        let stored = self.stored();
+
+        let mut effective = stored.clone();
+        let overridden = apply_env_overrides(&mut effective, |key| env::var(key).ok());
+
+        GetSettingsDTO {
+            restart_required: effective.database != self.running.database
+                || effective.data_dir != self.running.data_dir,
+            settings: stored.into(),
+            overridden: overridden.into_iter().map(String::from).collect(),
+        }
+    }
+
+    pub fn update_settings(&self, settings: SettingsDTO) -> Result<GetSettingsDTO, ErrorResponse> {
+        settings.validate()?;
+        settings.validate_database_url()?;
+
+        let updated = SettingsModel::from(settings);
+        write_settings(&self.path, &updated)?;
+
+        *self
+            .stored
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = updated;
+
+        log::set_max_level(self.current().log_level.into());
+        Ok(self.get_settings())
+    }
+
+    fn stored(&self) -> SettingsModel {
+        self.stored
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+}
+
+fn write_settings(path: &std::path::Path, settings: &SettingsModel) -> Result<(), ErrorResponse> {
+    let contents = toml::to_string_pretty(settings).map_err(|_| ErrorResponse::unhandled())?;
+    settings::write_settings_file(path, &contents)?;
+    Ok(())
+}
+
+/// Applies every override `lookup` finds and returns the names of the settings fields it replaced.
+/// Values that do not parse are logged and ignored rather than stopping the app. So are all of
+/// them when together they do not make valid settings, since there is no telling which one is
+/// wrong, such as a Postgres backend with a SQLite URL.
+fn apply_env_overrides(
+    settings: &mut SettingsModel,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Vec<&'static str> {
+    let original = settings.clone();
+    let var = |name: &str| {
+        lookup(&format!("{ENV_PREFIX}{name}")).filter(|value| !value.trim().is_empty())
+    };
+    let mut overridden = Vec::new();
+
+    if let Some(url) = var("DATABASE_URL").or_else(|| lookup("DATABASE_URL")) {
+        settings.database.backend = DbBackend::from_url(&url);
+        settings.database.url = Some(url);
+        overridden.extend(["databaseBackend", "databaseUrl"]);
+    }
+
+    if let Some(backend) = var("DATABASE_BACKEND") {
+        match backend.parse::<DbBackend>() {
+            Ok(backend) => {
+                settings.database.backend = backend;
+                overridden.push("databaseBackend");
+            }
+            Err(err) => log::warn!("Ignoring {ENV_PREFIX}DATABASE_BACKEND: {err}"),
+        }
+    }
+
+    if let Some(pool_size) = var("DATABASE_POOL_SIZE") {
+        match pool_size.parse::<u32>() {
+            Ok(pool_size) if (1..=64).contains(&pool_size) => {
+                settings.database.pool_size = pool_size;
+                overridden.push("databasePoolSize");
+            }
+            _ => log::warn!("Ignoring {ENV_PREFIX}DATABASE_POOL_SIZE: expected 1 to 64"),
+        }
+    }
+
+    if let Some(log_level) = var("LOG_LEVEL") {
+        match log_level.parse::<LogLevel>() {
+            Ok(log_level) => {
+                settings.log_level = log_level;
+                overridden.push("logLevel");
+            }
+            Err(err) => log::warn!("Ignoring {ENV_PREFIX}LOG_LEVEL: {err}"),
+        }
+    }
+
+    if let Some(locale) = var("DEFAULT_LOCALE") {
+        settings.default_locale = locale;
+        overridden.push("defaultLocale");
+    }
+
+    if let Some(currency) = var("DEFAULT_CURRENCY") {
+        settings.default_currency = currency;
+        overridden.push("defaultCurrency");
+    }
+
+    if let Some(minutes) = var("SESSION_IDLE_MINUTES") {
+        match minutes.parse::<u32>() {
+            Ok(minutes) => {
+                settings.session_idle_minutes = minutes;
+                overridden.push("sessionIdleMinutes");
+            }
+            Err(_) => log::warn!("Ignoring {ENV_PREFIX}SESSION_IDLE_MINUTES: expected minutes"),
+        }
+    }
+
+    if let Some(days) = var("TRASH_RETENTION_DAYS") {
+        match days.parse::<u32>() {
+            Ok(days) if days <= 3650 => {
+                settings.trash_retention_days = days;
+                overridden.push("trashRetentionDays");
+            }
+            _ => log::warn!("Ignoring {ENV_PREFIX}TRASH_RETENTION_DAYS: expected 0 to 3650"),
+        }
+    }
+
+    let backups = &mut settings.backups;
+    for (name, field, keep) in [
+        ("BACKUP_KEEP_DAILY", "backupKeepDaily", &mut backups.daily),
+        (
+            "BACKUP_KEEP_WEEKLY",
+            "backupKeepWeekly",
+            &mut backups.weekly,
+        ),
+        (
+            "BACKUP_KEEP_MONTHLY",
+            "backupKeepMonthly",
+            &mut backups.monthly,
+        ),
+    ] {
+        if let Some(count) = var(name) {
+            match count.parse::<u32>() {
+                Ok(count) if count <= MAX_BACKUPS_KEPT => {
+                    *keep = count;
+                    overridden.push(field);
+                }
+                _ => log::warn!("Ignoring {ENV_PREFIX}{name}: expected 0 to {MAX_BACKUPS_KEPT}"),
+            }
+        }
+    }
+
+    if let Some(encrypted) = var("BACKUP_ENCRYPTED") {
+        match encrypted.parse::<bool>() {
+            Ok(encrypted) => {
+                settings.backups.encrypted = encrypted;
+                overridden.push("backupEncrypted");
+            }
+            Err(_) => log::warn!("Ignoring {ENV_PREFIX}BACKUP_ENCRYPTED: expected true or false"),
+        }
+    }
+
+    if let Some(data_dir) = var("DATA_DIR") {
+        settings.data_dir = Some(PathBuf::from(data_dir));
+        overridden.push("dataDir");
+    }
+
+    let merged = SettingsDTO::from(settings.clone());
+    if let Err(err) = merged
+        .validate()
+        .map_err(ErrorResponse::from)
+        .and_then(|_| merged.validate_database_url())
+    {
+        log::warn!("Ignoring the environment overrides: {}", err.message);
+        *settings = original;
+        return Vec::new();
+    }
+
+    overridden.sort_unstable();
+    overridden.dedup();
+    overridden
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn temp_settings_path() -> PathBuf {
+        let dir = env::temp_dir().join(format!("nomorebeans-settings-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.join("settings.toml")
+    }
+
+    fn settings_dto() -> SettingsDTO {
+        SettingsModel::default().into()
+    }
+
+    #[test]
+    fn load_creates_file_with_defaults() {
+        let path = temp_settings_path();
+
+        let service = SettingsService::load_from(path.clone()).unwrap();
+
+        assert!(path.exists());
+        assert_eq!(
+            SettingsModel::from(service.get_settings().settings),
+            SettingsModel::default()
+        );
+    }
+
+    #[test]
+    fn update_settings_persists_to_file() {
+        let path = temp_settings_path();
+        let service = SettingsService::load_from(path.clone()).unwrap();
+
+        service
+            .update_settings(SettingsDTO {
+                database_pool_size: 10,
+                default_currency: "BRL".into(),
+                ..settings_dto()
+            })
+            .unwrap();
+
+        let reloaded = SettingsService::load_from(path).unwrap().get_settings();
+        assert_eq!(reloaded.settings.database_pool_size, 10);
+        assert_eq!(reloaded.settings.default_currency, "BRL");
+    }
+
+    #[test]
+    fn update_settings_rejects_invalid_values() {
+        let service = SettingsService::load_from(temp_settings_path()).unwrap();
+
+        let err = service
+            .update_settings(SettingsDTO {
+                default_currency: "dollars".into(),
+                ..settings_dto()
+            })
+            .unwrap_err();
+        assert!(matches!(err.code_name, ErrorCode::UserInputValidationError));
+        assert_eq!(err.field.as_deref(), Some("default_currency"));
+
+        let err = service
+            .update_settings(SettingsDTO {
+                database_backend: DbBackend::Postgres,
+                ..settings_dto()
+            })
+            .unwrap_err();
+        assert_eq!(err.field.as_deref(), Some("database_url"));
+
+        let err = service
+            .update_settings(SettingsDTO {
+                data_dir: Some("beans".into()),
+                ..settings_dto()
+            })
+            .unwrap_err();
+        assert_eq!(err.field.as_deref(), Some("data_dir"));
+        assert_eq!(err.message, "Data directory must be an absolute path");
+    }
+
+    #[test]
+    fn load_rejects_malformed_file() {
+        let path = temp_settings_path();
+        std::fs::write(&path, "log_level = [").unwrap();
+
+        let err = SettingsService::load_from(path.clone()).err().unwrap();
+
+        assert!(matches!(err.code_name, ErrorCode::ExpectedError));
+        assert_eq!(err.message_id, Some("settings-file-unreadable"));
+        assert!(err.message.starts_with(&path.display().to_string()));
+    }
+
+    #[test]
+    fn load_rejects_invalid_values() {
+        let path = temp_settings_path();
+        SettingsService::load_from(path.clone()).unwrap();
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(
+            &path,
+            contents.replace(
+                r#"default_currency = "USD""#,
+                r#"default_currency = "dollars""#,
+            ),
+        )
+        .unwrap();
+
+        let err = SettingsService::load_from(path.clone()).err().unwrap();
+
+        assert!(matches!(err.code_name, ErrorCode::UserInputValidationError));
+        assert_eq!(err.field.as_deref(), Some("default_currency"));
+        assert_eq!(
+            err.message,
+            format!("{} has an invalid default_currency", path.display())
+        );
+        assert_eq!(err.errors.len(), 1);
+    }
+
+    #[test]
+    fn env_overrides_replace_file_values() {
+        let env = HashMap::from([
+            ("DATABASE_URL", "postgres://localhost/beans"),
+            ("NOMOREBEANS_LOG_LEVEL", "debug"),
+            ("NOMOREBEANS_DATABASE_POOL_SIZE", "0"),
+        ]);
+        let mut settings = SettingsModel::default();
+
+        let overridden =
+            apply_env_overrides(&mut settings, |key| env.get(key).map(|v| v.to_string()));
+
+        assert_eq!(settings.database.backend, DbBackend::Postgres);
+        assert_eq!(settings.log_level, LogLevel::Debug);
+        assert_eq!(
+            settings.database.pool_size,
+            SettingsModel::default().database.pool_size
+        );
+        assert_eq!(overridden, ["databaseBackend", "databaseUrl", "logLevel"]);
+    }
+
+    #[test]
+    fn env_overrides_are_ignored_when_they_make_settings_invalid() {
+        let env = HashMap::from([
+            ("NOMOREBEANS_DATABASE_BACKEND", "postgres"),
+            ("NOMOREBEANS_LOG_LEVEL", "debug"),
+        ]);
+        let mut settings = SettingsModel::default();
+
+        let overridden =
+            apply_env_overrides(&mut settings, |key| env.get(key).map(|v| v.to_string()));
+
+        assert_eq!(settings, SettingsModel::default());
+        assert!(overridden.is_empty());
+
+        let env = HashMap::from([("NOMOREBEANS_DEFAULT_CURRENCY", "dollars")]);
+        let overridden =
+            apply_env_overrides(&mut settings, |key| env.get(key).map(|v| v.to_string()));
+
+        assert_eq!(settings, SettingsModel::default());
+        assert!(overridden.is_empty());
+    }
+}
@@ -0,0 +1,453 @@
+use crate::{
+    services::{
+        forecast_service::{Cents, Frequency, ScheduledTransaction},
+        report_service::{normalize_payee, HistoricalTransaction},
+    },
+    utils::error::mapping::{ErrorCode, ErrorResponse},
+};
+use chrono::{Days, Months, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+const MIN_OCCURRENCES: usize = 3;
+const MAX_STEP_CHANGE_PERCENT: i64 = 15; // Largest change allowed between two consecutive charges.
+const MIN_REGULAR_INTERVALS_PERCENT: usize = 75;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SubscriptionPeriod {
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+impl SubscriptionPeriod {
+    fn classify(interval_days: i64) -> Option<Self> {
+        match interval_days {
+            6..=8 => Some(SubscriptionPeriod::Weekly),
+            27..=33 => Some(SubscriptionPeriod::Monthly),
+            355..=375 => Some(SubscriptionPeriod::Yearly),
+            _ => None,
+        }
+    }
+
+    fn tolerance_days(self) -> i64 {
+        match self {
+            SubscriptionPeriod::Weekly => 1,
+            SubscriptionPeriod::Monthly => 4,
+            SubscriptionPeriod::Yearly => 10,
+        }
+    }
+
+    fn charges_per_year(self) -> i64 {
+        match self {
+            SubscriptionPeriod::Weekly => 52,
+            SubscriptionPeriod::Monthly => 12,
+            SubscriptionPeriod::Yearly => 1,
+        }
+    }
+
+    /// Next charge after `last`, counted from `anchor` so a plan billed on the 31st returns to the
+    /// 31st after a short month.
+    fn next_after(self, anchor: NaiveDate, last: NaiveDate) -> Option<NaiveDate> {
+        (1u32..)
+            .map_while(|n| match self {
+                SubscriptionPeriod::Weekly => anchor.checked_add_days(Days::new(n as u64 * 7)),
+                SubscriptionPeriod::Monthly => anchor.checked_add_months(Months::new(n)),
+                SubscriptionPeriod::Yearly => anchor.checked_add_months(Months::new(n * 12)),
+            })
+            .find(|date| *date > last)
+    }
+}
+
+impl From<SubscriptionPeriod> for Frequency {
+    fn from(period: SubscriptionPeriod) -> Self {
+        match period {
+            SubscriptionPeriod::Weekly => Frequency::Weekly,
+            SubscriptionPeriod::Monthly => Frequency::Monthly,
+            SubscriptionPeriod::Yearly => Frequency::Yearly,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PriceIncrease {
+    pub previous_amount: Cents,
+    pub current_amount: Cents,
+    pub percent: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DetectedSubscription {
+    pub account_id: i32,
+    pub category_id: Option<i32>,
+    pub payee: String,
+    pub period: SubscriptionPeriod,
+    pub amount: Cents, // Latest charge, as a positive amount.
+    pub occurrences: usize,
+    pub first_seen: NaiveDate,
+    pub last_seen: NaiveDate,
+    pub next_expected: NaiveDate,
+    pub annualised_cost: Cents,
+    pub price_increase: Option<PriceIncrease>,
+    pub transaction_ids: Vec<i32>,
+}
+
+/// A detected subscription the user wants scheduled, named by its account and payee.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfirmSubscriptionDTO {
+    pub account_id: i32,
+    pub payee: String,
+}
+
+impl DetectedSubscription {
+    /// Turns a confirmed subscription into a schedule starting at the next expected charge.
+    pub fn to_scheduled_transaction(&self) -> ScheduledTransaction {
+        ScheduledTransaction {
+            account_id: self.account_id,
+            amount: -self.amount,
+            start_date: self.next_expected,
+            end_date: None,
+            frequency: Some(self.period.into()),
+            interval: 1,
+        }
+    }
+}
+
+/// Looks for charges to the same payee from the same account that repeat weekly, monthly or yearly.
+///
+/// A group counts as a subscription when most intervals sit close to one period and each charge
+/// differs from the previous one by a small amount, so prices that creep up over the years are
+/// still recognised. Subscriptions whose next charge is overdue by more than the period tolerance
+/// are considered cancelled and left out.
+pub fn detect_subscriptions(
+    history: &[HistoricalTransaction],
+    today: NaiveDate,
+) -> Vec<DetectedSubscription> {
+    let mut groups: HashMap<(i32, String), Vec<&HistoricalTransaction>> = HashMap::new();
+
+    for transaction in history.iter().filter(|t| t.amount < 0) {
+        groups
+            .entry((transaction.account_id, normalize_payee(&transaction.payee)))
+            .or_default()
+            .push(transaction);
+    }
+
+    let mut detected: Vec<DetectedSubscription> = groups
+        .into_values()
+        .filter_map(|mut charges| {
+            charges.sort_by_key(|t| (t.date, t.id));
+            detect_in_group(&charges, today)
+        })
+        .collect();
+
+    detected.sort_by(|a, b| a.next_expected.cmp(&b.next_expected).then(a.payee.cmp(&b.payee)));
+    detected
+}
+
+/// Turns the subscription confirmed by the user into a schedule. Detection runs again on the
+/// current history, so a subscription that was cancelled in the meantime cannot be confirmed.
+pub fn confirm_subscription(
+    history: &[HistoricalTransaction],
+    today: NaiveDate,
+    confirmed: &ConfirmSubscriptionDTO,
+) -> Result<ScheduledTransaction, ErrorResponse> {
+    let payee = normalize_payee(&confirmed.payee);
+
+    detect_subscriptions(history, today)
+        .into_iter()
+        .find(|detected| {
+            detected.account_id == confirmed.account_id && normalize_payee(&detected.payee) == payee
+        })
+        .map(|detected| detected.to_scheduled_transaction())
+        .ok_or_else(|| {
+            ErrorResponse::keyed(
+                ErrorCode::SearchObjectNotFoundError,
+                Some("payee".into()),
+                "subscription-not-found",
+            )
+        })
+}
+
+fn detect_in_group(
+    charges: &[&HistoricalTransaction],
+    today: NaiveDate,
+) -> Option<DetectedSubscription> {
+    if charges.len() < MIN_OCCURRENCES {
+        return None;
+    }
+
+    let intervals: Vec<i64> = charges
+        .windows(2)
+        .map(|pair| (pair[1].date - pair[0].date).num_days())
+        .collect();
+
+    let mut sorted_intervals = intervals.clone();
+    sorted_intervals.sort_unstable();
+    let median = sorted_intervals[sorted_intervals.len() / 2];
+    let period = SubscriptionPeriod::classify(median)?;
+
+    let regular = intervals
+        .iter()
+        .filter(|interval| (**interval - median).abs() <= period.tolerance_days())
+        .count();
+
+    if regular * 100 < intervals.len() * MIN_REGULAR_INTERVALS_PERCENT {
+        return None;
+    }
+
+    let amounts: Vec<Cents> = charges.iter().map(|t| t.amount.abs()).collect();
+    let drifts_slowly = amounts
+        .windows(2)
+        .all(|pair| percent_change(pair[0], pair[1]).abs() <= MAX_STEP_CHANGE_PERCENT);
+
+    if !drifts_slowly {
+        return None;
+    }
+
+    let first = charges.first()?;
+    let last = charges.last()?;
+    let next_expected = period.next_after(first.date, last.date)?;
+
+    if (today - next_expected).num_days() > period.tolerance_days() {
+        return None;
+    }
+
+    let current_amount = *amounts.last()?;
+    let previous_amount = amounts[amounts.len() - 2];
+    let price_increase = (current_amount > previous_amount).then(|| PriceIncrease {
+        previous_amount,
+        current_amount,
+        percent: percent_change(previous_amount, current_amount),
+    });
+
+    Some(DetectedSubscription {
+        account_id: last.account_id,
+        category_id: last.category_id,
+        payee: last.payee.trim().to_string(),
+        period,
+        amount: current_amount,
+        occurrences: charges.len(),
+        first_seen: first.date,
+        last_seen: last.date,
+        next_expected,
+        annualised_cost: current_amount * period.charges_per_year(),
+        price_increase,
+        transaction_ids: charges.iter().map(|t| t.id).collect(),
+    })
+}
+
+fn percent_change(from: Cents, to: Cents) -> i64 {
+    if from == 0 {
+        return if to == 0 { 0 } else { 100 };
+    }
+
+    (to - from) * 100 / from
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(value: &str) -> NaiveDate {
+        value.parse().unwrap()
+    }
+
+    fn charges(payee: &str, charges: &[(&str, Cents)]) -> Vec<HistoricalTransaction> {
+        charges
+            .iter()
+            .enumerate()
+            .map(|(index, (on, amount))| HistoricalTransaction {
+                id: index as i32 + 1,
+                account_id: 1,
+                category_id: Some(3),
+                payee: payee.into(),
+                amount: *amount,
+                date: date(on),
+                tag_ids: Vec::new(),
+            })
+            .collect()
+    }
+
+    fn detected(history: &[HistoricalTransaction], today: &str) -> Vec<DetectedSubscription> {
+        detect_subscriptions(history, date(today))
+    }
+
+    #[test]
+    fn intervals_are_classified_by_period() {
+        let history = [
+            charges(
+                "Gym",
+                &[
+                    ("2025-03-03", -999),
+                    ("2025-03-10", -999),
+                    ("2025-03-17", -999),
+                    ("2025-03-24", -999),
+                ],
+            ),
+            charges(
+                " STREAMING  co",
+                &[("2025-01-15", -1500), ("2025-02-15", -1500)],
+            ),
+            charges(
+                "streaming co",
+                &[("2025-03-15", -1500), ("2025-04-15", -1500)],
+            ),
+            charges(
+                "Domain",
+                &[
+                    ("2022-03-01", -1200),
+                    ("2023-03-01", -1200),
+                    ("2024-03-01", -1200),
+                ],
+            ),
+            // Ten days apart matches no period.
+            charges(
+                "Bakery",
+                &[
+                    ("2025-03-01", -500),
+                    ("2025-03-11", -500),
+                    ("2025-03-21", -500),
+                ],
+            ),
+            // Money coming in is never a subscription.
+            charges(
+                "Employer",
+                &[
+                    ("2025-02-01", 5000),
+                    ("2025-03-01", 5000),
+                    ("2025-04-01", 5000),
+                ],
+            ),
+        ]
+        .concat();
+
+        let found = detected(&history, "2025-03-27");
+        let periods: Vec<_> = found
+            .iter()
+            .map(|s| (s.payee.as_str(), s.period, s.next_expected))
+            .collect();
+        assert_eq!(
+            periods,
+            [
+                ("Gym", SubscriptionPeriod::Weekly, date("2025-03-31")),
+                (
+                    "streaming co",
+                    SubscriptionPeriod::Monthly,
+                    date("2025-05-15")
+                ),
+            ]
+        );
+        assert_eq!(found[0].annualised_cost, 999 * 52);
+        assert_eq!(found[1].occurrences, 4);
+        assert_eq!(found[1].annualised_cost, 1500 * 12);
+
+        let domain: Vec<_> = detected(&history, "2024-06-01")
+            .into_iter()
+            .filter(|s| s.payee == "Domain")
+            .collect();
+        assert_eq!(domain.len(), 1);
+        assert_eq!(domain[0].period, SubscriptionPeriod::Yearly);
+        assert_eq!(domain[0].next_expected, date("2025-03-01"));
+        assert_eq!(domain[0].annualised_cost, 1200);
+    }
+
+    #[test]
+    fn amounts_may_drift_but_not_jump() {
+        let creeping = charges(
+            "Music",
+            &[
+                ("2025-01-10", -1000),
+                ("2025-02-10", -1050),
+                ("2025-03-10", -1100),
+                ("2025-04-10", -1150),
+            ],
+        );
+        let found = detected(&creeping, "2025-04-20");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].amount, 1150);
+        assert_eq!(
+            found[0].price_increase,
+            Some(PriceIncrease {
+                previous_amount: 1100,
+                current_amount: 1150,
+                percent: 4,
+            })
+        );
+
+        let steady = charges(
+            "Music",
+            &[
+                ("2025-02-10", -1000),
+                ("2025-03-10", -1000),
+                ("2025-04-10", -1000),
+            ],
+        );
+        assert_eq!(detected(&steady, "2025-04-20")[0].price_increase, None);
+
+        let jump = charges(
+            "Music",
+            &[
+                ("2025-02-10", -1000),
+                ("2025-03-10", -1000),
+                ("2025-04-10", -1500),
+            ],
+        );
+        assert!(detected(&jump, "2025-04-20").is_empty());
+    }
+
+    #[test]
+    fn overdue_subscriptions_count_as_cancelled() {
+        let history = charges(
+            "Cloud",
+            &[
+                ("2025-01-15", -300),
+                ("2025-02-15", -300),
+                ("2025-03-15", -300),
+                ("2025-04-15", -300),
+            ],
+        );
+
+        // A few days late is still within the monthly tolerance.
+        assert_eq!(detected(&history, "2025-05-19").len(), 1);
+        assert!(detected(&history, "2025-05-20").is_empty());
+        // Two charges are not enough to call it a pattern.
+        assert!(detected(&history[..2], "2025-02-20").is_empty());
+    }
+
+    #[test]
+    fn confirmed_subscriptions_become_schedules() {
+        let history = charges(
+            "Cloud Storage",
+            &[
+                ("2025-02-15", -300),
+                ("2025-03-15", -300),
+                ("2025-04-15", -300),
+            ],
+        );
+        let confirmed = ConfirmSubscriptionDTO {
+            account_id: 1,
+            payee: "cloud  storage".into(),
+        };
+
+        let schedule = confirm_subscription(&history, date("2025-04-20"), &confirmed).unwrap();
+        assert_eq!(schedule.account_id, 1);
+        assert_eq!(schedule.amount, -300);
+        assert_eq!(schedule.start_date, date("2025-05-15"));
+        assert_eq!(schedule.frequency, Some(Frequency::Monthly));
+
+        let err = confirm_subscription(&history, date("2025-06-01"), &confirmed).unwrap_err();
+        assert!(matches!(
+            err.code_name,
+            ErrorCode::SearchObjectNotFoundError
+        ));
+        let other_account = ConfirmSubscriptionDTO {
+            account_id: 2,
+            ..confirmed
+        };
+        assert!(confirm_subscription(&history, date("2025-04-20"), &other_account).is_err());
+    }
+}
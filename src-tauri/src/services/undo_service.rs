@@ -0,0 +1,338 @@
+use crate::{
+    models::v1::{audit_model::AuditOperation, profile_model::ProfileModel},
+    services::{dto::undo_dto::UndoStatusDTO, profile_service::ProfileService},
+    utils::error::mapping::{ErrorCode, ErrorResponse},
+};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+const MAX_UNDO_STEPS: usize = 50;
+
+/// A change that can be taken back by writing `revert_to` over the row, as long as the row is
+/// still `expected`, the way the change left it.
+#[derive(Debug, Clone)]
+pub enum UndoableChange {
+    Profile {
+        expected: ProfileModel,
+        revert_to: ProfileModel,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct UndoEntry {
+    /// The change this entry takes back (undo) or makes again (redo).
+    operation: AuditOperation,
+    change: UndoableChange,
+}
+
+#[derive(Default)]
+struct Stacks {
+    undo: Vec<UndoEntry>,
+    redo: Vec<UndoEntry>,
+}
+
+/// Undo and redo stacks of the current session, kept in memory only. Services record the inverse
+/// of each change they make; a new change drops whatever could have been redone.
+#[derive(Clone, Default)]
+pub struct UndoHistory {
+    stacks: Arc<Mutex<Stacks>>,
+}
+
+impl UndoHistory {
+    pub fn record(&self, operation: AuditOperation, change: UndoableChange) {
+        let mut stacks = self.lock();
+
+        stacks.redo.clear();
+        stacks.undo.push(UndoEntry { operation, change });
+        if stacks.undo.len() > MAX_UNDO_STEPS {
+            stacks.undo.remove(0);
+        }
+    }
+
+    pub fn clear(&self) {
+        *self.lock() = Stacks::default();
+    }
+
+    pub fn status(&self) -> UndoStatusDTO {
+        let stacks = self.lock();
+
+        UndoStatusDTO {
+            undo: stacks.undo.last().map(|entry| entry.operation),
+            redo: stacks.redo.last().map(|entry| entry.operation),
+        }
+    }
+
+    /// Avatars that undoing or redoing could bring back, so the orphan sweep leaves them alone.
+    pub fn profile_picture_urls(&self) -> Vec<String> {
+        let stacks = self.lock();
+
+        stacks
+            .undo
+            .iter()
+            .chain(&stacks.redo)
+            .flat_map(|entry| match &entry.change {
+                UndoableChange::Profile {
+                    expected,
+                    revert_to,
+                } => [
+                    &expected.profile_picture_url,
+                    &revert_to.profile_picture_url,
+                ],
+            })
+            .flatten()
+            .cloned()
+            .collect()
+    }
+
+    /// Applying a step leaves the row as an older snapshot had it, but with a new `updated_at`.
+    /// Snapshots of that state take the new time, so the steps next to it still apply.
+    fn retime(
+        stacks: &mut Stacks,
+        id: i32,
+        from: chrono::NaiveDateTime,
+        to: chrono::NaiveDateTime,
+    ) {
+        for entry in stacks.undo.iter_mut().chain(stacks.redo.iter_mut()) {
+            match &mut entry.change {
+                UndoableChange::Profile {
+                    expected,
+                    revert_to,
+                } => {
+                    for snapshot in [expected, revert_to] {
+                        if snapshot.id == id && snapshot.updated_at == from {
+                            snapshot.updated_at = to;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Stacks> {
+        self.stacks
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Direction {
+    Undo,
+    Redo,
+}
+
+/// Takes back the last recorded changes and makes them again. Each step goes through the service
+/// that made the change, with the same checks, and fails with `StaleDataError` when the data was
+/// changed since in a way the history does not know about. Such a step is dropped; any other
+/// failure leaves it in place to retry.
+#[derive(Clone)]
+pub struct UndoService {
+    profiles: ProfileService,
+    history: UndoHistory,
+}
+
+impl UndoService {
+    pub fn new(profiles: ProfileService) -> Self {
+        Self {
+            history: profiles.history(),
+            profiles,
+        }
+    }
+
+    /// The same service, auditing the changes it makes as made by `actor`.
+    pub fn acting_as(&self, actor: Option<i32>) -> Self {
+        Self {
+            profiles: self.profiles.acting_as(actor),
+            ..self.clone()
+        }
+    }
+
+    pub fn status(&self) -> UndoStatusDTO {
+        self.history.status()
+    }
+
+    /// Forgets the history, such as when another profile is selected.
+    pub fn clear(&self) {
+        self.history.clear()
+    }
+
+    pub async fn undo(&self) -> Result<UndoStatusDTO, ErrorResponse> {
+        self.step(Direction::Undo).await
+    }
+
+    pub async fn redo(&self) -> Result<UndoStatusDTO, ErrorResponse> {
+        self.step(Direction::Redo).await
+    }
+
+    async fn step(&self, direction: Direction) -> Result<UndoStatusDTO, ErrorResponse> {
+        let entry = {
+            let mut stacks = self.history.lock();
+            match direction {
+                Direction::Undo => stacks.undo.pop(),
+                Direction::Redo => stacks.redo.pop(),
+            }
+        };
+        let Some(entry) = entry else {
+            return Err(ErrorResponse::keyed(
+                ErrorCode::SearchObjectNotFoundError,
+                None,
+                match direction {
+                    Direction::Undo => "undo-nothing",
+                    Direction::Redo => "redo-nothing",
+                },
+            ));
+        };
+
+        let change = match &entry.change {
+            UndoableChange::Profile {
+                expected,
+                revert_to,
+            } => self
+                .profiles
+                .revert_profile(expected, revert_to.clone())
+                .await
+                .map(|reverted| {
+                    let retimed = (reverted.id, revert_to.updated_at, reverted.updated_at);
+                    let change = UndoableChange::Profile {
+                        expected: reverted,
+                        revert_to: expected.clone(),
+                    };
+                    (change, retimed)
+                }),
+        };
+
+        let mut stacks = self.history.lock();
+        match change {
+            Ok((change, (id, from, to))) => {
+                UndoHistory::retime(&mut stacks, id, from, to);
+                let inverse = UndoEntry {
+                    operation: entry.operation,
+                    change,
+                };
+                match direction {
+                    Direction::Undo => stacks.redo.push(inverse),
+                    Direction::Redo => stacks.undo.push(inverse),
+                }
+            }
+            Err(err) => {
+                if !matches!(err.code_name, ErrorCode::StaleDataError) {
+                    match direction {
+                        Direction::Undo => stacks.undo.push(entry),
+                        Direction::Redo => stacks.redo.push(entry),
+                    }
+                }
+                return Err(err);
+            }
+        }
+        drop(stacks);
+
+        Ok(self.history.status())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::patch::Patch,
+        repositories::memory::profile_repository::InMemoryProfileRepository,
+        services::dto::profile_dto::{CreateProfileDTO, RestoreProfileDTO, UpdateProfileDTO},
+    };
+
+    fn services() -> (UndoService, ProfileService) {
+        let profiles = ProfileService::new(Arc::new(InMemoryProfileRepository::new()));
+
+        (UndoService::new(profiles.clone()), profiles)
+    }
+
+    async fn create_profile(profiles: &ProfileService, username: &str) -> i32 {
+        profiles
+            .create_profile(CreateProfileDTO {
+                username: username.into(),
+                display_name: None,
+                profile_picture_bytes: None,
+                profile_picture_crop: None,
+            })
+            .await
+            .unwrap()
+            .id
+    }
+
+    fn rename(display_name: &str) -> UpdateProfileDTO {
+        UpdateProfileDTO {
+            display_name: Patch::Value(display_name.into()),
+            ..UpdateProfileDTO::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn undo_and_redo_walk_the_history() {
+        let (undo, profiles) = services();
+        let alice = create_profile(&profiles, "alice").await;
+        profiles
+            .update_profile(alice, rename("Alice"))
+            .await
+            .unwrap();
+        profiles.update_profile(alice, rename("Al")).await.unwrap();
+
+        undo.undo().await.unwrap();
+        let status = undo.undo().await.unwrap();
+        assert_eq!(status.undo, None);
+        assert_eq!(status.redo, Some(AuditOperation::Update));
+        let profile = profiles.get_one_by_id(alice).await.unwrap();
+        assert_eq!(profile.display_name, None);
+
+        undo.redo().await.unwrap();
+        let profile = profiles.get_one_by_id(alice).await.unwrap();
+        assert_eq!(profile.display_name.as_deref(), Some("Alice"));
+
+        // A new change makes the undone one unreachable.
+        profiles
+            .update_profile(alice, rename("Ally"))
+            .await
+            .unwrap();
+        let err = undo.redo().await.unwrap_err();
+        assert!(matches!(
+            err.code_name,
+            ErrorCode::SearchObjectNotFoundError
+        ));
+    }
+
+    #[tokio::test]
+    async fn undo_brings_back_deleted_profiles() {
+        let (undo, profiles) = services();
+        let alice = create_profile(&profiles, "alice").await;
+        profiles.delete_profile(alice).await.unwrap();
+        assert_eq!(undo.status().undo, Some(AuditOperation::Delete));
+
+        undo.undo().await.unwrap();
+        let profile = profiles.get_one_by_id(alice).await.unwrap();
+        assert_eq!(profile.username, "alice");
+
+        undo.redo().await.unwrap();
+        assert!(profiles.get_one_by_id(alice).await.is_err());
+        profiles
+            .restore_profile(alice, RestoreProfileDTO { username: None })
+            .await
+            .unwrap();
+        assert_eq!(undo.status().undo, Some(AuditOperation::Restore));
+    }
+
+    #[tokio::test]
+    async fn undo_refuses_data_changed_since() {
+        let (undo, profiles) = services();
+        let alice = create_profile(&profiles, "alice").await;
+        profiles
+            .update_profile(alice, rename("Alice"))
+            .await
+            .unwrap();
+        // Not a change the history records, but a change all the same.
+        profiles.set_profile_secret(alice, None).await.unwrap();
+
+        let err = undo.undo().await.unwrap_err();
+
+        assert!(matches!(err.code_name, ErrorCode::StaleDataError));
+        assert_eq!(undo.status().undo, None);
+        let profile = profiles.get_one_by_id(alice).await.unwrap();
+        assert_eq!(profile.display_name.as_deref(), Some("Alice"));
+    }
+}
@@ -1,17 +1,88 @@
 pub use crate::services;
-use crate::{repositories::v1::profile_repository::ProfileRepository, services::profile_service::ProfileService};
+use crate::{
+    repositories::v1::{
+        audit_repository::SqlAuditRepository, backup_repository::SqlBackupRepository,
+        notification_repository::SqlNotificationRepository,
+        preferences_repository::SqlPreferencesRepository,
+        profile_archive_repository::SqlProfileArchiveRepository,
+        profile_repository::SqlProfileRepository,
+    },
+    services::{
+        audit_service::AuditService, backup_service::BackupService,
+        notification_service::NotificationService, preferences_service::PreferencesService,
+        profile_archive_service::ProfileArchiveService, profile_service::ProfileService,
+        session_service::SessionService, settings_service::SettingsService,
+        undo_service::UndoService,
+    },
+    utils::db::connection::ConnectionManager,
+};
+use std::sync::Arc;
 
 #[derive(Clone)]
 pub struct AppState {
+    pub db: Arc<ConnectionManager>,
     pub profile_service: ProfileService,
+    pub preferences_service: PreferencesService,
+    pub profile_archive_service: ProfileArchiveService,
+    pub backup_service: BackupService,
+    pub audit_service: AuditService,
+    pub undo_service: UndoService,
+    pub notification_service: NotificationService,
+    pub settings_service: SettingsService,
+    pub session_service: SessionService,
 }
 
 impl AppState {
-    pub fn new(pool: sqlx::PgPool) -> Self {
+    pub fn new(db: Arc<ConnectionManager>, settings_service: SettingsService) -> Self {
         // Profile:
-        let profile_repo = ProfileRepository::new(pool.clone());
-        let profile_service = ProfileService::new(profile_repo);
+        let profile_repo = Arc::new(SqlProfileRepository::new(db.clone()));
+        let profile_service = ProfileService::new(profile_repo.clone());
 
-        Self { profile_service }
+        // Preferences:
+        let preferences_repo = Arc::new(SqlPreferencesRepository::new(db.clone()));
+        let preferences_service =
+            PreferencesService::new(preferences_repo.clone(), settings_service.clone());
+
+        // Notifications:
+        let notification_repo = Arc::new(SqlNotificationRepository::new(db.clone()));
+        let notification_service = NotificationService::new(notification_repo.clone());
+
+        // Export and import:
+        let archive_repo = Arc::new(SqlProfileArchiveRepository::new(db.clone()));
+        let profile_archive_service = ProfileArchiveService::new(
+            profile_service.clone(),
+            profile_repo,
+            preferences_repo,
+            notification_repo,
+            archive_repo,
+        );
+
+        // Backups:
+        let backup_repo = Arc::new(SqlBackupRepository::new(db.clone()));
+        let backup_service = BackupService::new(backup_repo, settings_service.clone());
+
+        // Audit log:
+        let audit_repo = Arc::new(SqlAuditRepository::new(db.clone()));
+        let audit_service = AuditService::new(audit_repo);
+
+        // Undo:
+        let undo_service = UndoService::new(profile_service.clone());
+
+        // Session:
+        let session_service =
+            SessionService::new(profile_service.clone(), settings_service.clone());
+
+        Self {
+            db,
+            profile_service,
+            preferences_service,
+            profile_archive_service,
+            backup_service,
+            audit_service,
+            undo_service,
+            notification_service,
+            settings_service,
+            session_service,
+        }
     }
 }
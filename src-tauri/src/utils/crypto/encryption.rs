@@ -0,0 +1,204 @@
+use crate::utils::error::mapping::{ErrorCode, ErrorResponse};
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::{
+    aead::{rand_core::RngCore, Aead, AeadCore, OsRng, Payload},
+    KeyInit, XChaCha20Poly1305, XNonce,
+};
+
+// Layout of an encrypted file, integers little-endian:
+//   magic (8) | format version (1) | Argon2id m, t, p costs (4 each) | salt (16) | key check (16)
+//   | nonce (24) | XChaCha20-Poly1305 ciphertext and tag
+// The whole header is authenticated along with the ciphertext.
+const MAGIC: &[u8; 8] = b"NMBCRYPT";
+const FORMAT_VERSION: u8 = 1;
+
+const SALT_LEN: usize = 16;
+const KEY_LEN: usize = 32;
+const CHECK_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+const HEADER_LEN: usize = MAGIC.len() + 1 + 3 * 4 + SALT_LEN + CHECK_LEN + NONCE_LEN;
+
+pub const MIN_PASSPHRASE_LENGTH: usize = 8;
+pub const MAX_PASSPHRASE_LENGTH: usize = 128;
+
+/// Whether `bytes` were written by [`encrypt`]. Anything else is read as plain.
+pub fn is_encrypted(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC)
+}
+
+/// Rules for passphrases that new files are encrypted with. Opening a file takes any passphrase.
+pub fn check_passphrase(passphrase: &str) -> Result<(), ErrorResponse> {
+    let length = passphrase.chars().count();
+    if !(MIN_PASSPHRASE_LENGTH..=MAX_PASSPHRASE_LENGTH).contains(&length) {
+        return Err(ErrorResponse::keyed(
+            ErrorCode::UserInputValidationError,
+            Some("passphrase".into()),
+            "encryption-passphrase-length",
+        )
+        .with_arg("min", MIN_PASSPHRASE_LENGTH)
+        .with_arg("max", MAX_PASSPHRASE_LENGTH));
+    }
+
+    Ok(())
+}
+
+/// Encrypts `plaintext` under a key derived from `passphrase` with Argon2id and a random salt.
+pub fn encrypt(plaintext: &[u8], passphrase: &str) -> Result<Vec<u8>, ErrorResponse> {
+    let params = Params::default();
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let (key, check) = derive_key(passphrase, &params, &salt)?;
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+
+    let mut header = Vec::with_capacity(HEADER_LEN);
+    header.extend_from_slice(MAGIC);
+    header.push(FORMAT_VERSION);
+    for cost in [params.m_cost(), params.t_cost(), params.p_cost()] {
+        header.extend_from_slice(&cost.to_le_bytes());
+    }
+    header.extend_from_slice(&salt);
+    header.extend_from_slice(&check);
+    header.extend_from_slice(&nonce);
+
+    let ciphertext = cipher(&key)?
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: plaintext,
+                aad: &header,
+            },
+        )
+        .map_err(|_| ErrorResponse::unhandled())?;
+
+    header.extend_from_slice(&ciphertext);
+    Ok(header)
+}
+
+/// Reverses [`encrypt`]. A missing or wrong passphrase is a `WrongPassphraseError`; a file that
+/// the right passphrase cannot open is a `CorruptedFileError`.
+pub fn decrypt(bytes: &[u8], passphrase: Option<&str>) -> Result<Vec<u8>, ErrorResponse> {
+    if bytes.len() < HEADER_LEN || !is_encrypted(bytes) {
+        return Err(corrupted());
+    }
+    let (header, ciphertext) = bytes.split_at(HEADER_LEN);
+    let mut rest = &header[MAGIC.len()..];
+
+    if take(&mut rest, 1)[0] > FORMAT_VERSION {
+        return Err(ErrorResponse::keyed(
+            ErrorCode::UnsupportedVersionError,
+            None,
+            "encryption-newer-version",
+        ));
+    }
+    let mut costs = [0u32; 3];
+    for cost in &mut costs {
+        *cost = u32::from_le_bytes(take(&mut rest, 4).try_into().map_err(|_| corrupted())?);
+    }
+    let salt = take(&mut rest, SALT_LEN);
+    let check = take(&mut rest, CHECK_LEN);
+    let nonce = XNonce::from_slice(take(&mut rest, NONCE_LEN));
+
+    let Some(passphrase) = passphrase else {
+        return Err(wrong_passphrase("encryption-passphrase-needed"));
+    };
+    // Costs come from the file; refuse ones that are invalid or far above what we write.
+    let params = Params::new(costs[0], costs[1], costs[2], None)
+        .ok()
+        .filter(|params| params.m_cost() <= 16 * Params::DEFAULT_M_COST)
+        .filter(|params| params.t_cost() <= 16 * Params::DEFAULT_T_COST)
+        .ok_or_else(corrupted)?;
+
+    let (key, expected_check) = derive_key(passphrase, &params, salt)?;
+    if expected_check != check {
+        return Err(wrong_passphrase("error-wrong-passphrase"));
+    }
+
+    cipher(&key)?
+        .decrypt(
+            nonce,
+            Payload {
+                msg: ciphertext,
+                aad: header,
+            },
+        )
+        .map_err(|_| corrupted())
+}
+
+/// Derives the encryption key and, from the same Argon2 output, a check value stored in the clear
+/// so a wrong passphrase can be told apart from a damaged file.
+fn derive_key(
+    passphrase: &str,
+    params: &Params,
+    salt: &[u8],
+) -> Result<([u8; KEY_LEN], [u8; CHECK_LEN]), ErrorResponse> {
+    let params = Params::new(
+        params.m_cost(),
+        params.t_cost(),
+        params.p_cost(),
+        Some(KEY_LEN + CHECK_LEN),
+    )
+    .map_err(|_| ErrorResponse::unhandled())?;
+
+    let mut output = [0u8; KEY_LEN + CHECK_LEN];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), salt, &mut output)
+        .map_err(|_| ErrorResponse::unhandled())?;
+
+    let mut key = [0u8; KEY_LEN];
+    let mut check = [0u8; CHECK_LEN];
+    key.copy_from_slice(&output[..KEY_LEN]);
+    check.copy_from_slice(&output[KEY_LEN..]);
+    Ok((key, check))
+}
+
+fn cipher(key: &[u8]) -> Result<XChaCha20Poly1305, ErrorResponse> {
+    XChaCha20Poly1305::new_from_slice(key).map_err(|_| ErrorResponse::unhandled())
+}
+
+fn take<'a>(bytes: &mut &'a [u8], len: usize) -> &'a [u8] {
+    let (head, tail) = bytes.split_at(len);
+    *bytes = tail;
+    head
+}
+
+fn wrong_passphrase(message_id: &'static str) -> ErrorResponse {
+    ErrorResponse::keyed(
+        ErrorCode::WrongPassphraseError,
+        Some("passphrase".into()),
+        message_id,
+    )
+}
+
+fn corrupted() -> ErrorResponse {
+    ErrorResponse::keyed(ErrorCode::CorruptedFileError, None, "encryption-damaged")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_with_the_right_passphrase() {
+        let encrypted = encrypt(b"ledger", "correct horse").unwrap();
+
+        assert!(is_encrypted(&encrypted));
+        assert_eq!(
+            decrypt(&encrypted, Some("correct horse")).unwrap(),
+            b"ledger"
+        );
+    }
+
+    #[test]
+    fn tells_wrong_passphrases_from_damage() {
+        let mut encrypted = encrypt(b"ledger", "correct horse").unwrap();
+
+        let err = decrypt(&encrypted, Some("battery staple")).unwrap_err();
+        assert!(matches!(err.code_name, ErrorCode::WrongPassphraseError));
+        let err = decrypt(&encrypted, None).unwrap_err();
+        assert!(matches!(err.code_name, ErrorCode::WrongPassphraseError));
+
+        *encrypted.last_mut().unwrap() ^= 1;
+        let err = decrypt(&encrypted, Some("correct horse")).unwrap_err();
+        assert!(matches!(err.code_name, ErrorCode::CorruptedFileError));
+    }
+}
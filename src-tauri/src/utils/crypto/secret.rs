@@ -0,0 +1,25 @@
+use crate::utils::error::mapping::{ErrorCode, ErrorResponse};
+use argon2::{
+    password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Argon2,
+};
+
+/// Hashes a PIN or passphrase with Argon2id and a random salt, returning a PHC string that
+/// carries its own parameters.
+pub fn hash_secret(secret: &str) -> Result<String, ErrorResponse> {
+    let salt = SaltString::generate(&mut OsRng);
+
+    Argon2::default()
+        .hash_password(secret.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|_| ErrorResponse::unhandled())
+}
+
+pub fn verify_secret(secret: &str, hash: &str) -> Result<bool, ErrorResponse> {
+    let hash = PasswordHash::new(hash)
+        .map_err(|_| ErrorResponse::keyed(ErrorCode::ExpectedError, None, "secret-corrupted"))?;
+
+    Ok(Argon2::default()
+        .verify_password(secret.as_bytes(), &hash)
+        .is_ok())
+}
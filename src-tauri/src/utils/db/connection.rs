@@ -0,0 +1,215 @@
+use crate::utils::{
+    db::{
+        migration,
+        pool::{with_pool, DbConfig, DbPool},
+    },
+    error::mapping::{ErrorCode, ErrorResponse},
+};
+use serde::Serialize;
+use std::{
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+use tokio::sync::watch;
+
+const INITIAL_RETRY_DELAY: Duration = Duration::from_secs(1);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ConnectionStatus {
+    Connecting,
+    Connected,
+    Disconnected,
+    Incompatible, // Schema is newer than this build; retrying will not help.
+}
+
+/// Owns the database pool and tracks whether it is usable.
+///
+/// The app starts even when the database cannot be reached. Until a connection succeeds,
+/// [`ConnectionManager::pool`] returns an error that commands hand straight back to the UI, while
+/// [`ConnectionManager::supervise`] keeps retrying in the background.
+pub struct ConnectionManager {
+    config: DbConfig,
+    pool: RwLock<Option<DbPool>>,
+    status: watch::Sender<ConnectionStatus>,
+}
+
+impl ConnectionManager {
+    pub fn new(config: DbConfig) -> Arc<Self> {
+        let (status, _) = watch::channel(ConnectionStatus::Connecting);
+
+        Arc::new(Self {
+            config,
+            pool: RwLock::new(None),
+            status,
+        })
+    }
+
+    pub fn status(&self) -> ConnectionStatus {
+        *self.status.borrow()
+    }
+
+    pub fn subscribe(&self) -> watch::Receiver<ConnectionStatus> {
+        self.status.subscribe()
+    }
+
+    pub fn pool(&self) -> Result<DbPool, ErrorResponse> {
+        match self.status() {
+            ConnectionStatus::Connected => {}
+            ConnectionStatus::Incompatible => {
+                return Err(ErrorResponse::keyed(
+                    ErrorCode::IncompatibleSchemaError,
+                    None,
+                    "error-incompatible-schema",
+                ))
+            }
+            ConnectionStatus::Connecting | ConnectionStatus::Disconnected => {
+                return Err(ErrorResponse::keyed(
+                    ErrorCode::NetworkError,
+                    None,
+                    "db-unreachable",
+                ))
+            }
+        }
+
+        self.pool
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+            .ok_or_else(|| ErrorResponse::keyed(ErrorCode::NetworkError, None, "db-unreachable"))
+    }
+
+    /// Opens the pool and brings the schema up to date.
+    pub async fn connect(&self) -> Result<(), ErrorResponse> {
+        let pool = match DbPool::connect(&self.config).await {
+            Ok(pool) => pool,
+            Err(err) => {
+                self.set_status(ConnectionStatus::Disconnected);
+                return Err(
+                    ErrorResponse::keyed(ErrorCode::NetworkError, None, "db-open-failed")
+                        .with_arg("details", err.to_string()),
+                );
+            }
+        };
+
+        if let Err(err) = migration::run_migrations(&pool).await {
+            self.set_status(match err.code_name {
+                ErrorCode::IncompatibleSchemaError => ConnectionStatus::Incompatible,
+                _ => ConnectionStatus::Disconnected,
+            });
+            return Err(err);
+        }
+
+        *self
+            .pool
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(pool);
+        self.set_status(ConnectionStatus::Connected);
+
+        Ok(())
+    }
+
+    /// Reconnects with exponential backoff while disconnected and health-checks the pool while
+    /// connected. Runs until the schema turns out to be incompatible.
+    pub async fn supervise(self: Arc<Self>) {
+        let mut retry_delay = INITIAL_RETRY_DELAY;
+
+        loop {
+            match self.status() {
+                ConnectionStatus::Incompatible => return,
+                ConnectionStatus::Connected => {
+                    tokio::time::sleep(HEALTH_CHECK_INTERVAL).await;
+
+                    if !self.is_healthy().await {
+                        log::warn!("Lost connection to the database");
+                        self.set_status(ConnectionStatus::Disconnected);
+                    }
+                }
+                ConnectionStatus::Connecting | ConnectionStatus::Disconnected => {
+                    tokio::time::sleep(retry_delay).await;
+
+                    let existing = self
+                        .pool
+                        .read()
+                        .unwrap_or_else(|poisoned| poisoned.into_inner())
+                        .clone();
+
+                    // sqlx re-establishes connections on its own, so an existing pool only needs
+                    // to answer again; a missing one has to be opened and migrated first.
+                    let recovered = match existing {
+                        Some(_) if self.is_healthy().await => {
+                            self.set_status(ConnectionStatus::Connected);
+                            true
+                        }
+                        Some(_) => false,
+                        None => match self.connect().await {
+                            Ok(()) => true,
+                            Err(err) => {
+                                log::warn!("Database still unreachable: {}", err.message);
+                                false
+                            }
+                        },
+                    };
+
+                    retry_delay = if recovered {
+                        log::info!("Connected to the database");
+                        INITIAL_RETRY_DELAY
+                    } else {
+                        (retry_delay * 2).min(MAX_RETRY_DELAY)
+                    };
+                }
+            }
+        }
+    }
+
+    async fn is_healthy(&self) -> bool {
+        let Some(pool) = self
+            .pool
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+        else {
+            return false;
+        };
+
+        with_pool!(&pool, |pool| sqlx::query("SELECT 1")
+            .execute(pool)
+            .await
+            .is_ok())
+    }
+
+    fn set_status(&self, status: ConnectionStatus) {
+        self.status.send_if_modified(|current| {
+            let changed = *current != status;
+            *current = status;
+            changed
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::db::pool::DbBackend;
+
+    fn manager() -> Arc<ConnectionManager> {
+        ConnectionManager::new(DbConfig {
+            backend: DbBackend::Sqlite,
+            url: "sqlite::memory:".into(),
+            max_connections: 1,
+        })
+    }
+
+    #[test]
+    fn pool_reports_why_it_is_unavailable() {
+        let manager = manager();
+        let err = manager.pool().unwrap_err();
+        assert!(matches!(err.code_name, ErrorCode::NetworkError));
+
+        manager.set_status(ConnectionStatus::Incompatible);
+        let err = manager.pool().unwrap_err();
+        assert!(matches!(err.code_name, ErrorCode::IncompatibleSchemaError));
+    }
+}
@@ -0,0 +1,139 @@
+use crate::utils::{
+    db::pool::{with_pool, DbBackend, DbPool},
+    error::mapping::{ErrorCode, ErrorResponse},
+    fs::common::{get_app_dir, AppDirs},
+};
+use serde_json::{Map, Value};
+use sqlx::migrate::{Migrate, Migrator};
+use std::collections::HashSet;
+
+static POSTGRES_MIGRATOR: Migrator = sqlx::migrate!("./migrations/postgres");
+static SQLITE_MIGRATOR: Migrator = sqlx::migrate!("./migrations/sqlite");
+
+// Statements that rewrite or remove existing data. Matching is deliberately loose: a false positive
+// only costs an extra backup.
+const DESTRUCTIVE_KEYWORDS: [&str; 5] = ["DROP ", "DELETE ", "TRUNCATE ", "UPDATE ", "RENAME "];
+
+fn migrator(backend: DbBackend) -> &'static Migrator {
+    match backend {
+        DbBackend::Postgres => &POSTGRES_MIGRATOR,
+        DbBackend::Sqlite => &SQLITE_MIGRATOR,
+    }
+}
+
+/// Applies the migrations embedded in the binary.
+///
+/// Refuses to touch a database that has migrations this build does not know about, since that
+/// means it was last opened by a newer version of the app. The database is backed up first when a
+/// pending migration changes or removes existing data: SQLite as a copy of the file, Postgres as a
+/// JSON dump of every table.
+pub async fn run_migrations(pool: &DbPool) -> Result<(), ErrorResponse> {
+    let migrator = migrator(pool.backend());
+
+    let applied: HashSet<i64> = with_pool!(pool, |pool| {
+        let mut conn = pool.acquire().await?;
+        conn.ensure_migrations_table().await?;
+        conn.list_applied_migrations()
+            .await?
+            .into_iter()
+            .map(|migration| migration.version)
+            .collect()
+    });
+
+    if let Some(newest) = applied
+        .iter()
+        .filter(|version| !migrator.version_exists(**version))
+        .max()
+    {
+        return Err(ErrorResponse::keyed(
+            ErrorCode::IncompatibleSchemaError,
+            None,
+            "db-newer-schema",
+        )
+        .with_arg("schema", newest.to_string()));
+    }
+
+    let destructive = migrator
+        .iter()
+        .filter(|migration| !migration.migration_type.is_down_migration())
+        .filter(|migration| !applied.contains(&migration.version))
+        .find(|migration| is_destructive(&migration.sql));
+
+    if let (Some(migration), false) = (destructive, applied.is_empty()) {
+        backup_before_migration(pool, migration.version).await?;
+    }
+
+    with_pool!(pool, |pool| migrator.run(pool).await?);
+
+    Ok(())
+}
+
+fn is_destructive(sql: &str) -> bool {
+    let sql = sql.to_uppercase();
+    DESTRUCTIVE_KEYWORDS
+        .iter()
+        .any(|keyword| sql.contains(keyword))
+}
+
+/// Copies the database into the backups dir. The migration does not run unless this succeeds.
+///
+/// Unlike the backups `BackupService` writes, these copies are never encrypted: migrations run at
+/// startup, before the backup passphrase, which is only kept in memory, can have been entered.
+/// They hold no more than the database itself, which is not encrypted either.
+async fn backup_before_migration(pool: &DbPool, version: i64) -> Result<(), ErrorResponse> {
+    let timestamp = chrono::Utc::now().format("%Y%m%d%H%M%S");
+    let dir = get_app_dir(AppDirs::Backups)?;
+
+    match pool {
+        DbPool::Sqlite(pool) => {
+            let path = dir.join(format!("pre-migration-{version}-{timestamp}.db"));
+
+            // VACUUM INTO writes a consistent copy even while other connections are open.
+            sqlx::query("VACUUM INTO $1")
+                .bind(path.to_string_lossy().to_string())
+                .execute(pool)
+                .await?;
+
+            log::info!("Backed up database to {} before migrating", path.display());
+        }
+        DbPool::Postgres(pool) => {
+            let path = dir.join(format!("pre-migration-{version}-{timestamp}.json"));
+            let mut tx = pool.begin().await?;
+
+            // One snapshot for every table, so the dump is consistent while the app keeps writing.
+            sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY")
+                .execute(&mut *tx)
+                .await?;
+
+            let tables = sqlx::query_scalar::<_, String>(
+                r#"
+                SELECT table_name::text FROM information_schema.tables
+                WHERE table_schema = current_schema() AND table_type = 'BASE TABLE'
+                ORDER BY table_name
+                "#,
+            )
+            .fetch_all(&mut *tx)
+            .await?;
+
+            // A logical dump: every row of every table as JSON, keyed by table name.
+            let mut dump = Map::new();
+            for table in tables {
+                let rows = sqlx::query_scalar::<_, String>(&format!(
+                    r#"SELECT COALESCE(json_agg(t), '[]')::text FROM "{}" t"#,
+                    table.replace('"', "\"\"")
+                ))
+                .fetch_one(&mut *tx)
+                .await?;
+                let rows = serde_json::from_str(&rows).map_err(|_| ErrorResponse::unhandled())?;
+                dump.insert(table, rows);
+            }
+            tx.commit().await?;
+
+            tokio::fs::write(&path, Value::Object(dump).to_string()).await?;
+
+            log::info!("Dumped database to {} before migrating", path.display());
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,35 @@
+pub mod connection;
+pub mod migration;
+pub mod pool;
+
+use sqlx::error::ErrorKind;
+
+pub fn extract_field_from_constraint(c: &str) -> Option<String> {
+    let parts: Vec<&str> = c.split('_').collect();
+    if parts.len() >= 3 {
+        Some(parts[parts.len() - 2].to_string())
+    } else {
+        None
+    }
+}
+
+/// SQLite does not report constraint names, only messages such as
+/// `UNIQUE constraint failed: profiles.username`.
+pub fn extract_field_from_message(message: &str) -> Option<String> {
+    let (_, columns) = message.split_once("constraint failed: ")?;
+    let first = columns.split(',').next()?.trim();
+
+    first
+        .rsplit_once('.')
+        .map(|(_, column)| column.to_string())
+}
+
+/// Catalogue id of the message for a constraint the input violated.
+pub fn violation(kind: ErrorKind) -> Option<&'static str> {
+    match kind {
+        ErrorKind::UniqueViolation => Some("db-unique-violation"),
+        ErrorKind::NotNullViolation => Some("db-not-null-violation"),
+        ErrorKind::CheckViolation => Some("db-check-violation"),
+        _ => None,
+    }
+}
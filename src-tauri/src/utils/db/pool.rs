@@ -0,0 +1,134 @@
+use crate::{
+    models::v1::settings_model::DatabaseSettingsModel, utils::fs::common::ensure_app_data_dir,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::{
+    postgres::PgPoolOptions,
+    sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions},
+    PgPool, SqlitePool,
+};
+use std::{
+    io::{Error, ErrorKind},
+    str::FromStr,
+};
+
+const SQLITE_FILE_NAME: &str = "nomorebeans.db";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DbBackend {
+    Postgres,
+    Sqlite,
+}
+
+impl DbBackend {
+    pub fn from_url(url: &str) -> Self {
+        if url.starts_with("postgres://") || url.starts_with("postgresql://") {
+            DbBackend::Postgres
+        } else {
+            DbBackend::Sqlite
+        }
+    }
+}
+
+impl FromStr for DbBackend {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "postgres" | "postgresql" => Ok(DbBackend::Postgres),
+            "sqlite" => Ok(DbBackend::Sqlite),
+            _ => Err(format!("Unknown database backend: {value}")),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DbConfig {
+    pub backend: DbBackend,
+    pub url: String,
+    pub max_connections: u32,
+}
+
+impl DbConfig {
+    /// Without a URL, SQLite uses the embedded file in the app data dir. Postgres always needs one.
+    pub fn from_settings(settings: &DatabaseSettingsModel) -> std::io::Result<Self> {
+        let url = match (settings.backend, &settings.url) {
+            (_, Some(url)) => url.clone(),
+            (DbBackend::Sqlite, None) => Self::default_sqlite_url()?,
+            (DbBackend::Postgres, None) => {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "The Postgres backend needs a database URL",
+                ))
+            }
+        };
+
+        Ok(Self {
+            backend: settings.backend,
+            url,
+            max_connections: settings.pool_size,
+        })
+    }
+
+    fn default_sqlite_url() -> std::io::Result<String> {
+        let path = ensure_app_data_dir()?.join(SQLITE_FILE_NAME);
+        Ok(format!("sqlite://{}", path.to_string_lossy()))
+    }
+}
+
+/// Connection pool for whichever backend the app was configured with.
+///
+/// Repositories match on it through [`with_pool!`] so each query is written once and compiled for
+/// both backends. Queries must therefore stick to SQL both dialects accept: `$n` placeholders,
+/// `RETURNING`, `||` for concatenation, and timestamps bound from Rust instead of `NOW()`.
+#[derive(Debug, Clone)]
+pub enum DbPool {
+    Postgres(PgPool),
+    Sqlite(SqlitePool),
+}
+
+impl DbPool {
+    pub async fn connect(config: &DbConfig) -> Result<Self, sqlx::Error> {
+        match config.backend {
+            DbBackend::Postgres => {
+                let pool = PgPoolOptions::new()
+                    .max_connections(config.max_connections)
+                    .connect(&config.url)
+                    .await?;
+                Ok(DbPool::Postgres(pool))
+            }
+            DbBackend::Sqlite => {
+                let options = SqliteConnectOptions::from_str(&config.url)?
+                    .create_if_missing(true)
+                    .foreign_keys(true)
+                    .journal_mode(SqliteJournalMode::Wal);
+
+                let pool = SqlitePoolOptions::new()
+                    .max_connections(config.max_connections)
+                    .connect_with(options)
+                    .await?;
+                Ok(DbPool::Sqlite(pool))
+            }
+        }
+    }
+
+    pub fn backend(&self) -> DbBackend {
+        match self {
+            DbPool::Postgres(_) => DbBackend::Postgres,
+            DbPool::Sqlite(_) => DbBackend::Sqlite,
+        }
+    }
+}
+
+/// Runs `$body` with `$conn` bound to the concrete pool of either backend.
+macro_rules! with_pool {
+    ($pool:expr, |$conn:ident| $body:expr) => {
+        match $pool {
+            $crate::utils::db::pool::DbPool::Postgres($conn) => $body,
+            $crate::utils::db::pool::DbPool::Sqlite($conn) => $body,
+        }
+    };
+}
+
+pub(crate) use with_pool;
@@ -1,7 +1,36 @@
 use crate::utils::{
     db,
-    error::mapping::{ErrorCode, ErrorResponse},
+    error::mapping::{ErrorCode, ErrorResponse, FieldErrorResponse},
+    i18n::catalogue::{self, DEFAULT_LOCALE},
 };
+use serde_json::{Map, Value};
+use validator::{ValidationErrors, ValidationErrorsKind};
+
+// Struct-level rules report under this key instead of a field.
+const STRUCT_ERRORS_KEY: &str = "__all__";
+
+impl ErrorCode {
+    /// Catalogue id of the generic message of this code, such as `error-user-input-validation`.
+    pub fn message_id(self) -> String {
+        let name = self.name();
+        let chars: Vec<char> = name.strip_suffix("Error").unwrap_or(name).chars().collect();
+        let mut id = String::from("error");
+
+        for (i, c) in chars.iter().enumerate() {
+            // Capitals start a word, except inside acronyms such as `IO` and `RW`.
+            let starts_word = c.is_ascii_uppercase()
+                && (i == 0
+                    || chars[i - 1].is_ascii_lowercase()
+                    || chars.get(i + 1).is_some_and(char::is_ascii_lowercase));
+            if starts_word {
+                id.push('-');
+            }
+            id.push(c.to_ascii_lowercase());
+        }
+
+        id
+    }
+}
 
 impl ErrorResponse {
     pub fn new(code: ErrorCode, field: Option<String>, message: impl Into<String>) -> Self {
@@ -10,15 +39,27 @@ impl ErrorResponse {
             code: code.into(),
             field,
             message: message.into(),
+            errors: Vec::new(),
+            message_id: None,
+            message_args: Map::new(),
+        }
+    }
+
+    /// An error whose message comes from the catalogue, formatted in the default locale until
+    /// [`ErrorResponse::localize`] picks another.
+    pub fn keyed(code: ErrorCode, field: Option<String>, message_id: &'static str) -> Self {
+        Self {
+            message_id: Some(message_id),
+            ..Self::new(code, field, default_message(message_id, &Map::new()))
         }
     }
 
     pub fn expected() -> Self {
-        Self::new(ErrorCode::ExpectedError, None, "Expected error")
+        Self::keyed(ErrorCode::ExpectedError, None, "error-expected")
     }
 
     pub fn unhandled() -> Self {
-        Self::new(ErrorCode::UnhandledError, None, "Unhandled error")
+        Self::keyed(ErrorCode::UnhandledError, None, "error-unhandled")
     }
 
     pub fn with_field(mut self, field: impl Into<String>) -> Self {
@@ -26,70 +67,332 @@ impl ErrorResponse {
         self
     }
 
+    /// Replaces the message, and with it the catalogue message it was formatted from.
     pub fn with_message(mut self, message: impl Into<String>) -> Self {
         self.message = message.into();
+        self.message_id = None;
+        self.message_args.clear();
+        self
+    }
+
+    /// Sets an argument of the catalogue message.
+    pub fn with_arg(mut self, name: &str, value: impl Into<Value>) -> Self {
+        self.message_args.insert(name.into(), value.into());
+        if let Some(message_id) = self.message_id {
+            self.message = default_message(message_id, &self.message_args);
+        }
+        self
+    }
+
+    /// Translates the messages into `locale`. Catalogue messages are formatted again; messages
+    /// written in the code only exist in the default locale, so other locales get the generic
+    /// message of their code or validation rule instead.
+    pub fn localize(mut self, locale: &str) -> Self {
+        if catalogue::is_default(locale) {
+            return self;
+        }
+
+        let message_id = match self.message_id {
+            Some(message_id) => message_id.to_string(),
+            None => self.code_name.message_id(),
+        };
+        if let Some(message) = catalogue::message(locale, &message_id, &self.message_args) {
+            self.message = message;
+        }
+
+        for error in &mut self.errors {
+            error.message =
+                validation_message(locale, error.field.as_deref(), &error.code, &error.params);
+        }
+        if let Some(first) = self.errors.first() {
+            self.message = first.message.clone();
+        }
+
         self
     }
 }
 
+fn default_message(message_id: &str, args: &Map<String, Value>) -> String {
+    catalogue::message(DEFAULT_LOCALE, message_id, args).unwrap_or_else(|| message_id.to_string())
+}
+
+/// The message of the rule for this field if there is one, else the one of the rule.
+fn validation_message(
+    locale: &str,
+    field: Option<&str>,
+    code: &str,
+    params: &Map<String, Value>,
+) -> String {
+    // `items[1].name` and `name` share messages.
+    let leaf = field
+        .and_then(|field| field.rsplit('.').next())
+        .and_then(|leaf| leaf.split('[').next());
+
+    leaf.and_then(|leaf| catalogue::message(locale, &format!("validation-{leaf}-{code}"), params))
+        .or_else(|| catalogue::message(locale, &format!("validation-{code}"), params))
+        .unwrap_or_else(|| default_message("validation-invalid", params))
+}
+
 impl From<sqlx::Error> for ErrorResponse {
     fn from(err: sqlx::Error) -> Self {
         match err {
             sqlx::Error::Database(db_err) => {
-                if let Some(code) = db_err.code() {
-                    if let Some(msg) = db::pg_violation(code.as_ref()) {
-                        let field = db_err
-                            .constraint()
-                            .and_then(|c| db::extract_field_from_constraint(c));
+                if let Some(message_id) = db::violation(db_err.kind()) {
+                    let field = db_err
+                        .constraint()
+                        .and_then(db::extract_field_from_constraint)
+                        .or_else(|| db::extract_field_from_message(db_err.message()));
 
-                        return ErrorResponse::new(ErrorCode::UserInputValidationError, field, msg);
-                    }
+                    return ErrorResponse::keyed(
+                        ErrorCode::UserInputValidationError,
+                        field,
+                        message_id,
+                    );
                 }
-                ErrorResponse::new(ErrorCode::DatabaseError, None, "Database error")
+                ErrorResponse::keyed(ErrorCode::DatabaseError, None, "error-database")
             }
             sqlx::Error::RowNotFound => {
-                ErrorResponse::new(ErrorCode::ResourceError, None, "Resource not found")
+                ErrorResponse::keyed(ErrorCode::ResourceError, None, "error-resource")
+            }
+            sqlx::Error::Io(_) | sqlx::Error::PoolTimedOut | sqlx::Error::PoolClosed => {
+                ErrorResponse::keyed(ErrorCode::NetworkError, None, "db-connection-lost")
             }
             _ => ErrorResponse::unhandled(),
         }
     }
 }
 
+impl From<sqlx::migrate::MigrateError> for ErrorResponse {
+    fn from(err: sqlx::migrate::MigrateError) -> Self {
+        ErrorResponse::keyed(ErrorCode::MigrationError, None, "db-migration-failed")
+            .with_arg("details", err.to_string())
+    }
+}
+
 impl From<std::io::Error> for ErrorResponse {
     fn from(err: std::io::Error) -> Self {
         use std::io::ErrorKind::*;
 
-        let (msg, error_code) = match err.kind() {
-            NotFound => ("File or resource not found", ErrorCode::FileRWError),
-            PermissionDenied => ("Permission denied", ErrorCode::InsufficientPrivilegesError),
+        let (message_id, error_code) = match err.kind() {
+            NotFound => ("io-not-found", ErrorCode::FileRWError),
+            PermissionDenied => (
+                "io-permission-denied",
+                ErrorCode::InsufficientPrivilegesError,
+            ),
             ConnectionRefused | ConnectionReset | ConnectionAborted => {
-                ("Connection error occurred", ErrorCode::NetworkError)
+                ("io-connection", ErrorCode::NetworkError)
             }
-            TimedOut => ("I/O operation timed out", ErrorCode::IOError),
-            UnexpectedEof => ("Unexpected end of file", ErrorCode::FileRWError),
-            _ => ("I/O error occurred", ErrorCode::IOError),
+            TimedOut => ("io-timed-out", ErrorCode::IOError),
+            UnexpectedEof => ("io-unexpected-eof", ErrorCode::FileRWError),
+            _ => ("error-io", ErrorCode::IOError),
         };
 
-        ErrorResponse::new(error_code, None, msg)
+        ErrorResponse::keyed(error_code, None, message_id)
     }
 }
 
 impl From<validator::ValidationErrors> for ErrorResponse {
     fn from(err: validator::ValidationErrors) -> Self {
-        let field_detail = err.field_errors().iter().next().map(|(field, errs)| {
-            let msg = errs
-                .first()
-                .and_then(|e| e.message.as_ref())
-                .map(|m| m.to_string())
-                .unwrap_or_else(|| "Invalid value".to_string());
-
-            (field.to_string(), msg)
-        });
-
-        if let Some((field, msg)) = field_detail {
-            ErrorResponse::new(ErrorCode::UserInputValidationError, Some(field), msg)
-        } else {
-            ErrorResponse::new(ErrorCode::ExpectedError, None, "Validation error occurred")
+        let mut errors = Vec::new();
+        collect_field_errors(&err, None, &mut errors);
+        errors.sort_by(|a, b| a.field.cmp(&b.field));
+
+        let Some(first) = errors.first() else {
+            return ErrorResponse::keyed(ErrorCode::ExpectedError, None, "validation-failed");
+        };
+
+        ErrorResponse {
+            errors: errors.clone(),
+            ..ErrorResponse::new(
+                ErrorCode::UserInputValidationError,
+                first.field.clone(),
+                first.message.clone(),
+            )
+        }
+    }
+}
+
+/// Flattens `errors`, including those of nested structs and list items, into `out` with the path
+/// of each field from the validated input.
+fn collect_field_errors(
+    errors: &ValidationErrors,
+    path: Option<&str>,
+    out: &mut Vec<FieldErrorResponse>,
+) {
+    for (key, kind) in errors.errors() {
+        let field = match (path, key.as_ref()) {
+            (_, STRUCT_ERRORS_KEY) => path.map(str::to_string),
+            (Some(path), key) => Some(format!("{path}.{key}")),
+            (None, key) => Some(key.to_string()),
+        };
+
+        match kind {
+            ValidationErrorsKind::Field(field_errors) => {
+                out.extend(field_errors.iter().map(|error| {
+                    // The rejected value is left out; it may be a secret.
+                    let params: Map<String, Value> = error
+                        .params
+                        .iter()
+                        .filter(|(name, _)| name.as_ref() != "value")
+                        .map(|(name, value)| (name.to_string(), value.clone()))
+                        .collect();
+                    let message = match &error.message {
+                        Some(message) => message.to_string(),
+                        None => validation_message(
+                            DEFAULT_LOCALE,
+                            field.as_deref(),
+                            &error.code,
+                            &params,
+                        ),
+                    };
+
+                    FieldErrorResponse {
+                        field: field.clone(),
+                        code: error.code.to_string(),
+                        message,
+                        params,
+                    }
+                }));
+            }
+            ValidationErrorsKind::Struct(nested) => {
+                collect_field_errors(nested, field.as_deref(), out);
+            }
+            ValidationErrorsKind::List(items) => {
+                for (index, nested) in items {
+                    let item = format!("{}[{index}]", field.as_deref().unwrap_or_default());
+                    collect_field_errors(nested, Some(&item), out);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use validator::Validate;
+
+    #[derive(Validate)]
+    struct Item {
+        #[validate(length(min = 1, message = "Name is required"))]
+        name: String,
+    }
+
+    #[derive(Validate)]
+    struct Form {
+        #[validate(length(
+            min = 3,
+            max = 16,
+            message = "Username must be between 3 and 16 characters"
+        ))]
+        username: String,
+        #[validate(range(min = 1, max = 28, message = "Day must be between 1 and 28"))]
+        day: u8,
+        #[validate(nested)]
+        item: Item,
+        #[validate(nested)]
+        items: Vec<Item>,
+    }
+
+    #[test]
+    fn validation_errors_report_every_field() {
+        let form = Form {
+            username: "al".into(),
+            day: 30,
+            item: Item { name: "".into() },
+            items: vec![Item { name: "ok".into() }, Item { name: "".into() }],
+        };
+
+        let err = ErrorResponse::from(form.validate().unwrap_err());
+
+        let fields: Vec<_> = err.errors.iter().map(|e| e.field.as_deref()).collect();
+        assert_eq!(
+            fields,
+            [
+                Some("day"),
+                Some("item.name"),
+                Some("items[1].name"),
+                Some("username")
+            ]
+        );
+        assert_eq!(err.field.as_deref(), Some("day"));
+        assert_eq!(err.message, "Day must be between 1 and 28");
+
+        let username = &err.errors[3];
+        assert_eq!(username.code, "length");
+        assert_eq!(username.params["min"], 3);
+        assert_eq!(username.params["max"], 16);
+        assert!(!username.params.contains_key("value"));
+    }
+
+    #[test]
+    fn errors_are_localized() {
+        let taken = ErrorResponse::keyed(
+            ErrorCode::UserInputValidationError,
+            None,
+            "profile-restore-username-taken",
+        )
+        .with_arg("username", "alice");
+        assert_eq!(
+            taken.message,
+            "Username alice is taken; pick another one to restore this profile"
+        );
+        assert_eq!(
+            taken.localize("pt-BR").message,
+            "O nome de usuário alice já está em uso; escolha outro para restaurar este perfil"
+        );
+
+        let missing = || {
+            ErrorResponse::keyed(
+                ErrorCode::SearchObjectNotFoundError,
+                Some("name".into()),
+                "backup-not-found",
+            )
+        };
+        assert_eq!(missing().localize("en-US").message, "Backup not found");
+        assert_eq!(missing().localize("pt-BR").message, "Backup não encontrado");
+
+        let not_a_backup = ErrorResponse::keyed(
+            ErrorCode::CorruptedFileError,
+            None,
+            "archive-not-this-format",
+        )
+        .with_arg("format", "nomorebeans-backup");
+        assert_eq!(not_a_backup.message, "This is not a NoMoreBeans backup");
+        assert_eq!(
+            not_a_backup.localize("pt-BR").message,
+            "Este não é um backup do NoMoreBeans"
+        );
+
+        // Messages written in the code stay in English and get the generic one elsewhere.
+        let custom = || ErrorResponse::new(ErrorCode::ResourceError, None, "Ledger is gone");
+        assert_eq!(custom().localize("en-US").message, "Ledger is gone");
+        assert_eq!(custom().localize("pt-BR").message, "Recurso não encontrado");
+
+        let form = Form {
+            username: "al".into(),
+            day: 1,
+            item: Item { name: "ok".into() },
+            items: Vec::new(),
+        };
+        let err = ErrorResponse::from(form.validate().unwrap_err()).localize("pt-BR");
+        assert_eq!(
+            err.message,
+            "O nome de usuário deve ter entre 3 e 16 caracteres"
+        );
+        assert_eq!(err.errors[0].message, err.message);
+    }
+
+    #[test]
+    fn every_error_code_has_a_message() {
+        for code in ErrorCode::ALL {
+            let message_id = code.message_id();
+            assert!(
+                catalogue::message(DEFAULT_LOCALE, &message_id, &Map::new()).is_some(),
+                "{message_id} is missing"
+            );
         }
+        assert_eq!(ErrorCode::IOError.message_id(), "error-io");
+        assert_eq!(ErrorCode::FileRWError.message_id(), "error-file-rw");
     }
 }
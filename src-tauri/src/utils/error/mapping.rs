@@ -42,6 +42,16 @@ macro_rules! error_codes {
             $($variant,)+
         }
 
+        impl ErrorCode {
+            pub const ALL: &'static [ErrorCode] = &[$(ErrorCode::$variant,)+];
+
+            pub fn name(self) -> &'static str {
+                match self {
+                    $(ErrorCode::$variant => stringify!($variant),)+
+                }
+            }
+        }
+
         impl From<ErrorCode> for u32 {
             fn from(code: ErrorCode) -> Self {
                 match code {
@@ -65,10 +75,18 @@ macro_rules! error_codes {
 error_codes! {
     UserInputValidationError  = Validation Client 01;
     SearchObjectNotFoundError = Validation Client 02;
+    UnsupportedImageError     = Validation Client 03; // Unknown format, animated or undecodable.
+    ImageTooLargeError        = Validation Client 04; // Dimensions or decoded size past the limits.
+    CorruptedFileError        = Validation Client 05; // Archive that is not ours, damaged or modified.
+    UnsupportedVersionError   = Validation Client 06; // Archive written by a newer build than the one running.
+    StaleDataError            = Validation Client 07; // Undo or redo of a change whose data was changed again since.
 
     ExpectedError = Service Server 01; // Used for known, server, unhandled errors. Example: database errors, IO errors, etc.
     DatabaseError = Service Server 02;
 
+    IncompatibleSchemaError = Service Critical 01; // The database was migrated by a newer build than the one running.
+    MigrationError          = Service Critical 02;
+
     ResourceError = Service Client 01; // Not tied to user input. Example: resource not found, etc.
 
     IOError     = IO Server 01;
@@ -77,6 +95,8 @@ error_codes! {
     NetworkError = IO Critical 01;
 
     InsufficientPrivilegesError = Permission Client 01;
+    TooManyAttemptsError        = Permission Client 02;
+    WrongPassphraseError        = Permission Client 03; // Encrypted file opened without its passphrase or with a wrong one.
 
     MisconfiguredPrivilegesError = Permission Server 01;
 
@@ -91,6 +111,23 @@ pub struct ErrorResponse {
     pub code_name: ErrorCode,
     pub code: u32,
 
-    pub field: Option<String>,
+    pub field: Option<String>, // Same as the first of `errors`, when there are any.
+    pub message: String,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<FieldErrorResponse>, // Every failed validation rule, sorted by field.
+
+    #[serde(skip)]
+    pub message_id: Option<&'static str>, // Catalogue message `message` was formatted from, if any.
+    #[serde(skip)]
+    pub message_args: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldErrorResponse {
+    pub field: Option<String>, // Path such as `profile_picture_crop.x` or `items[2].name`; None for the whole input.
+    pub code: String,          // Validation rule, such as `length`, `range` or `regex`.
     pub message: String,
+    pub params: serde_json::Map<String, serde_json::Value>, // Rule arguments, such as `min` and `max`.
 }
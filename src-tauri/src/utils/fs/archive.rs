@@ -0,0 +1,319 @@
+use crate::utils::{
+    crypto::encryption,
+    error::mapping::{ErrorCode, ErrorResponse},
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::BTreeMap,
+    fs::{self, File},
+    io::{Cursor, Read, Write},
+    path::{Path, PathBuf},
+};
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipArchive, ZipWriter};
+
+const MANIFEST_NAME: &str = "manifest.json";
+
+/// Kind of archive, recorded in its manifest so one kind cannot be passed off as another.
+pub struct ArchiveFormat {
+    pub name: &'static str,
+    pub version: u32,
+    pub max_entries: usize,
+    pub max_entry_size: u64,
+}
+
+// Profile archives come from other machines and other people; refuse them before inflating
+// anything huge.
+pub const PROFILE_ARCHIVE: ArchiveFormat = ArchiveFormat {
+    name: "nomorebeans-profile",
+    version: 1,
+    max_entries: 64,
+    max_entry_size: 16 * 1024 * 1024,
+};
+
+// Backups are written by the app itself and grow with the data.
+pub const BACKUP_ARCHIVE: ArchiveFormat = ArchiveFormat {
+    name: "nomorebeans-backup",
+    version: 1,
+    max_entries: 16 * 1024,
+    max_entry_size: 1024 * 1024 * 1024,
+};
+
+/// `manifest.json` of an archive. `checksums` holds the SHA-256 of every other file in it, so a
+/// damaged or edited archive is refused as a whole.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveManifest {
+    pub format: String,
+    pub version: u32,
+    pub app_version: String,
+    pub created_at: chrono::NaiveDateTime,
+    pub checksums: BTreeMap<String, String>,
+}
+
+/// Writes `files` and their manifest to `path` through a temporary file, so a failed export does
+/// not leave a partial archive behind. With a `passphrase` the whole archive is encrypted.
+pub fn write_archive(
+    format: &ArchiveFormat,
+    path: &Path,
+    files: &BTreeMap<String, Vec<u8>>,
+    passphrase: Option<&str>,
+) -> Result<(), ErrorResponse> {
+    let manifest = ArchiveManifest {
+        format: format.name.into(),
+        version: format.version,
+        app_version: env!("CARGO_PKG_VERSION").into(),
+        created_at: chrono::Utc::now().naive_utc(),
+        checksums: files
+            .iter()
+            .map(|(name, bytes)| (name.clone(), sha256_hex(bytes)))
+            .collect(),
+    };
+    let manifest = serde_json::to_vec_pretty(&manifest).map_err(|_| ErrorResponse::unhandled())?;
+
+    let mut bytes = zip_files(&manifest, files)?;
+    if let Some(passphrase) = passphrase {
+        bytes = encryption::encrypt(&bytes, passphrase)?;
+    }
+
+    let tmp_path = tmp_path(path);
+    let result = write_synced(&tmp_path, &bytes)
+        .and_then(|()| fs::rename(&tmp_path, path).map_err(ErrorResponse::from));
+
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result
+}
+
+/// Reads an archive made by [`write_archive`], refusing it unless the manifest is of `format`, no
+/// newer than its version and every checksum matches. Returns the files without the manifest.
+///
+/// Encrypted archives need their `passphrase`; it is ignored for plain ones.
+pub fn read_archive(
+    format: &ArchiveFormat,
+    path: &Path,
+    passphrase: Option<&str>,
+) -> Result<BTreeMap<String, Vec<u8>>, ErrorResponse> {
+    let mut bytes = fs::read(path)?;
+    if encryption::is_encrypted(&bytes) {
+        bytes = encryption::decrypt(&bytes, passphrase)?;
+    }
+
+    let mut zip = ZipArchive::new(Cursor::new(bytes)).map_err(|_| not_this_format(format))?;
+    if zip.len() > format.max_entries {
+        return Err(corrupted("archive-too-many-files"));
+    }
+
+    let mut files = BTreeMap::new();
+    for index in 0..zip.len() {
+        let mut entry = zip.by_index(index).map_err(|_| damaged())?;
+        if entry.is_dir() {
+            continue;
+        }
+
+        let name = entry.name().to_string();
+        let mut bytes = Vec::new();
+        (&mut entry)
+            .take(format.max_entry_size + 1)
+            .read_to_end(&mut bytes)
+            .map_err(|_| damaged())?;
+        if bytes.len() as u64 > format.max_entry_size {
+            return Err(corrupted("archive-file-too-large").with_arg("name", name.as_str()));
+        }
+
+        files.insert(name, bytes);
+    }
+
+    let manifest = files
+        .remove(MANIFEST_NAME)
+        .ok_or_else(|| not_this_format(format))?;
+    let manifest: ArchiveManifest =
+        serde_json::from_slice(&manifest).map_err(|_| corrupted("archive-manifest-unreadable"))?;
+
+    if manifest.format != format.name {
+        return Err(not_this_format(format));
+    }
+    if manifest.version > format.version {
+        return Err(ErrorResponse::keyed(
+            ErrorCode::UnsupportedVersionError,
+            None,
+            "archive-newer-version",
+        )
+        .with_arg("format", format.name)
+        .with_arg("version", manifest.app_version));
+    }
+
+    let intact = manifest.checksums.len() == files.len()
+        && files
+            .iter()
+            .all(|(name, bytes)| manifest.checksums.get(name) == Some(&sha256_hex(bytes)));
+    if !intact {
+        return Err(damaged());
+    }
+
+    Ok(files)
+}
+
+/// Whether the file at `path` is encrypted, judging from its first bytes.
+pub fn is_encrypted(path: &Path) -> Result<bool, ErrorResponse> {
+    let mut start = Vec::new();
+    File::open(path)?.take(16).read_to_end(&mut start)?;
+
+    Ok(encryption::is_encrypted(&start))
+}
+
+fn zip_files(manifest: &[u8], files: &BTreeMap<String, Vec<u8>>) -> Result<Vec<u8>, ErrorResponse> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let entries = std::iter::once((MANIFEST_NAME, manifest)).chain(
+        files
+            .iter()
+            .map(|(name, bytes)| (name.as_str(), bytes.as_slice())),
+    );
+
+    for (name, bytes) in entries {
+        // WebP is compressed already.
+        let method = if name.ends_with(".webp") {
+            CompressionMethod::Stored
+        } else {
+            CompressionMethod::Deflated
+        };
+
+        zip.start_file(
+            name,
+            SimpleFileOptions::default().compression_method(method),
+        )
+        .map_err(zip_error)?;
+        zip.write_all(bytes)?;
+    }
+
+    Ok(zip.finish().map_err(zip_error)?.into_inner())
+}
+
+fn write_synced(path: &Path, bytes: &[u8]) -> Result<(), ErrorResponse> {
+    let mut file = File::create(path)?;
+    file.write_all(bytes)?;
+    file.sync_all()?;
+    Ok(())
+}
+
+fn tmp_path(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    path.with_file_name(format!("{name}.tmp"))
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+fn corrupted(message_id: &'static str) -> ErrorResponse {
+    ErrorResponse::keyed(ErrorCode::CorruptedFileError, None, message_id)
+}
+
+fn not_this_format(format: &ArchiveFormat) -> ErrorResponse {
+    corrupted("archive-not-this-format").with_arg("format", format.name)
+}
+
+fn damaged() -> ErrorResponse {
+    corrupted("archive-damaged")
+}
+
+fn zip_error(err: zip::result::ZipError) -> ErrorResponse {
+    match err {
+        zip::result::ZipError::Io(err) => err.into(),
+        _ => ErrorResponse::unhandled(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir() -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("nomorebeans-archive-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn files() -> BTreeMap<String, Vec<u8>> {
+        BTreeMap::from([
+            (
+                "profile.json".to_string(),
+                br#"{"username":"alice"}"#.to_vec(),
+            ),
+            ("avatars/avatar.webp".to_string(), vec![1, 2, 3]),
+        ])
+    }
+
+    #[test]
+    fn archive_round_trips() {
+        let path = temp_dir().join("alice.zip");
+
+        write_archive(&PROFILE_ARCHIVE, &path, &files(), None).unwrap();
+
+        assert_eq!(
+            read_archive(&PROFILE_ARCHIVE, &path, None).unwrap(),
+            files()
+        );
+        assert!(!tmp_path(&path).exists());
+    }
+
+    #[test]
+    fn encrypted_archive_needs_its_passphrase() {
+        let path = temp_dir().join("alice.zip");
+
+        write_archive(&PROFILE_ARCHIVE, &path, &files(), Some("correct horse")).unwrap();
+
+        assert!(is_encrypted(&path).unwrap());
+        let err = read_archive(&PROFILE_ARCHIVE, &path, None).unwrap_err();
+        assert!(matches!(err.code_name, ErrorCode::WrongPassphraseError));
+        let read = read_archive(&PROFILE_ARCHIVE, &path, Some("correct horse")).unwrap();
+        assert_eq!(read, files());
+    }
+
+    #[test]
+    fn modified_archive_is_refused() {
+        let dir = temp_dir();
+        let original = dir.join("original.zip");
+        write_archive(&PROFILE_ARCHIVE, &original, &files(), None).unwrap();
+
+        let mut manifest = Vec::new();
+        ZipArchive::new(File::open(&original).unwrap())
+            .unwrap()
+            .by_name(MANIFEST_NAME)
+            .unwrap()
+            .read_to_end(&mut manifest)
+            .unwrap();
+
+        let mut edited = files();
+        edited.insert("profile.json".into(), br#"{"username":"mallory"}"#.to_vec());
+        let tampered = dir.join("tampered.zip");
+        fs::write(&tampered, zip_files(&manifest, &edited).unwrap()).unwrap();
+
+        let err = read_archive(&PROFILE_ARCHIVE, &tampered, None).unwrap_err();
+        assert!(matches!(err.code_name, ErrorCode::CorruptedFileError));
+    }
+
+    #[test]
+    fn other_formats_are_refused() {
+        let path = temp_dir().join("alice.zip");
+        write_archive(&PROFILE_ARCHIVE, &path, &files(), None).unwrap();
+
+        let err = read_archive(&BACKUP_ARCHIVE, &path, None).unwrap_err();
+        assert!(matches!(err.code_name, ErrorCode::CorruptedFileError));
+    }
+
+    #[test]
+    fn other_files_are_refused() {
+        let path = temp_dir().join("notes.zip");
+        fs::write(&path, "not a zip").unwrap();
+
+        let err = read_archive(&PROFILE_ARCHIVE, &path, None).unwrap_err();
+        assert!(matches!(err.code_name, ErrorCode::CorruptedFileError));
+    }
+}
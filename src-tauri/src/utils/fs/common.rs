@@ -2,10 +2,22 @@ use dirs::config_dir;
 use std::{
     io::{Error, ErrorKind, Result},
     path::PathBuf,
+    sync::RwLock,
 };
 
+/// Set from the settings at startup; profile pictures, backups and the SQLite file live here
+/// instead of the config dir when it is present.
+static DATA_DIR_OVERRIDE: RwLock<Option<PathBuf>> = RwLock::new(None);
+
 pub enum AppDirs {
     Pfps,
+    Backups,
+}
+
+pub fn set_data_dir_override(dir: Option<PathBuf>) {
+    *DATA_DIR_OVERRIDE
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = dir;
 }
 
 pub fn ensure_app_config_dir() -> Result<PathBuf> {
@@ -16,7 +28,22 @@ pub fn ensure_app_config_dir() -> Result<PathBuf> {
         ))?
         .join("nomorebeans");
 
-    let dirs_to_create = ["pfps"];
+    std::fs::create_dir_all(&base)?;
+    Ok(base)
+}
+
+pub fn ensure_app_data_dir() -> Result<PathBuf> {
+    let data_dir_override = DATA_DIR_OVERRIDE
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .clone();
+
+    let base = match data_dir_override {
+        Some(dir) => dir,
+        None => ensure_app_config_dir()?,
+    };
+
+    let dirs_to_create = ["pfps", "backups"];
 
     for dir in dirs_to_create.iter() {
         let dir_path = base.join(dir);
@@ -28,9 +55,10 @@ pub fn ensure_app_config_dir() -> Result<PathBuf> {
 }
 
 pub fn get_app_dir(app_dir: AppDirs) -> Result<PathBuf> {
-    let base = ensure_app_config_dir()?;
+    let base = ensure_app_data_dir()?;
     let dir_path = match app_dir {
         AppDirs::Pfps => base.join("pfps"),
+        AppDirs::Backups => base.join("backups"),
     };
     Ok(dir_path)
 }
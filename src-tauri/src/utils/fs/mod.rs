@@ -1,2 +1,4 @@
+pub mod archive;
 pub mod common;
 pub mod profile_picture;
+pub mod settings;
@@ -1,21 +1,388 @@
-use crate::utils::fs::common::{get_app_dir, AppDirs};
+use crate::utils::{
+    error::mapping::{ErrorCode, ErrorResponse},
+    fs::common::{get_app_dir, AppDirs},
+};
+use image::{
+    codecs::{gif::GifDecoder, png::PngDecoder, webp::WebPDecoder},
+    imageops::FilterType,
+    metadata::Orientation,
+    AnimationDecoder, DynamicImage, ImageDecoder, ImageError, ImageFormat, ImageReader, Limits,
+};
 use std::{
+    collections::HashSet,
     fs,
-    io::{Cursor, Error, ErrorKind, Result},
-    path::PathBuf,
+    io::{self, Cursor},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
 };
 
-pub fn save_profile_picture(bytes: &[u8]) -> Result<PathBuf> {
-    let config_dir = get_app_dir(AppDirs::Pfps)?;
-    let img = image::load_from_memory(bytes).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+pub const AVATAR_SIZE: u32 = 256;
+pub const AVATAR_VARIANT_SIZES: [u32; 2] = [64, 128];
 
-    let file_path = format!("{}.webp", uuid::Uuid::new_v4());
-    let full_path = config_dir.join(&file_path);
+// Uploads are written before their row is; anything younger than this may still get one.
+const ORPHAN_GRACE_PERIOD: Duration = Duration::from_secs(5 * 60);
 
-    let mut buf = Vec::new();
-    img.write_to(&mut Cursor::new(&mut buf), image::ImageFormat::WebP)
-        .map_err(|e| Error::new(ErrorKind::Other, e))?;
+// A 2MB upload can still describe a gigapixel image; refuse before allocating for it.
+const MAX_SOURCE_DIMENSION: u32 = 8192;
+const MAX_DECODE_ALLOC: u64 = 256 * 1024 * 1024;
+
+/// Area of the (already EXIF-rotated) source image to keep, in pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CropRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Decodes, orients and square-crops the upload, then stores `<uuid>.webp` at [`AVATAR_SIZE`]
+/// with a `<uuid>-<size>.webp` for each of [`AVATAR_VARIANT_SIZES`]. Returns the main file.
+pub fn save_profile_picture(
+    bytes: &[u8],
+    crop: Option<CropRect>,
+) -> Result<PathBuf, ErrorResponse> {
+    let image = crop_square(decode_avatar(bytes)?, crop)?;
+
+    let dir = get_app_dir(AppDirs::Pfps)?;
+    let full_path = dir.join(format!("{}.webp", uuid::Uuid::new_v4()));
+
+    // Encode everything first so a failure cannot leave only some of the sizes on disk.
+    let mut files = vec![(full_path.clone(), encode_webp(&image, AVATAR_SIZE)?)];
+    for size in AVATAR_VARIANT_SIZES {
+        files.push((variant_path(&full_path, size), encode_webp(&image, size)?));
+    }
+
+    for (index, (path, buf)) in files.iter().enumerate() {
+        if let Err(err) = fs::write(path, buf) {
+            for (written, _) in &files[..index] {
+                let _ = fs::remove_file(written);
+            }
+            return Err(err.into());
+        }
+    }
 
-    fs::write(&full_path, &buf)?;
     Ok(full_path)
 }
+
+/// Where the `size` pixel variant of an avatar lives: `<uuid>-<size>.webp` next to the original.
+pub fn variant_path(path: &Path, size: u32) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    path.with_file_name(format!("{stem}-{size}.webp"))
+}
+
+/// Removes an avatar and all of its size variants. Missing files are not an error.
+pub fn remove_profile_picture(path: &Path) -> io::Result<()> {
+    let variants = AVATAR_VARIANT_SIZES.map(|size| variant_path(path, size));
+
+    for file in std::iter::once(path).chain(variants.iter().map(PathBuf::as_path)) {
+        match fs::remove_file(file) {
+            Ok(()) => {}
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err),
+        }
+    }
+
+    Ok(())
+}
+
+/// Deletes files in the pfps dir whose main avatar is not in `referenced`, returning how many
+/// went. Files written within [`ORPHAN_GRACE_PERIOD`] are left alone.
+pub fn remove_unreferenced_profile_pictures(referenced: &HashSet<PathBuf>) -> io::Result<usize> {
+    remove_unreferenced_in(
+        &get_app_dir(AppDirs::Pfps)?,
+        referenced,
+        ORPHAN_GRACE_PERIOD,
+    )
+}
+
+fn remove_unreferenced_in(
+    dir: &Path,
+    referenced: &HashSet<PathBuf>,
+    grace_period: Duration,
+) -> io::Result<usize> {
+    let referenced_names: HashSet<_> = referenced
+        .iter()
+        .filter_map(|path| path.file_name())
+        .collect();
+    let now = SystemTime::now();
+    let mut removed = 0;
+
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+
+        if path.extension().is_none_or(|extension| extension != "webp") {
+            continue;
+        }
+
+        let recent = entry
+            .metadata()
+            .and_then(|metadata| metadata.modified())
+            .is_ok_and(|modified| now.duration_since(modified).unwrap_or_default() < grace_period);
+        if recent {
+            continue;
+        }
+
+        let main_name = format!("{}.webp", main_stem(&path));
+        if referenced_names.contains(std::ffi::OsStr::new(&main_name)) {
+            continue;
+        }
+
+        fs::remove_file(&path)?;
+        removed += 1;
+    }
+
+    Ok(removed)
+}
+
+/// `<uuid>` for both `<uuid>.webp` and `<uuid>-<size>.webp`.
+fn main_stem(path: &Path) -> String {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    match stem.rsplit_once('-') {
+        Some((main, size)) if AVATAR_VARIANT_SIZES.iter().any(|s| s.to_string() == size) => {
+            main.to_string()
+        }
+        _ => stem,
+    }
+}
+
+fn decode_avatar(bytes: &[u8]) -> Result<DynamicImage, ErrorResponse> {
+    let mut reader = ImageReader::new(Cursor::new(bytes)).with_guessed_format()?;
+    let format = reader
+        .format()
+        .ok_or_else(|| unsupported_image("image-unrecognized-format"))?;
+
+    if is_animated(bytes, format)? {
+        return Err(unsupported_image("image-animated"));
+    }
+
+    reader.limits(decode_limits());
+
+    let mut decoder = reader.into_decoder().map_err(image_error)?;
+    let orientation = decoder.orientation().unwrap_or(Orientation::NoTransforms);
+
+    let mut image = DynamicImage::from_decoder(decoder).map_err(image_error)?;
+    image.apply_orientation(orientation);
+
+    Ok(image)
+}
+
+fn decode_limits() -> Limits {
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_SOURCE_DIMENSION);
+    limits.max_image_height = Some(MAX_SOURCE_DIMENSION);
+    limits.max_alloc = Some(MAX_DECODE_ALLOC);
+    limits
+}
+
+fn is_animated(bytes: &[u8], format: ImageFormat) -> Result<bool, ErrorResponse> {
+    let animated = match format {
+        ImageFormat::Gif => {
+            let mut decoder = GifDecoder::new(Cursor::new(bytes)).map_err(image_error)?;
+            // Frames are drawn onto a canvas of the declared screen size, so check it first.
+            decoder.set_limits(decode_limits()).map_err(image_error)?;
+            decoder.into_frames().take(2).count() > 1
+        }
+        ImageFormat::Png => PngDecoder::new(Cursor::new(bytes))
+            .and_then(|decoder| decoder.is_apng())
+            .map_err(image_error)?,
+        ImageFormat::WebP => WebPDecoder::new(Cursor::new(bytes))
+            .map_err(image_error)?
+            .has_animation(),
+        _ => false,
+    };
+
+    Ok(animated)
+}
+
+/// Applies `crop` when given, then keeps the centered square of what is left.
+fn crop_square(image: DynamicImage, crop: Option<CropRect>) -> Result<DynamicImage, ErrorResponse> {
+    let crop = crop.unwrap_or(CropRect {
+        x: 0,
+        y: 0,
+        width: image.width(),
+        height: image.height(),
+    });
+
+    let fits = crop.width > 0
+        && crop.height > 0
+        && crop
+            .x
+            .checked_add(crop.width)
+            .is_some_and(|right| right <= image.width())
+        && crop
+            .y
+            .checked_add(crop.height)
+            .is_some_and(|bottom| bottom <= image.height());
+    if !fits {
+        return Err(ErrorResponse::keyed(
+            ErrorCode::UserInputValidationError,
+            Some("profile_picture_crop".into()),
+            "image-crop-outside",
+        ));
+    }
+
+    let side = crop.width.min(crop.height);
+    let x = crop.x + (crop.width - side) / 2;
+    let y = crop.y + (crop.height - side) / 2;
+
+    Ok(image.crop_imm(x, y, side, side))
+}
+
+fn encode_webp(image: &DynamicImage, size: u32) -> Result<Vec<u8>, ErrorResponse> {
+    let mut buf = Vec::new();
+
+    image
+        .resize_exact(size, size, FilterType::Lanczos3)
+        .write_to(&mut Cursor::new(&mut buf), ImageFormat::WebP)
+        .map_err(|_| ErrorResponse::unhandled())?;
+
+    Ok(buf)
+}
+
+fn unsupported_image(message_id: &'static str) -> ErrorResponse {
+    ErrorResponse::keyed(
+        ErrorCode::UnsupportedImageError,
+        Some("profile_picture_bytes".into()),
+        message_id,
+    )
+}
+
+fn image_error(err: ImageError) -> ErrorResponse {
+    match err {
+        ImageError::Limits(_) => ErrorResponse::keyed(
+            ErrorCode::ImageTooLargeError,
+            Some("profile_picture_bytes".into()),
+            "image-too-many-pixels",
+        )
+        .with_arg("max", MAX_SOURCE_DIMENSION),
+        ImageError::IoError(err) => err.into(),
+        _ => unsupported_image("image-undecodable"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{codecs::gif::GifEncoder, Frame, RgbaImage};
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let mut buf = Vec::new();
+        DynamicImage::new_rgb8(width, height)
+            .write_to(&mut Cursor::new(&mut buf), ImageFormat::Png)
+            .unwrap();
+        buf
+    }
+
+    #[test]
+    fn crop_square_keeps_centered_square() {
+        let image = DynamicImage::new_rgb8(300, 200);
+
+        let cropped = crop_square(image.clone(), None).unwrap();
+        assert_eq!((cropped.width(), cropped.height()), (200, 200));
+
+        let rect = CropRect {
+            x: 10,
+            y: 20,
+            width: 50,
+            height: 80,
+        };
+        let cropped = crop_square(image, Some(rect)).unwrap();
+        assert_eq!((cropped.width(), cropped.height()), (50, 50));
+    }
+
+    #[test]
+    fn crop_square_rejects_crop_outside_image() {
+        let rect = CropRect {
+            x: 250,
+            y: 0,
+            width: 100,
+            height: 100,
+        };
+
+        let err = crop_square(DynamicImage::new_rgb8(300, 200), Some(rect)).unwrap_err();
+
+        assert!(matches!(err.code_name, ErrorCode::UserInputValidationError));
+        assert_eq!(err.field.as_deref(), Some("profile_picture_crop"));
+    }
+
+    #[test]
+    fn decode_avatar_rejects_oversized_image() {
+        let err = decode_avatar(&png(MAX_SOURCE_DIMENSION + 1, 1)).unwrap_err();
+
+        assert!(matches!(err.code_name, ErrorCode::ImageTooLargeError));
+    }
+
+    #[test]
+    fn decode_avatar_rejects_oversized_gif_canvas() {
+        // A single 1x1 frame on a declared 65535x65535 screen.
+        let gif = [
+            b"GIF89a".as_slice(),
+            &[0xff, 0xff, 0xff, 0xff, 0x80, 0x00, 0x00],
+            &[0x00, 0x00, 0x00, 0xff, 0xff, 0xff],
+            &[0x2c, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x01, 0x00, 0x00],
+            &[0x02, 0x02, 0x44, 0x01, 0x00, 0x3b],
+        ]
+        .concat();
+
+        let err = decode_avatar(&gif).unwrap_err();
+
+        assert!(matches!(err.code_name, ErrorCode::ImageTooLargeError));
+    }
+
+    #[test]
+    fn decode_avatar_rejects_animation() {
+        let mut buf = Vec::new();
+        {
+            let mut encoder = GifEncoder::new(&mut buf);
+            let frames = (0..2).map(|_| Frame::new(RgbaImage::new(4, 4)));
+            encoder.encode_frames(frames).unwrap();
+        }
+
+        let err = decode_avatar(&buf).unwrap_err();
+
+        assert!(matches!(err.code_name, ErrorCode::UnsupportedImageError));
+    }
+
+    #[test]
+    fn decode_avatar_rejects_garbage() {
+        let err = decode_avatar(b"definitely not an image").unwrap_err();
+
+        assert!(matches!(err.code_name, ErrorCode::UnsupportedImageError));
+    }
+
+    #[test]
+    fn remove_unreferenced_keeps_referenced_avatars_and_variants() {
+        let dir = std::env::temp_dir().join(format!("nomorebeans-pfps-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let kept = dir.join("kept.webp");
+        let orphan = dir.join("orphan.webp");
+        for path in [&kept, &orphan] {
+            fs::write(path, b"").unwrap();
+            for size in AVATAR_VARIANT_SIZES {
+                fs::write(variant_path(path, size), b"").unwrap();
+            }
+        }
+        fs::write(dir.join("notes.txt"), b"").unwrap();
+
+        let referenced = HashSet::from([kept.clone()]);
+        let removed = remove_unreferenced_in(&dir, &referenced, Duration::ZERO).unwrap();
+
+        assert_eq!(removed, 1 + AVATAR_VARIANT_SIZES.len());
+        assert!(kept.exists() && variant_path(&kept, 64).exists());
+        assert!(!orphan.exists() && !variant_path(&orphan, 64).exists());
+        assert!(dir.join("notes.txt").exists());
+
+        let removed = remove_unreferenced_in(&dir, &HashSet::new(), ORPHAN_GRACE_PERIOD).unwrap();
+        assert_eq!(removed, 0);
+    }
+}
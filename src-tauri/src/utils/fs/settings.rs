@@ -0,0 +1,31 @@
+use crate::utils::fs::common::ensure_app_config_dir;
+use std::{
+    fs,
+    io::{ErrorKind, Result},
+    path::{Path, PathBuf},
+};
+
+const SETTINGS_FILE_NAME: &str = "settings.toml";
+
+/// Settings always live in the config dir, even when the data dir is overridden, since the
+/// override itself is stored in them.
+pub fn settings_path() -> Result<PathBuf> {
+    Ok(ensure_app_config_dir()?.join(SETTINGS_FILE_NAME))
+}
+
+/// Returns `None` when the file does not exist yet.
+pub fn read_settings_file(path: &Path) -> Result<Option<String>> {
+    match fs::read_to_string(path) {
+        Ok(contents) => Ok(Some(contents)),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// Writes through a temporary file so a crash mid-write cannot leave a truncated settings file.
+pub fn write_settings_file(path: &Path, contents: &str) -> Result<()> {
+    let tmp_path = path.with_extension("toml.tmp");
+
+    fs::write(&tmp_path, contents)?;
+    fs::rename(&tmp_path, path)
+}
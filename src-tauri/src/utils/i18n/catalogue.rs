@@ -0,0 +1,166 @@
+use fluent_bundle::{concurrent::FluentBundle, FluentArgs, FluentResource, FluentValue};
+use serde_json::{Map, Value};
+use std::sync::LazyLock;
+use unic_langid::LanguageIdentifier;
+
+/// Locale of the messages written in the code, and the one used when no other matches.
+pub const DEFAULT_LOCALE: &str = "en";
+
+// The default locale goes first.
+const SOURCES: &[(&str, &str)] = &[
+    ("en", include_str!("../../../locales/en/errors.ftl")),
+    ("pt-BR", include_str!("../../../locales/pt-BR/errors.ftl")),
+];
+
+struct Catalogue {
+    language: LanguageIdentifier,
+    bundle: FluentBundle<FluentResource>,
+}
+
+static CATALOGUES: LazyLock<Vec<Catalogue>> = LazyLock::new(|| {
+    SOURCES
+        .iter()
+        .map(|(tag, source)| {
+            let language: LanguageIdentifier = tag.parse().unwrap();
+            let resource = FluentResource::try_new(source.to_string())
+                .unwrap_or_else(|(_, errors)| panic!("Invalid {tag} catalogue: {errors:?}"));
+
+            let mut bundle = FluentBundle::new_concurrent(vec![language.clone()]);
+            // Bidi isolation marks would end up in plain-text error messages.
+            bundle.set_use_isolating(false);
+            bundle
+                .add_resource(resource)
+                .unwrap_or_else(|errors| panic!("Invalid {tag} catalogue: {errors:?}"));
+
+            Catalogue { language, bundle }
+        })
+        .collect()
+});
+
+/// Formats message `id` in the catalogue closest to `locale`, or in the default one when that
+/// catalogue lacks it. `args` are the message arguments; numbers keep their plural forms.
+pub fn message(locale: &str, id: &str, args: &Map<String, Value>) -> Option<String> {
+    format(catalogue(locale), id, args).or_else(|| format(&CATALOGUES[0], id, args))
+}
+
+/// Whether `locale` resolves to the catalogue of [`DEFAULT_LOCALE`], whose messages the code
+/// already carries.
+pub fn is_default(locale: &str) -> bool {
+    std::ptr::eq(catalogue(locale), &CATALOGUES[0])
+}
+
+/// Matches the whole tag first (`pt-BR`), then the language alone (`pt-PT` gets `pt-BR`).
+fn catalogue(locale: &str) -> &'static Catalogue {
+    let Ok(wanted) = locale.parse::<LanguageIdentifier>() else {
+        return &CATALOGUES[0];
+    };
+
+    CATALOGUES
+        .iter()
+        .find(|catalogue| catalogue.language == wanted)
+        .or_else(|| {
+            CATALOGUES
+                .iter()
+                .find(|catalogue| catalogue.language.language == wanted.language)
+        })
+        .unwrap_or(&CATALOGUES[0])
+}
+
+fn format(catalogue: &Catalogue, id: &str, args: &Map<String, Value>) -> Option<String> {
+    let pattern = catalogue.bundle.get_message(id)?.value()?;
+
+    let mut fluent_args = FluentArgs::new();
+    for (name, value) in args {
+        fluent_args.set(name.as_str(), fluent_value(value));
+    }
+
+    let mut errors = Vec::new();
+    let text = catalogue
+        .bundle
+        .format_pattern(pattern, Some(&fluent_args), &mut errors);
+    if !errors.is_empty() {
+        log::warn!("Message {id} of {}: {errors:?}", catalogue.language);
+    }
+
+    Some(text.into_owned())
+}
+
+fn fluent_value(value: &Value) -> FluentValue<'_> {
+    match value {
+        Value::Number(number) => match number.as_i64() {
+            Some(number) => number.into(),
+            None => number.as_f64().unwrap_or_default().into(),
+        },
+        Value::String(text) => text.as_str().into(),
+        other => other.to_string().into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn args(value: Value) -> Map<String, Value> {
+        value.as_object().cloned().unwrap_or_default()
+    }
+
+    fn ids(source: &str) -> Vec<&str> {
+        source
+            .lines()
+            .filter(|line| line.starts_with(|c: char| c.is_ascii_alphabetic()))
+            .filter_map(|line| line.split_once(" =").map(|(id, _)| id))
+            .collect()
+    }
+
+    #[test]
+    fn messages_resolve_with_arguments_and_plurals() {
+        let range = args(json!({ "min": 3, "max": 16 }));
+        assert_eq!(
+            message("en", "validation-username-length", &range).as_deref(),
+            Some("Username must be between 3 and 16 characters")
+        );
+        assert_eq!(
+            message("pt-BR", "validation-username-length", &range).as_deref(),
+            Some("O nome de usuário deve ter entre 3 e 16 caracteres")
+        );
+
+        let one = args(json!({ "seconds": 1 }));
+        let many = args(json!({ "seconds": 30 }));
+        assert_eq!(
+            message("en-US", "profile-too-many-attempts", &one).as_deref(),
+            Some("Too many failed attempts. Try again in 1 second")
+        );
+        assert_eq!(
+            message("pt", "profile-too-many-attempts", &many).as_deref(),
+            Some("Tentativas malsucedidas demais. Tente novamente em 30 segundos")
+        );
+    }
+
+    #[test]
+    fn unknown_locales_fall_back_to_the_default() {
+        assert!(is_default("en-GB"));
+        assert!(is_default("fr"));
+        assert!(is_default("not a locale"));
+        assert!(!is_default("pt-PT"));
+        assert_eq!(
+            message("fr", "profile-not-found", &Map::new()).as_deref(),
+            Some("Profile not found")
+        );
+        assert_eq!(message("en", "no-such-message", &Map::new()), None);
+    }
+
+    #[test]
+    fn catalogues_have_the_same_messages() {
+        let default = ids(SOURCES[0].1);
+        assert!(!default.is_empty());
+
+        for (tag, source) in &SOURCES[1..] {
+            assert_eq!(
+                ids(source),
+                default,
+                "{tag} catalogue differs from {DEFAULT_LOCALE}"
+            );
+        }
+    }
+}
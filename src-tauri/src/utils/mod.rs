@@ -1,3 +1,5 @@
+pub mod crypto;
 pub mod db;
 pub mod error;
 pub mod fs;
+pub mod i18n;
@@ -0,0 +1,10 @@
+import { invoke } from '@tauri-apps/api/core';
+import type { AnomalyReportResponse } from '../api/types/anomaly';
+import type { HistoricalTransactionRequest } from '../api/types/subscription';
+
+// Pass the profile's transaction history; needs an active profile.
+export async function getAnomalies(
+    history: HistoricalTransactionRequest[]
+): Promise<AnomalyReportResponse> {
+    return await invoke<AnomalyReportResponse>('get_anomalies', { history });
+}
@@ -0,0 +1,7 @@
+import { invoke } from '@tauri-apps/api/core';
+import type { AuditEventResponse, AuditLogFilter } from '../api/types/audit';
+
+// Newest first. Needs an active profile.
+export async function getAuditLog(filter?: AuditLogFilter): Promise<AuditEventResponse[]> {
+    return await invoke<AuditEventResponse[]>('get_audit_log', { filter });
+}
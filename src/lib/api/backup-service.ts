@@ -0,0 +1,17 @@
+import { invoke } from '@tauri-apps/api/core';
+import type { BackupResponse } from '../api/types/backup';
+
+export async function listBackups(): Promise<BackupResponse[]> {
+    return await invoke<BackupResponse[]>('list_backups');
+}
+
+// Kept in memory only; backups taken while it is set are encrypted with it. Setting it and
+// restoring need an unlocked session.
+export async function setBackupPassphrase(passphrase: string | null): Promise<void> {
+    await invoke('set_backup_passphrase', { passphrase });
+}
+
+// Replaces all data and leaves the active profile; the data it replaced is backed up first.
+export async function restoreBackup(name: string, passphrase?: string): Promise<void> {
+    await invoke('restore_backup', { name, passphrase });
+}
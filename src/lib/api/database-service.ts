@@ -0,0 +1,15 @@
+import { invoke } from '@tauri-apps/api/core';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+import type { DatabaseStatus } from '../api/types/database';
+
+export const DATABASE_STATUS_EVENT = 'database-status-changed';
+
+export async function getDatabaseStatus(): Promise<DatabaseStatus> {
+    return await invoke<DatabaseStatus>('get_database_status');
+}
+
+export async function onDatabaseStatusChanged(
+    callback: (status: DatabaseStatus) => void
+): Promise<UnlistenFn> {
+    return await listen<DatabaseStatus>(DATABASE_STATUS_EVENT, (event) => callback(event.payload));
+}
@@ -0,0 +1,47 @@
+import { invoke } from '@tauri-apps/api/core';
+import type {
+    BudgetAlertRuleResponse,
+    CategoryBudgetRequest,
+    NotificationResponse,
+    RaisedNotificationResponse,
+    SetBudgetAlertRuleRequest
+} from '../api/types/notification';
+
+// Newest first. Needs an active profile, like every call below.
+export async function getNotifications(unreadOnly?: boolean): Promise<NotificationResponse[]> {
+    return await invoke<NotificationResponse[]>('get_notifications', { unreadOnly });
+}
+
+export async function setNotificationRead(
+    id: number,
+    read: boolean
+): Promise<NotificationResponse> {
+    return await invoke<NotificationResponse>('set_notification_read', { id, read });
+}
+
+// Resolves to how many notifications were unread.
+export async function markAllNotificationsRead(): Promise<number> {
+    return await invoke<number>('mark_all_notifications_read');
+}
+
+export async function getBudgetAlertRules(): Promise<BudgetAlertRuleResponse[]> {
+    return await invoke<BudgetAlertRuleResponse[]>('get_budget_alert_rules');
+}
+
+export async function setBudgetAlertRule(
+    rule: SetBudgetAlertRuleRequest
+): Promise<BudgetAlertRuleResponse> {
+    return await invoke<BudgetAlertRuleResponse>('set_budget_alert_rule', { rule });
+}
+
+export async function deleteBudgetAlertRule(categoryId: number): Promise<void> {
+    await invoke('delete_budget_alert_rule', { categoryId });
+}
+
+// Call after recording or importing transactions, with the categories they touched. Show the
+// raised notifications with `notifyDesktop` as desktop notifications too.
+export async function checkBudgets(
+    budgets: CategoryBudgetRequest[]
+): Promise<RaisedNotificationResponse[]> {
+    return await invoke<RaisedNotificationResponse[]>('check_budgets', { budgets });
+}
@@ -0,0 +1,12 @@
+import { invoke } from '@tauri-apps/api/core';
+import type { ProfilePreferences, SetPreferencesRequest } from '../api/types/preferences';
+
+export async function getPreferences(): Promise<ProfilePreferences> {
+    return await invoke<ProfilePreferences>('get_preferences');
+}
+
+export async function setPreferences(
+    preferences: SetPreferencesRequest
+): Promise<ProfilePreferences> {
+    return await invoke<ProfilePreferences>('set_preferences', { preferences });
+}
@@ -1,56 +1,96 @@
 import { invoke } from '@tauri-apps/api/core';
 import type {
     CreateProfileRequest,
+    DeletedProfileResponse,
     GetProfileResponse,
+    RestoreProfileRequest,
+    SetProfileSecretRequest,
     UpdateProfileRequest
 } from '../api/types/profile';
-import { AVATAR_DATA_URI_PREFIX } from '$lib/constants';
 import { fileToBytes } from '$lib/utils';
 
-function transformProfileAvatar(profile: GetProfileResponse): GetProfileResponse {
-    return {
-        ...profile,
-        avatar: profile.avatar ? `${AVATAR_DATA_URI_PREFIX}${profile.avatar}` : undefined
-    };
-}
-
 export async function getProfiles(): Promise<GetProfileResponse[]> {
-    const profiles = await invoke<GetProfileResponse[]>('get_profiles');
-    return profiles.map(transformProfileAvatar);
+    return await invoke<GetProfileResponse[]>('get_profiles');
 }
 
 export async function createProfile(profile: CreateProfileRequest): Promise<GetProfileResponse> {
-    const { username, displayName, profilePicture } = profile;
+    const { username, displayName, profilePicture, profilePictureCrop } = profile;
 
-    const result = await invoke<GetProfileResponse>('create_profile', {
+    return await invoke<GetProfileResponse>('create_profile', {
         profile: {
             username,
             displayName: displayName?.trim() === '' ? undefined : displayName,
-            profilePictureBytes: profilePicture ? await fileToBytes(profilePicture) : undefined
+            profilePictureBytes: profilePicture ? await fileToBytes(profilePicture) : undefined,
+            profilePictureCrop: profilePicture ? profilePictureCrop : undefined
         }
     });
+}
 
-    return transformProfileAvatar(result);
+// Starts an unlocked session on the profile, like selectProfile with a secret. Repeated wrong
+// secrets fail with TooManyAttemptsError for a growing wait.
+export async function unlockProfile(id: number, secret: string): Promise<GetProfileResponse> {
+    return await invoke<GetProfileResponse>('unlock_profile', { id, unlock: { secret } });
 }
 
-export async function updateProfile(
-    id: number,
-    profile: UpdateProfileRequest
-): Promise<GetProfileResponse> {
-    const { username, displayName, profilePicture } = profile;
+export async function getActiveProfile(): Promise<GetProfileResponse> {
+    return await invoke<GetProfileResponse>('get_active_profile');
+}
 
-    const result = await invoke<GetProfileResponse>('update_profile', {
-        id,
+export async function updateProfile(profile: UpdateProfileRequest): Promise<GetProfileResponse> {
+    const { username, displayName, profilePicture, profilePictureCrop } = profile;
+
+    return await invoke<GetProfileResponse>('update_profile', {
         profile: {
             username: username?.trim() === '' ? undefined : username,
-            displayName: displayName?.trim() === '' ? undefined : displayName,
-            profilePictureBytes: profilePicture ? await fileToBytes(profilePicture) : undefined
+            displayName: displayName?.trim() === '' ? null : displayName,
+            profilePictureBytes: profilePicture
+                ? await fileToBytes(profilePicture)
+                : profilePicture,
+            profilePictureCrop: profilePicture ? profilePictureCrop : undefined
         }
     });
+}
 
-    return transformProfileAvatar(result);
+export async function deleteProfile(): Promise<void> {
+    await invoke('delete_profile');
 }
 
-export async function deleteProfile(id: number): Promise<void> {
-    await invoke('delete_profile', { id });
+export async function getDeletedProfiles(): Promise<DeletedProfileResponse[]> {
+    return await invoke<DeletedProfileResponse[]>('get_deleted_profiles');
+}
+
+// Restoring and purging need an unlocked session, though the profile is named by id.
+export async function restoreProfile(
+    id: number,
+    restore: RestoreProfileRequest = {}
+): Promise<GetProfileResponse> {
+    return await invoke<GetProfileResponse>('restore_profile', {
+        id,
+        restore: {
+            username: restore.username?.trim() === '' ? undefined : restore.username
+        }
+    });
+}
+
+export async function purgeProfile(id: number): Promise<void> {
+    await invoke('purge_profile', { id });
+}
+
+// Paths come from the dialog plugin; the archive holds the active profile.
+// With a passphrase the archive is encrypted and importing it asks for the same one.
+export async function exportProfile(path: string, passphrase?: string): Promise<void> {
+    await invoke('export_profile', { path, passphrase });
+}
+
+export async function importProfile(
+    path: string,
+    passphrase?: string
+): Promise<GetProfileResponse> {
+    return await invoke<GetProfileResponse>('import_profile', { path, passphrase });
+}
+
+export async function setProfileSecret(
+    secret: SetProfileSecretRequest | null
+): Promise<GetProfileResponse> {
+    return await invoke<GetProfileResponse>('set_profile_secret', { secret });
 }
@@ -0,0 +1,26 @@
+import { invoke } from '@tauri-apps/api/core';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+import type { GetProfileResponse } from '../api/types/profile';
+import type { Session } from '../api/types/session';
+
+export const SESSION_CHANGED_EVENT = 'session-changed';
+
+export async function selectProfile(id: number, secret?: string): Promise<GetProfileResponse> {
+    return await invoke<GetProfileResponse>('select_profile', { id, secret });
+}
+
+export async function leaveProfile(): Promise<void> {
+    await invoke('leave_profile');
+}
+
+export async function getSession(): Promise<Session> {
+    return await invoke<Session>('get_session');
+}
+
+export async function touchSession(): Promise<void> {
+    await invoke('touch_session');
+}
+
+export async function onSessionChanged(callback: (session: Session) => void): Promise<UnlistenFn> {
+    return await listen<Session>(SESSION_CHANGED_EVENT, (event) => callback(event.payload));
+}
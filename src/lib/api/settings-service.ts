@@ -0,0 +1,11 @@
+import { invoke } from '@tauri-apps/api/core';
+import type { AppSettings, GetSettingsResponse } from '../api/types/settings';
+
+export async function getSettings(): Promise<GetSettingsResponse> {
+    return await invoke<GetSettingsResponse>('get_settings');
+}
+
+// Needs an unlocked session. Without a reachable database, edit settings.toml instead.
+export async function updateSettings(settings: AppSettings): Promise<GetSettingsResponse> {
+    return await invoke<GetSettingsResponse>('update_settings', { settings });
+}
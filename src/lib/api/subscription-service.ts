@@ -0,0 +1,18 @@
+import { invoke } from '@tauri-apps/api/core';
+import type {
+    ConfirmSubscriptionRequest,
+    HistoricalTransactionRequest,
+    ScheduledTransactionResponse
+} from '../api/types/subscription';
+
+// Pass the transactions the subscription was detected in. Fails with SearchObjectNotFoundError
+// when they no longer show it, such as after it was cancelled.
+export async function confirmSubscription(
+    history: HistoricalTransactionRequest[],
+    subscription: ConfirmSubscriptionRequest
+): Promise<ScheduledTransactionResponse> {
+    return await invoke<ScheduledTransactionResponse>('confirm_subscription', {
+        history,
+        subscription
+    });
+}
@@ -0,0 +1,24 @@
+export type AnomalyScope = 'category' | 'payee';
+
+// `amount` and `typicalAmount` are spending in cents; `score` is the modified z-score.
+export type TransactionAnomaly = {
+    transactionId: number;
+    scope: AnomalyScope;
+    amount: number;
+    typicalAmount: number;
+    score: number;
+};
+
+// `month` is its first day.
+export type MonthlyCategoryAnomaly = {
+    categoryId: number;
+    month: string;
+    spent: number;
+    trailingAverage: number;
+    deviationPercent: number;
+};
+
+export type AnomalyReportResponse = {
+    transactions: TransactionAnomaly[];
+    months: MonthlyCategoryAnomaly[];
+};
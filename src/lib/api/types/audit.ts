@@ -0,0 +1,28 @@
+// 'database' events are backup restores, which replace every table at once; their entityId is 0.
+export type AuditEntity = 'profile' | 'database';
+
+export type AuditOperation = 'create' | 'update' | 'delete' | 'restore' | 'purge';
+
+export type AuditEventResponse = {
+    id: number;
+    createdAt: string;
+    actorProfileId: number | null;
+    entity: AuditEntity;
+    entityId: number;
+    operation: AuditOperation;
+    oldValues: Record<string, unknown> | null;
+    newValues: Record<string, unknown> | null;
+};
+
+// Every given condition must match. `until` is exclusive; pass the last id seen as `beforeId` for
+// the next page. `limit` defaults to 100, at most 500.
+export type AuditLogFilter = {
+    actorProfileId?: number;
+    entity?: AuditEntity;
+    entityId?: number;
+    operation?: AuditOperation;
+    from?: string;
+    until?: string;
+    beforeId?: number;
+    limit?: number;
+};
@@ -0,0 +1,6 @@
+export type BackupResponse = {
+    name: string;
+    createdAt: string;
+    size: number;
+    encrypted: boolean;
+};
@@ -0,0 +1 @@
+export type DatabaseStatus = 'connecting' | 'connected' | 'disconnected' | 'incompatible';
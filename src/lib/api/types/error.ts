@@ -1,7 +1,18 @@
+export type FieldError = {
+    // Path such as `profile_picture_crop.x` or `items[2].name`; null for the whole input.
+    field: string | null;
+    code: string;
+    message: string;
+    params: Record<string, unknown>;
+};
+
 export type ErrorResponse = {
     codeName: string;
     code: number;
 
+    // The first of `errors` when there are any, for callers that show one message.
     field?: string;
-    message: string;
+    message: string; // In the locale of the selected profile.
+
+    errors?: FieldError[];
 };
@@ -0,0 +1,47 @@
+export type NotificationKind = 'budget_threshold';
+
+export type BudgetThreshold = 'warning' | 'reached';
+
+// Payload of 'budget_threshold' notifications; amounts are in cents, `month` is its first day.
+export type BudgetThresholdPayload = {
+    categoryId: number;
+    month: string;
+    threshold: BudgetThreshold;
+    warnAtPercent: number;
+    allocated: number;
+    spent: number;
+};
+
+export type NotificationResponse = {
+    id: number;
+    createdAt: string;
+    kind: NotificationKind;
+    payload: BudgetThresholdPayload;
+    readAt: string | null;
+};
+
+export type RaisedNotificationResponse = {
+    notification: NotificationResponse;
+    notifyDesktop: boolean;
+};
+
+// Spending of one category over a budget month, in cents; `month` is its first day.
+export type CategoryBudgetRequest = {
+    categoryId: number;
+    month: string;
+    allocated: number;
+    spent: number;
+};
+
+export type BudgetAlertRuleResponse = {
+    categoryId: number;
+    warnAtPercent: number;
+    notifyDesktop: boolean;
+};
+
+// `warnAtPercent` defaults to 80, between 1 and 99. Reaching the whole allocation always notifies.
+export type SetBudgetAlertRuleRequest = {
+    categoryId: number;
+    warnAtPercent?: number;
+    notifyDesktop?: boolean;
+};
@@ -0,0 +1,27 @@
+import type { ProfileSelectionLayoutMode } from './settings';
+
+export type Theme = 'system' | 'light' | 'dark';
+
+export type DateFormat = 'locale' | 'yyyy-mm-dd' | 'dd/mm/yyyy' | 'mm/dd/yyyy';
+
+export type Weekday =
+    | 'monday'
+    | 'tuesday'
+    | 'wednesday'
+    | 'thursday'
+    | 'friday'
+    | 'saturday'
+    | 'sunday';
+
+export type ProfilePreferences = {
+    theme: Theme;
+    layoutMode: ProfileSelectionLayoutMode;
+    locale: string;
+    dateFormat: DateFormat;
+    baseCurrency: string;
+    firstDayOfWeek: Weekday;
+    budgetMonthStartDay: number;
+};
+
+// Only the given preferences change.
+export type SetPreferencesRequest = Partial<ProfilePreferences>;
@@ -3,16 +3,48 @@ export type GetProfileResponse = {
     username: string;
     displayName?: string;
     avatar?: string;
+    secretKind?: ProfileSecretKind;
+    locked: boolean;
+};
+
+export type DeletedProfileResponse = {
+    id: number;
+    username?: string;
+    displayName?: string;
+    avatar?: string;
+    deletedAt: string;
+    purgeAt?: string;
+};
+
+export type ProfileSecretKind = 'pin' | 'passphrase';
+
+export type ProfilePictureCrop = {
+    x: number;
+    y: number;
+    width: number;
+    height: number;
 };
 
 export type CreateProfileRequest = {
     username: string;
     displayName?: string;
     profilePicture?: File;
+    profilePictureCrop?: ProfilePictureCrop;
 };
 
+// Leave a field out to keep it; `null` clears the display name or removes the picture.
 export type UpdateProfileRequest = {
     username?: string;
-    displayName?: string;
-    profilePicture?: File;
+    displayName?: string | null;
+    profilePicture?: File | null;
+    profilePictureCrop?: ProfilePictureCrop;
+};
+
+export type RestoreProfileRequest = {
+    username?: string;
+};
+
+export type SetProfileSecretRequest = {
+    kind: ProfileSecretKind;
+    secret: string;
 };
@@ -0,0 +1,5 @@
+export type Session = {
+    profileId?: number;
+    locked: boolean;
+    unlockedAt?: string;
+};
@@ -5,3 +5,29 @@ export function toggleLayoutMode(
 ): ProfileSelectionLayoutMode {
     return currentMode === 'grid' ? 'list' : 'grid';
 }
+
+export type DatabaseBackend = 'sqlite' | 'postgres';
+
+export type LogLevel = 'error' | 'warn' | 'info' | 'debug' | 'trace';
+
+export type AppSettings = {
+    databaseBackend: DatabaseBackend;
+    databaseUrl?: string;
+    databasePoolSize: number;
+    logLevel: LogLevel;
+    defaultLocale: string;
+    defaultCurrency: string;
+    sessionIdleMinutes: number;
+    trashRetentionDays: number;
+    backupKeepDaily: number;
+    backupKeepWeekly: number;
+    backupKeepMonthly: number;
+    backupEncrypted: boolean;
+    dataDir?: string;
+};
+
+export type GetSettingsResponse = {
+    settings: AppSettings;
+    overridden: (keyof AppSettings)[];
+    restartRequired: boolean;
+};
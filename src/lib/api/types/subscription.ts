@@ -0,0 +1,28 @@
+// A past transaction as detection reads it; amounts are in cents, negative for charges.
+export type HistoricalTransactionRequest = {
+    id: number;
+    accountId: number;
+    categoryId: number | null;
+    payee: string;
+    amount: number;
+    date: string;
+    tagIds: number[];
+};
+
+// Names a detected subscription; payees match however their case and spacing differ.
+export type ConfirmSubscriptionRequest = {
+    accountId: number;
+    payee: string;
+};
+
+export type Frequency = 'daily' | 'weekly' | 'monthly' | 'yearly';
+
+// Starts at the next expected charge; `frequency` is null for a one-off transaction.
+export type ScheduledTransactionResponse = {
+    accountId: number;
+    amount: number;
+    startDate: string;
+    endDate: string | null;
+    frequency: Frequency | null;
+    interval: number;
+};
@@ -0,0 +1,7 @@
+import type { AuditOperation } from './audit';
+
+// What the next undo would take back and the next redo would make again; null when nothing.
+export type UndoStatusResponse = {
+    undo: AuditOperation | null;
+    redo: AuditOperation | null;
+};
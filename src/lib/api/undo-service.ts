@@ -0,0 +1,16 @@
+import { invoke } from '@tauri-apps/api/core';
+import type { UndoStatusResponse } from '../api/types/undo';
+
+// The history belongs to the session, so all three need an unlocked one.
+export async function getUndoStatus(): Promise<UndoStatusResponse> {
+    return await invoke<UndoStatusResponse>('get_undo_status');
+}
+
+// Fails with StaleDataError, and forgets the step, when its data was changed some other way since.
+export async function undo(): Promise<UndoStatusResponse> {
+    return await invoke<UndoStatusResponse>('undo');
+}
+
+export async function redo(): Promise<UndoStatusResponse> {
+    return await invoke<UndoStatusResponse>('redo');
+}
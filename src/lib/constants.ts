@@ -4,5 +4,3 @@ export const MAX_USERNAME_LENGTH = 16;
 export const MAX_DISPLAY_NAME_LENGTH = 32;
 
 export const MODAL_CLOSE_ANIMATION_MS = 300;
-
-export const AVATAR_DATA_URI_PREFIX = 'data:image/webp;base64,';
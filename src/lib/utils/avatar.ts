@@ -4,8 +4,12 @@ export function getPlaceholderAvatarUrl(username: string): string {
     return `${AVATAR_PLACEHOLDER_API}?seed=${encodeURIComponent(username)}`;
 }
 
-export function getAvatarUrl(avatar: string | undefined, username: string): string {
-    return avatar ?? getPlaceholderAvatarUrl(username);
+export function getAvatarUrl(avatar: string | undefined, username: string, size?: number): string {
+    if (!avatar) {
+        return getPlaceholderAvatarUrl(username);
+    }
+
+    return size ? `${avatar}&size=${size}` : avatar;
 }
 
 export function createManagedObjectUrl(file: File): { url: string; revoke: () => void } {